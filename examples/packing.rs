@@ -0,0 +1,99 @@
+//! Compares how many glyphs each packing strategy fits into the same texture
+//!
+//! The glyphs are a mix of sizes, like a UI with body text, headings, and titles.
+
+use elefont::rusttype_provider::SizedFont;
+use elefont::{
+    CacheError, FontCache, FontProvider, Glyph, Metrics, NullTexture, Packing, PixelType,
+};
+use rusttype::Font;
+
+/// Serves the same font at several sizes, with the index of the size in the top bits of each
+/// glyph
+struct MixedSizes {
+    sizes: Vec<SizedFont<'static>>,
+}
+
+impl MixedSizes {
+    fn split(glyph: Glyph) -> (usize, Glyph) {
+        ((glyph.0 >> 16) as usize, Glyph(glyph.0 & 0xFFFF))
+    }
+}
+
+impl FontProvider for MixedSizes {
+    fn pixel_type(&self) -> PixelType {
+        PixelType::Alpha
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        self.sizes[0].single_glyph(character)
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        self.sizes[0].glyphs(string, glyphs);
+    }
+
+    fn line_height(&self) -> f32 {
+        self.sizes[0].line_height()
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        let (size, glyph) = Self::split(glyph);
+        self.sizes[size].metrics(glyph)
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        let (size, glyph) = Self::split(glyph);
+        self.sizes[size].rasterize(glyph)
+    }
+}
+
+fn main() {
+    let font_data = include_bytes!("DejaVuSans.ttf");
+    let font = Font::from_bytes(font_data as &[u8]).expect("Error constructing Font");
+    let sizes = [12.0, 16.0, 24.0, 36.0, 64.0];
+    let characters: Vec<char> = ('!'..='~').chain('А'..='я').collect();
+
+    // Every character at every size, shuffled so the sizes are mixed together like real text
+    let mut glyphs = Vec::new();
+    for index in 0..sizes.len() {
+        for character in characters.iter() {
+            let glyph = font.glyph(*character).id().0;
+            glyphs.push(Glyph(((index as u32) << 16) | glyph));
+        }
+    }
+    glyphs.sort_by_key(|glyph| glyph.0.wrapping_mul(2_654_435_761));
+
+    let strategies = [
+        Packing::Shelf,
+        Packing::ShelfBucketed { bucket: 8 },
+        Packing::Skyline,
+        Packing::MaxRects,
+    ];
+    for packing in strategies.iter() {
+        let provider = MixedSizes {
+            sizes: sizes
+                .iter()
+                .map(|size| SizedFont::new(font.clone(), *size))
+                .collect(),
+        };
+        let mut cache =
+            FontCache::with_packing(Box::new(provider), NullTexture::new(512, 512), *packing);
+        let mut placed = 0;
+        for glyph in glyphs.iter() {
+            match cache.render_glyph(*glyph) {
+                Ok(_) => placed += 1,
+                Err(CacheError::OutOfSpace { .. }) => break,
+                Err(err) => panic!("Failed to render a glyph: {:?}", err),
+            }
+        }
+        let stats = cache.stats();
+        println!(
+            "{:?}: {} of {} glyphs, {:.1}% of the texture covered",
+            packing,
+            placed,
+            glyphs.len(),
+            stats.used_pixels as f64 / stats.texture_pixels as f64 * 100.0
+        );
+    }
+}
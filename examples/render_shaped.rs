@@ -0,0 +1,49 @@
+use elefont::swash_provider::SizedFont;
+use elefont::{FontCache, Glyph, NullTexture, ShapedGlyph};
+use rustybuzz::{Face, UnicodeBuffer};
+use swash::FontRef;
+
+fn main() {
+    let font_data = include_bytes!("DejaVuSans.ttf");
+    let size = 24.0;
+
+    // Shape the text with rustybuzz, which handles ligatures, kerning, and complex scripts
+    let face = Face::from_slice(font_data, 0).expect("Error constructing Face");
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str("office affluent Vä");
+    let output = rustybuzz::shape(&face, &[], buffer);
+
+    // rustybuzz positions glyphs in font units, with y increasing upwards
+    let scale = size / face.units_per_em() as f32;
+    let mut pen_x = 0.0;
+    let mut pen_y = 0.0;
+    let shaped: Vec<_> = output
+        .glyph_infos()
+        .iter()
+        .zip(output.glyph_positions())
+        .map(|(info, position)| {
+            let glyph = ShapedGlyph {
+                glyph: Glyph(info.glyph_id),
+                x: pen_x + position.x_offset as f32 * scale,
+                y: pen_y - position.y_offset as f32 * scale,
+            };
+            pen_x += position.x_advance as f32 * scale;
+            pen_y -= position.y_advance as f32 * scale;
+            glyph
+        })
+        .collect();
+
+    // The provider has to use the same font, at the size the positions were scaled to
+    let font = FontRef::from_index(font_data, 0).expect("Error constructing FontRef");
+    let font = SizedFont::new(font, size);
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(256, 256));
+    for glyph in cache
+        .render_shaped(&shaped)
+        .expect("Failed to render glyphs")
+    {
+        println!(
+            "{:?} at ({}, {}), stored at {:?}",
+            glyph.texture_glyph.glyph, glyph.x, glyph.y, glyph.texture_glyph.bounds
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use super::*;
+
+use ab_glyph::{point, Font, FontRef, GlyphId, OutlinedGlyph, PxScale, ScaleFont};
+
+#[derive(Clone)]
+pub struct SizedFont<F: Font> {
+    font: F,
+    size: f32,
+    scale_factor: f32,
+    hinting: bool,
+}
+
+impl<F: Font> SizedFont<F> {
+    pub fn new(font: F, size: f32) -> Self {
+        SizedFont {
+            font,
+            size,
+            scale_factor: 1.0,
+            hinting: false,
+        }
+    }
+
+    pub fn with_size(&self, size: f32) -> Self
+    where
+        F: Clone,
+    {
+        SizedFont {
+            size,
+            ..self.clone()
+        }
+    }
+
+    pub fn font(&self) -> &F {
+        &self.font
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// Create a copy of the font that rasterizes at `scale_factor` times its size, for HiDPI
+    /// displays
+    ///
+    /// Line metrics, advances, bearings and kerning stay in logical pixels, at the font's size.
+    /// The bounds of glyphs, their phases and their data are in physical pixels, so a glyph with
+    /// its pen at `x` logical pixels is drawn at `x * scale_factor + bounds.x` physical pixels.
+    pub fn with_scale_factor(&self, scale_factor: f32) -> Self
+    where
+        F: Clone,
+    {
+        SizedFont {
+            scale_factor,
+            ..self.clone()
+        }
+    }
+
+    /// Create a copy of the font with hinting turned on (or off)
+    ///
+    /// ab_glyph can't hint glyphs, so they're always rendered unhinted and this only records the
+    /// setting, which lets code that configures fonts work the same with every provider. Use the
+    /// swash provider for hinted glyphs.
+    pub fn with_hinting(&self, hinting: bool) -> Self
+    where
+        F: Clone,
+    {
+        SizedFont {
+            hinting,
+            ..self.clone()
+        }
+    }
+
+    pub fn hinting(&self) -> bool {
+        self.hinting
+    }
+
+    fn scale(&self) -> PxScale {
+        PxScale::from(self.size)
+    }
+
+    /// The scale glyphs are rasterized at, in physical pixels
+    fn pixel_scale(&self) -> PxScale {
+        PxScale::from(self.size * self.scale_factor)
+    }
+
+    /// The outline of a glyph with its origin at the given position
+    fn outline_at(&self, glyph: Glyph, x: f32, y: f32) -> Option<OutlinedGlyph> {
+        let id = GlyphId(glyph.0 as u16);
+        self.font
+            .outline_glyph(id.with_scale_and_position(self.pixel_scale(), point(x, y)))
+    }
+}
+
+impl<'a> SizedFont<FontRef<'a>> {
+    /// Parse a font file, returning [`CacheError::InvalidFont`] if it isn't a valid font
+    pub fn from_bytes(data: &'a [u8], size: f32) -> Result<Self, CacheError> {
+        Self::from_bytes_indexed(data, 0, size)
+    }
+
+    /// Parse one of the fonts in a font collection (like a .ttc file)
+    ///
+    /// For a file with a single font, the only index is 0. Returns [`CacheError::InvalidFont`]
+    /// if the data isn't valid, or doesn't have a font at that index.
+    pub fn from_bytes_indexed(data: &'a [u8], index: u32, size: f32) -> Result<Self, CacheError> {
+        let font =
+            FontRef::try_from_slice_and_index(data, index).map_err(|_| CacheError::InvalidFont)?;
+
+        Ok(SizedFont::new(font, size))
+    }
+}
+
+fn outline_bounds(outline: &OutlinedGlyph) -> Bounds {
+    let shape = outline.px_bounds();
+    Bounds {
+        x: shape.min.x as i32,
+        y: shape.min.y as i32,
+        width: shape.width() as u32,
+        height: shape.height() as u32,
+    }
+}
+
+impl<F: Font + Send + Sync> FontProvider for SizedFont<F> {
+    fn line_height(&self) -> f32 {
+        let font = self.font.as_scaled(self.scale());
+
+        font.ascent() - font.descent() + font.line_gap()
+    }
+
+    fn ascent(&self) -> f32 {
+        self.font.as_scaled(self.scale()).ascent()
+    }
+
+    fn descent(&self) -> f32 {
+        self.font.as_scaled(self.scale()).descent()
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn units_per_em(&self) -> u16 {
+        self.font.units_per_em().map_or(1000, |units| units as u16)
+    }
+
+    fn unscaled_advance(&self, glyph: Glyph) -> Option<f32> {
+        Some(self.font.h_advance_unscaled(GlyphId(glyph.0 as u16)))
+    }
+
+    fn pixel_type(&self) -> PixelType {
+        PixelType::Alpha
+    }
+
+    fn single_glyph(&self, c: char) -> Glyph {
+        Glyph(self.font.glyph_id(c).0 as u32)
+    }
+
+    fn covered_chars(&self, out: &mut Vec<char>) {
+        out.extend(
+            self.font
+                .codepoint_ids()
+                .filter(|(id, _)| id.0 != 0)
+                .map(|(_, c)| c),
+        );
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        char_glyphs(string, glyphs, None, |c| self.single_glyph(c));
+    }
+
+    fn glyphs_with_clusters(
+        &self,
+        string: &str,
+        glyphs: &mut Vec<Glyph>,
+        clusters: &mut Vec<Range<usize>>,
+    ) {
+        char_glyphs(string, glyphs, Some(clusters), |c| self.single_glyph(c));
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        let font = self.font.as_scaled(self.scale());
+        let id = GlyphId(glyph.0 as u16);
+        let bounds = self
+            .outline_at(glyph, 0.0, 0.0)
+            .map(|outline| outline_bounds(&outline));
+        let bounds_f = self.font.outline(id).map(|outline| {
+            let shape = outline.bounds;
+            let physical = self.font.as_scaled(self.pixel_scale());
+            let (x_scale, y_scale) = (physical.h_scale_factor(), physical.v_scale_factor());
+            RectF {
+                x: shape.min.x * x_scale,
+                // Outlines are measured upwards from the baseline, with `min.y` at the top
+                y: -shape.min.y * y_scale,
+                width: shape.width() * x_scale,
+                height: (shape.min.y - shape.max.y) * y_scale,
+            }
+        });
+
+        Metrics {
+            bounds,
+            bounds_f,
+            bearing_x: font.h_side_bearing(id),
+            advance_x: font.h_advance(id),
+            bearing_y: 0.0,
+            advance_y: 0.0,
+        }
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        self.rasterize_phased(glyph, 0.0, 0.0)
+    }
+
+    fn bounds_phased(&self, glyph: Glyph, x_phase: f32, y_phase: f32) -> Option<Bounds> {
+        self.outline_at(glyph, x_phase, y_phase)
+            .map(|outline| outline_bounds(&outline))
+    }
+
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        x_phase: f32,
+        y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        let outline = self
+            .outline_at(glyph, x_phase, y_phase)
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+        let bounds = outline.px_bounds();
+        let width = bounds.width() as u32;
+        let mut buffer = alloc::vec![0u8; (width * bounds.height() as u32) as usize];
+        outline.draw(|x, y, val| buffer[(x + y * width) as usize] = (val * 255.0) as u8);
+
+        Ok(buffer)
+    }
+
+    fn kerning(&self, a: Glyph, b: Glyph) -> f32 {
+        self.font
+            .as_scaled(self.scale())
+            .kern(GlyphId(a.0 as u16), GlyphId(b.0 as u16))
+    }
+}
@@ -0,0 +1,168 @@
+use super::*;
+
+use core::future::Future;
+use core::pin::Pin;
+
+/// The future returned by [`FontProvider::rasterize_async`]
+///
+/// It can't borrow the provider, so providers that rasterize somewhere else (like a web worker)
+/// should give it everything it needs up front.
+pub type RasterizeFuture = Pin<Box<dyn Future<Output = Result<Vec<u8>, CacheError>>>>;
+
+/// The glyphs of a string rasterized by [`FontCache::rasterize_string_async`], ready to be
+/// written to the texture by [`FontCache::insert_rasterized`]
+pub struct RasterizedGlyphs {
+    glyphs: Vec<(Glyph, Metrics, Result<Vec<u8>, CacheError>)>,
+    /// The glyphs of the string that were already cached, which are marked as used when
+    /// inserting so they aren't evicted for the new ones
+    cached: Vec<GlyphKey>,
+    /// Whether the string had characters the font doesn't have, which are left to the
+    /// [`MissingGlyphPolicy`]
+    missing: bool,
+}
+
+impl RasterizedGlyphs {
+    /// How many glyphs were rasterized
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+}
+
+impl<T: Texture> FontCache<T> {
+    /// Start rasterizing the glyphs of a string that aren't cached yet, without touching the
+    /// texture
+    ///
+    /// The string is prepared like in [`render_string`], and each new glyph is rasterized with
+    /// [`FontProvider::rasterize_async`]. Every glyph's future is created before the first one
+    /// is awaited, so providers that start their work when called can rasterize them all at
+    /// once. The returned future doesn't borrow the cache, so it can be spawned on any executor
+    /// (like `wasm_bindgen_futures::spawn_local`) while the cache keeps drawing the glyphs it
+    /// already has, and its result is then passed to [`insert_rasterized`].
+    ///
+    /// [`render_string`]: FontCache::render_string
+    /// [`insert_rasterized`]: FontCache::insert_rasterized
+    pub fn rasterize_string_async(
+        &mut self,
+        string: &str,
+    ) -> impl Future<Output = RasterizedGlyphs> + 'static {
+        let string = self.prepare_string(string);
+        let cache = &mut self.cache;
+        let glyph_buffer = &mut self.glyph_buffer;
+        cache.font.glyphs(&string, glyph_buffer);
+        let mut seen = HashMap::new();
+        let notdef = cache.font.notdef();
+        let mut missing = false;
+        let mut cached = Vec::new();
+        let pending: Vec<_> = glyph_buffer
+            .drain(..)
+            .filter(|glyph| {
+                let key = GlyphKey::new(*glyph);
+                let is_cached = cache.map.contains_key(&key);
+                if is_cached {
+                    cached.push(key);
+                }
+                !is_cached
+            })
+            .filter(|glyph| {
+                let render = *glyph != notdef || cache.missing_glyph == MissingGlyphPolicy::Render;
+                missing |= !render;
+                render
+            })
+            .filter(|glyph| seen.insert(*glyph, ()).is_none())
+            .filter_map(|glyph| {
+                let metrics = cache.font.metrics(glyph);
+                metrics.bounds?;
+                let future = cache.font.rasterize_async(glyph);
+                Some((glyph, metrics, future))
+            })
+            .collect();
+
+        async move {
+            let mut glyphs = Vec::with_capacity(pending.len());
+            for (glyph, metrics, future) in pending {
+                glyphs.push((glyph, metrics, future.await));
+            }
+
+            RasterizedGlyphs {
+                glyphs,
+                cached,
+                missing,
+            }
+        }
+    }
+
+    /// Write glyphs rasterized with [`rasterize_string_async`] to the texture
+    ///
+    /// This is where the texture changes, so it always happens all at once, on the thread that
+    /// owns the cache. Glyphs are packed in the order they appear in the string, so they end up
+    /// in the same places as they would with [`cache_string`], however long rasterizing took.
+    /// Results from several strings can be inserted in any order: glyphs that were cached in
+    /// the meantime (by another insert, or by rendering them synchronously) are skipped, so a
+    /// glyph is never stored twice. The glyphs of the string that were already cached when it
+    /// was rasterized are marked as used, so like with [`cache_string`], they aren't evicted to
+    /// make room for its new ones. If any glyph failed to rasterize or doesn't fit, the first
+    /// error is returned, after the glyphs before it have been cached.
+    ///
+    /// The glyphs are only meaningful to the font they were rasterized with, so results that
+    /// were started before [`FontCache::replace_font`] should be dropped instead of inserted.
+    ///
+    /// [`rasterize_string_async`]: FontCache::rasterize_string_async
+    /// [`cache_string`]: FontCache::cache_string
+    pub fn insert_rasterized(&mut self, rasterized: RasterizedGlyphs) -> Result<(), CacheError> {
+        let cache = &mut self.cache;
+        cache.start_window();
+        for key in rasterized.cached {
+            cache.touch(key);
+        }
+        for (glyph, metrics, data) in rasterized.glyphs {
+            let key = GlyphKey::new(glyph);
+            if cache.map.contains_key(&key) {
+                continue;
+            }
+            let data = data?;
+            let bounds = metrics
+                .bounds
+                .expect("Glyphs without bounds aren't rasterized");
+            let pixel_type = cache.font.glyph_pixel_type(glyph);
+            check_length(&bounds, pixel_type, &data)?;
+            cache.clock += 1;
+            let placement = cache.allocate(glyph, &bounds)?;
+            cache.store(key, metrics, placement, pixel_type, &data[..]);
+        }
+        if rasterized.missing {
+            let notdef = cache.font.notdef();
+            cache.render_glyph(notdef)?;
+        }
+
+        Ok(())
+    }
+
+    /// Render a string like [`render_string`], waiting for its new glyphs to be rasterized with
+    /// [`FontProvider::rasterize_async`]
+    ///
+    /// This is [`rasterize_string_async`] followed by [`insert_rasterized`], after which every
+    /// glyph of the string is cached. The cache is borrowed until the future completes, so
+    /// nothing else can draw with it in the meantime. To keep drawing while glyphs are
+    /// rasterized, call those two methods yourself instead: spawn the future from
+    /// [`rasterize_string_async`], draw placeholders (or nothing) for the glyphs that aren't
+    /// cached yet, and pass its result to [`insert_rasterized`] once it's done. With the default
+    /// [`rasterize_async`](FontProvider::rasterize_async), this rasterizes synchronously and
+    /// completes the first time it's polled.
+    ///
+    /// [`render_string`]: FontCache::render_string
+    /// [`rasterize_string_async`]: FontCache::rasterize_string_async
+    /// [`insert_rasterized`]: FontCache::insert_rasterized
+    pub async fn render_string_async(
+        &mut self,
+        string: &str,
+    ) -> Result<Vec<(Metrics, TextureGlyph)>, CacheError> {
+        let rasterized = self.rasterize_string_async(string).await;
+        self.insert_rasterized(rasterized)?;
+
+        self.render_string(string).collect()
+    }
+}
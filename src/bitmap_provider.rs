@@ -0,0 +1,157 @@
+use super::*;
+
+/// A single character in a [`BitmapFontProvider`]'s sprite sheet
+#[derive(Copy, Clone, Debug)]
+pub struct BitmapGlyph {
+    /// The character this glyph draws
+    pub character: char,
+    /// Where the glyph's pixels are in the sprite sheet
+    pub source: Bounds,
+    /// The position of the top-left of the glyph relative to the pen, the same as
+    /// [`Metrics::bounds`]
+    pub offset: (i32, i32),
+    /// How far to move the pen after drawing the glyph
+    pub advance: f32,
+}
+
+/// A font made of pre-rendered glyphs on a sprite sheet (like one exported by BMFont)
+///
+/// Because the glyphs are already rendered, rasterizing one just copies its pixels out of the
+/// sheet. Each character maps to one glyph, and characters that aren't in the sheet map to
+/// [`Glyph::NOTDEF`], which doesn't draw anything.
+pub struct BitmapFontProvider {
+    pixel_type: PixelType,
+    width: u32,
+    pixels: Vec<u8>,
+    line_height: f32,
+    glyphs: Vec<BitmapGlyph>,
+    characters: HashMap<char, Glyph>,
+}
+
+impl BitmapFontProvider {
+    /// Create a font from a sprite sheet and the glyphs on it
+    ///
+    /// The pixels should be stored row by row, in the given format, with no padding between
+    /// rows.
+    ///
+    /// # Panics
+    ///
+    /// If `pixels` is the wrong size for the sheet, or a glyph's source isn't inside the sheet
+    pub fn new(
+        pixel_type: PixelType,
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+        line_height: f32,
+        glyphs: impl IntoIterator<Item = BitmapGlyph>,
+    ) -> Self {
+        let bytes_per_pixel = pixel_type.bytes_per_pixel() as u32;
+        assert_eq!(pixels.len(), (width * height * bytes_per_pixel) as usize);
+        let glyphs: Vec<_> = glyphs.into_iter().collect();
+        let mut characters = HashMap::new();
+        for (index, glyph) in glyphs.iter().enumerate() {
+            let source = &glyph.source;
+            assert!(source.x >= 0 && source.y >= 0);
+            assert!(source.x as u32 + source.width <= width);
+            assert!(source.y as u32 + source.height <= height);
+            characters.insert(glyph.character, Glyph(index as u32 + 1));
+        }
+
+        BitmapFontProvider {
+            pixel_type,
+            width,
+            pixels,
+            line_height,
+            glyphs,
+            characters,
+        }
+    }
+
+    /// Look up the glyph that a [`Glyph`] id refers to
+    pub fn glyph(&self, glyph: Glyph) -> Option<&BitmapGlyph> {
+        let index = glyph.0.checked_sub(1)?;
+        self.glyphs.get(index as usize)
+    }
+}
+
+impl FontProvider for BitmapFontProvider {
+    fn pixel_type(&self) -> PixelType {
+        self.pixel_type
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        self.characters
+            .get(&character)
+            .copied()
+            .unwrap_or(Glyph::NOTDEF)
+    }
+
+    fn covered_chars(&self, out: &mut Vec<char>) {
+        out.extend(self.characters.keys());
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        glyphs.extend(string.chars().map(|c| self.single_glyph(c)));
+    }
+
+    fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        match self.glyph(glyph) {
+            Some(glyph) => {
+                let bounds = if glyph.source.width > 0 && glyph.source.height > 0 {
+                    Some(Bounds {
+                        x: glyph.offset.0,
+                        y: glyph.offset.1,
+                        width: glyph.source.width,
+                        height: glyph.source.height,
+                    })
+                } else {
+                    None
+                };
+
+                Metrics {
+                    bounds,
+                    bounds_f: bounds.map(RectF::from),
+                    bearing_x: glyph.offset.0 as f32,
+                    advance_x: glyph.advance,
+                    bearing_y: 0.0,
+                    advance_y: 0.0,
+                }
+            }
+            None => Metrics {
+                bounds: None,
+                bounds_f: None,
+                bearing_x: 0.0,
+                advance_x: 0.0,
+                bearing_y: 0.0,
+                advance_y: 0.0,
+            },
+        }
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        let mut data = Vec::new();
+        self.rasterize_into(glyph, &mut data)?;
+
+        Ok(data)
+    }
+
+    fn rasterize_into(&self, glyph: Glyph, out: &mut Vec<u8>) -> Result<(), CacheError> {
+        let source = match self.glyph(glyph) {
+            Some(bitmap) if bitmap.source.width > 0 && bitmap.source.height > 0 => bitmap.source,
+            _ => return Err(CacheError::NonRenderableGlyph(glyph)),
+        };
+        let bytes_per_pixel = self.pixel_type.bytes_per_pixel() as u32;
+        let row_length = (source.width * bytes_per_pixel) as usize;
+        out.reserve(row_length * source.height as usize);
+        for y in source.y as u32..source.y as u32 + source.height {
+            let start = ((source.x as u32 + y * self.width) * bytes_per_pixel) as usize;
+            out.extend_from_slice(&self.pixels[start..start + row_length]);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use super::*;
+
+/// Configures a [`FontCache`] before creating it
+///
+/// Start with [`FontCache::builder`], chain any of the setters, and finish with
+/// [`build`](FontCacheBuilder::build). Every option that isn't set keeps the same default as
+/// [`FontCache::new`].
+pub struct FontCacheBuilder<T: Texture> {
+    font: Box<dyn FontProvider>,
+    texture: T,
+    new_page: Option<Box<dyn Fn() -> T + Send + Sync>>,
+    padding: u32,
+    packing: Packing,
+    allow_rotation: bool,
+    max_glyph_dimensions: Option<(u32, u32)>,
+    missing_glyph: MissingGlyphPolicy,
+    origin: TextureOrigin,
+    eviction: EvictionPolicy,
+    normalization: Option<NormalizationForm>,
+    keep_whitespace: bool,
+    shaping_cache: usize,
+}
+
+impl<T: Texture> FontCacheBuilder<T> {
+    /// Start configuring a cache that pulls from the given provider and renders to the provided
+    /// texture
+    pub fn new(font: Box<dyn FontProvider>, texture: T) -> Self {
+        FontCacheBuilder {
+            font,
+            texture,
+            new_page: None,
+            padding: 1,
+            packing: Packing::Shelf,
+            allow_rotation: false,
+            max_glyph_dimensions: None,
+            missing_glyph: MissingGlyphPolicy::Render,
+            origin: TextureOrigin::TopLeft,
+            eviction: EvictionPolicy::Never,
+            normalization: if cfg!(feature = "unicode-normalization") {
+                Some(NormalizationForm::Nfc)
+            } else {
+                None
+            },
+            keep_whitespace: false,
+            shaping_cache: 0,
+        }
+    }
+
+    /// How many pixels to leave empty between neighboring glyphs (1 by default)
+    ///
+    /// See [`FontCache::set_padding`].
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// How to place glyphs in the texture ([`Packing::Shelf`] by default)
+    pub fn packing(mut self, packing: Packing) -> Self {
+        self.packing = packing;
+        self
+    }
+
+    /// Whether glyphs can be stored turned sideways when they fit better that way (off by
+    /// default)
+    ///
+    /// See [`FontCache::set_allow_rotation`].
+    pub fn allow_rotation(mut self, allow_rotation: bool) -> Self {
+        self.allow_rotation = allow_rotation;
+        self
+    }
+
+    /// The largest width and height of a glyph the cache will store (no limit by default)
+    ///
+    /// See [`FontCache::set_max_glyph_dimensions`].
+    pub fn max_glyph_dimensions(mut self, max: Option<(u32, u32)>) -> Self {
+        self.max_glyph_dimensions = max;
+        self
+    }
+
+    /// What to do with characters the font doesn't have ([`MissingGlyphPolicy::Render`] by
+    /// default)
+    pub fn missing_glyph_policy(mut self, policy: MissingGlyphPolicy) -> Self {
+        self.missing_glyph = policy;
+        self
+    }
+
+    /// Which corner of the texture glyph positions start from ([`TextureOrigin::TopLeft`] by
+    /// default)
+    pub fn texture_origin(mut self, origin: TextureOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// What to do when the texture fills up ([`EvictionPolicy::Never`] by default)
+    pub fn eviction(mut self, eviction: EvictionPolicy) -> Self {
+        self.eviction = eviction;
+        self
+    }
+
+    /// Whether to normalize strings before converting them to glyphs (on by default)
+    ///
+    /// This only has an effect if the "unicode-normalization" feature is activated, in which case
+    /// strings are normalized to [`NormalizationForm::Nfc`]. To pick another form, use
+    /// [`normalization`](FontCacheBuilder::normalization).
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalization = if normalize && cfg!(feature = "unicode-normalization") {
+            Some(NormalizationForm::Nfc)
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Which form to normalize strings to before converting them to glyphs
+    ///
+    /// See [`FontCache::set_normalization`].
+    ///
+    /// # Panics
+    ///
+    /// If a form is given without the "unicode-normalization" feature
+    pub fn normalization(mut self, normalization: Option<NormalizationForm>) -> Self {
+        assert!(
+            cfg!(feature = "unicode-normalization") || normalization.is_none(),
+            "Normalization requires the unicode-normalization feature"
+        );
+        self.normalization = normalization;
+        self
+    }
+
+    /// Whether to keep whitespace characters when rendering strings (off by default)
+    ///
+    /// See [`FontCache::set_keep_whitespace`].
+    pub fn keep_whitespace(mut self, keep_whitespace: bool) -> Self {
+        self.keep_whitespace = keep_whitespace;
+        self
+    }
+
+    /// How many recently rendered strings to remember the glyphs of (0 by default)
+    ///
+    /// See [`FontCache::set_shaping_cache`].
+    pub fn shaping_cache(mut self, capacity: usize) -> Self {
+        self.shaping_cache = capacity;
+        self
+    }
+
+    /// Create another texture page with `new_page` whenever the existing pages are full
+    ///
+    /// See [`FontCache::with_pages`].
+    pub fn pages(mut self, new_page: impl 'static + Send + Sync + Fn() -> T) -> Self {
+        self.new_page = Some(Box::new(new_page));
+        self
+    }
+
+    pub fn build(self) -> FontCache<T> {
+        FontCache {
+            glyph_buffer: Vec::new(),
+            cluster_buffer: Vec::new(),
+            keep_whitespace: self.keep_whitespace,
+            normalization: self.normalization,
+            shaping: ShapingCache::new(self.shaping_cache),
+            cache: Cache {
+                font: self.font,
+                extra_fonts: Vec::new(),
+                pages: alloc::vec![Page::new(self.texture, self.padding, self.packing)],
+                new_page: self.new_page,
+                map: HashMap::new(),
+                padding: self.padding,
+                packing: self.packing,
+                allow_rotation: self.allow_rotation,
+                max_glyph_dimensions: self.max_glyph_dimensions,
+                missing_glyph: self.missing_glyph,
+                origin: self.origin,
+                eviction: self.eviction,
+                clock: 0,
+                window_start: 0,
+                misses: 0,
+                scratch: Vec::new(),
+            },
+        }
+    }
+}
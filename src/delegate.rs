@@ -0,0 +1,167 @@
+/// Implement the named [`FontProvider`](crate::FontProvider) methods by passing them straight
+/// through to the `inner` provider of a wrapper
+///
+/// This goes inside the wrapper's `impl FontProvider` block, next to the methods it changes:
+///
+/// ```ignore
+/// impl<P: FontProvider> FontProvider for Wrapper<P> {
+///     delegate_to_inner!(pixel_type, single_glyph, glyphs, line_height, metrics);
+///
+///     fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+///         // ...
+///     }
+/// }
+/// ```
+macro_rules! delegate_to_inner {
+    ($($method:ident),* $(,)?) => {
+        $(delegate_to_inner!(@method $method);)*
+    };
+    (@method pixel_type) => {
+        fn pixel_type(&self) -> $crate::PixelType {
+            self.inner.pixel_type()
+        }
+    };
+    (@method glyph_pixel_type) => {
+        fn glyph_pixel_type(&self, glyph: $crate::Glyph) -> $crate::PixelType {
+            self.inner.glyph_pixel_type(glyph)
+        }
+    };
+    (@method is_color_glyph) => {
+        fn is_color_glyph(&self, glyph: $crate::Glyph) -> bool {
+            self.inner.is_color_glyph(glyph)
+        }
+    };
+    (@method single_glyph) => {
+        fn single_glyph(&self, character: char) -> $crate::Glyph {
+            self.inner.single_glyph(character)
+        }
+    };
+    (@method has_glyph) => {
+        fn has_glyph(&self, character: char) -> bool {
+            self.inner.has_glyph(character)
+        }
+    };
+    (@method covered_chars) => {
+        fn covered_chars(&self, out: &mut alloc::vec::Vec<char>) {
+            self.inner.covered_chars(out);
+        }
+    };
+    (@method notdef) => {
+        fn notdef(&self) -> $crate::Glyph {
+            self.inner.notdef()
+        }
+    };
+    (@method glyphs) => {
+        fn glyphs(&self, string: &str, glyphs: &mut alloc::vec::Vec<$crate::Glyph>) {
+            self.inner.glyphs(string, glyphs);
+        }
+    };
+    (@method glyphs_directional) => {
+        fn glyphs_directional(
+            &self,
+            string: &str,
+            direction: $crate::Direction,
+            glyphs: &mut alloc::vec::Vec<$crate::Glyph>,
+        ) {
+            self.inner.glyphs_directional(string, direction, glyphs);
+        }
+    };
+    (@method glyphs_with_clusters) => {
+        fn glyphs_with_clusters(
+            &self,
+            string: &str,
+            glyphs: &mut alloc::vec::Vec<$crate::Glyph>,
+            clusters: &mut alloc::vec::Vec<core::ops::Range<usize>>,
+        ) {
+            self.inner.glyphs_with_clusters(string, glyphs, clusters);
+        }
+    };
+    (@method line_height) => {
+        fn line_height(&self) -> f32 {
+            self.inner.line_height()
+        }
+    };
+    (@method ascent) => {
+        fn ascent(&self) -> f32 {
+            self.inner.ascent()
+        }
+    };
+    (@method descent) => {
+        fn descent(&self) -> f32 {
+            self.inner.descent()
+        }
+    };
+    (@method vertical_line_width) => {
+        fn vertical_line_width(&self) -> f32 {
+            self.inner.vertical_line_width()
+        }
+    };
+    (@method scale_factor) => {
+        fn scale_factor(&self) -> f32 {
+            self.inner.scale_factor()
+        }
+    };
+    (@method units_per_em) => {
+        fn units_per_em(&self) -> u16 {
+            self.inner.units_per_em()
+        }
+    };
+    (@method unscaled_advance) => {
+        fn unscaled_advance(&self, glyph: $crate::Glyph) -> Option<f32> {
+            self.inner.unscaled_advance(glyph)
+        }
+    };
+    (@method metrics) => {
+        fn metrics(&self, glyph: $crate::Glyph) -> $crate::Metrics {
+            self.inner.metrics(glyph)
+        }
+    };
+    (@method rasterize) => {
+        fn rasterize(
+            &self,
+            glyph: $crate::Glyph,
+        ) -> Result<alloc::vec::Vec<u8>, $crate::CacheError> {
+            self.inner.rasterize(glyph)
+        }
+    };
+    (@method rasterize_into) => {
+        fn rasterize_into(
+            &self,
+            glyph: $crate::Glyph,
+            out: &mut alloc::vec::Vec<u8>,
+        ) -> Result<(), $crate::CacheError> {
+            self.inner.rasterize_into(glyph, out)
+        }
+    };
+    (@method rasterize_async) => {
+        #[cfg(feature = "async")]
+        fn rasterize_async(&self, glyph: $crate::Glyph) -> $crate::RasterizeFuture {
+            self.inner.rasterize_async(glyph)
+        }
+    };
+    (@method bounds_phased) => {
+        fn bounds_phased(
+            &self,
+            glyph: $crate::Glyph,
+            x_phase: f32,
+            y_phase: f32,
+        ) -> Option<$crate::Bounds> {
+            self.inner.bounds_phased(glyph, x_phase, y_phase)
+        }
+    };
+    (@method rasterize_phased) => {
+        fn rasterize_phased(
+            &self,
+            glyph: $crate::Glyph,
+            x_phase: f32,
+            y_phase: f32,
+        ) -> Result<alloc::vec::Vec<u8>, $crate::CacheError> {
+            self.inner.rasterize_phased(glyph, x_phase, y_phase)
+        }
+    };
+    (@method kerning) => {
+        fn kerning(&self, a: $crate::Glyph, b: $crate::Glyph) -> f32 {
+            self.inner.kerning(a, b)
+        }
+    };
+}
@@ -0,0 +1,304 @@
+use super::*;
+
+/// Combines several fonts, using each character from the first font that has it
+///
+/// This is useful when the main font doesn't cover every character the application needs, like
+/// CJK characters or emoji. Each character is looked up in the providers in order, and the first
+/// one whose [`has_glyph`](FontProvider::has_glyph) returns true is used for it. Characters that
+/// no provider has use the first provider's [`notdef`](FontProvider::notdef) glyph.
+///
+/// # Glyph ids
+///
+/// Glyph ids only mean something to the font that produced them, so the provider's index is
+/// stored in the top 8 bits of each glyph, and the sub-font's own id in the bottom 24 bits (see
+/// [`FallbackProvider::encode`]). The first provider's glyphs keep their original ids. This
+/// limits the chain to 256 providers, with glyph ids below 2<sup>24</sup>, which every TrueType
+/// and OpenType font stays under. Glyphs with larger ids (like the glyphs a nested chain
+/// encodes for its later providers) are replaced by the chain's notdef glyph, so chains should
+/// be flattened into one instead of nested.
+///
+/// The line metrics (like the line height and ascent) and the
+/// [`scale_factor`](FontProvider::scale_factor) are the first provider's, so every provider
+/// should use the same scale factor. Glyphs are only kerned against glyphs from the same
+/// provider. If the providers produce different pixel types, [`pixel_type`] is the first
+/// provider's and [`glyph_pixel_type`] reports the type of each glyph.
+///
+/// [`pixel_type`]: FontProvider::pixel_type
+/// [`glyph_pixel_type`]: FontProvider::glyph_pixel_type
+pub struct FallbackProvider {
+    providers: Vec<Box<dyn FontProvider>>,
+}
+
+const INDEX_SHIFT: u32 = 24;
+const GLYPH_MASK: u32 = (1 << INDEX_SHIFT) - 1;
+const MAX_PROVIDERS: usize = 1 << (32 - INDEX_SHIFT);
+
+impl FallbackProvider {
+    /// Create a chain of providers, from the most preferred to the least
+    ///
+    /// # Panics
+    ///
+    /// If there are no providers, or more than 256
+    pub fn new(providers: Vec<Box<dyn FontProvider>>) -> Self {
+        assert!(!providers.is_empty());
+        assert!(providers.len() <= MAX_PROVIDERS);
+
+        FallbackProvider { providers }
+    }
+
+    pub fn providers(&self) -> &[Box<dyn FontProvider>] {
+        &self.providers[..]
+    }
+
+    /// Combine the index of a provider and one of its glyphs into a glyph of the chain
+    ///
+    /// # Panics
+    ///
+    /// If the index is 256 or more, or the glyph doesn't fit in 24 bits
+    pub fn encode(provider: usize, glyph: Glyph) -> Glyph {
+        assert!(provider < MAX_PROVIDERS);
+        assert!(glyph.0 <= GLYPH_MASK);
+
+        Glyph(((provider as u32) << INDEX_SHIFT) | glyph.0)
+    }
+
+    /// Split a glyph of the chain into the index of its provider and the provider's own glyph
+    pub fn decode(glyph: Glyph) -> (usize, Glyph) {
+        (
+            (glyph.0 >> INDEX_SHIFT) as usize,
+            Glyph(glyph.0 & GLYPH_MASK),
+        )
+    }
+
+    /// The glyph of the chain for one of a provider's glyphs, or the chain's notdef glyph if its
+    /// id doesn't fit in 24 bits
+    fn chain_glyph(&self, provider: usize, glyph: Glyph) -> Glyph {
+        if glyph.0 <= GLYPH_MASK {
+            Self::encode(provider, glyph)
+        } else {
+            self.notdef()
+        }
+    }
+
+    /// The index of the provider to use for a character
+    fn provider_for(&self, character: char) -> usize {
+        self.providers
+            .iter()
+            .position(|provider| provider.has_glyph(character))
+            .unwrap_or(0)
+    }
+
+    /// Look up the provider a glyph came from, along with its own glyph
+    fn route(&self, glyph: Glyph) -> Option<(&dyn FontProvider, Glyph)> {
+        let (index, inner) = Self::decode(glyph);
+        let provider = self.providers.get(index)?;
+
+        Some((provider.as_ref(), inner))
+    }
+
+    /// Split a string into runs of characters that use the same provider, so that each provider
+    /// still sees whole runs of text (which matters for providers that shape text)
+    fn runs<'a>(&'a self, string: &'a str) -> impl 'a + Iterator<Item = (usize, Range<usize>)> {
+        let mut chars = string.char_indices().peekable();
+        core::iter::from_fn(move || {
+            let (start, first) = chars.next()?;
+            let index = self.provider_for(first);
+            let mut end = start + first.len_utf8();
+            while let Some(&(next_start, next)) = chars.peek() {
+                if self.provider_for(next) != index {
+                    break;
+                }
+                end = next_start + next.len_utf8();
+                chars.next();
+            }
+
+            Some((index, start..end))
+        })
+    }
+}
+
+impl FontProvider for FallbackProvider {
+    fn pixel_type(&self) -> PixelType {
+        self.providers[0].pixel_type()
+    }
+
+    fn glyph_pixel_type(&self, glyph: Glyph) -> PixelType {
+        match self.route(glyph) {
+            Some((provider, inner)) => provider.glyph_pixel_type(inner),
+            None => self.pixel_type(),
+        }
+    }
+
+    fn is_color_glyph(&self, glyph: Glyph) -> bool {
+        self.route(glyph)
+            .is_some_and(|(provider, inner)| provider.is_color_glyph(inner))
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        let index = self.provider_for(character);
+        self.chain_glyph(index, self.providers[index].single_glyph(character))
+    }
+
+    fn has_glyph(&self, character: char) -> bool {
+        self.providers
+            .iter()
+            .any(|provider| provider.has_glyph(character))
+    }
+
+    fn covered_chars(&self, out: &mut Vec<char>) {
+        let mut covered = Vec::new();
+        for provider in self.providers.iter() {
+            provider.covered_chars(&mut covered);
+        }
+        // Characters covered by more than one font should only be listed once
+        covered.sort_unstable();
+        covered.dedup();
+        out.extend(covered);
+    }
+
+    fn notdef(&self) -> Glyph {
+        let notdef = self.providers[0].notdef();
+        if notdef.0 <= GLYPH_MASK {
+            Self::encode(0, notdef)
+        } else {
+            Glyph::NOTDEF
+        }
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        for (index, range) in self.runs(string) {
+            let start = glyphs.len();
+            self.providers[index].glyphs(&string[range], glyphs);
+            for glyph in &mut glyphs[start..] {
+                *glyph = self.chain_glyph(index, *glyph);
+            }
+        }
+    }
+
+    fn glyphs_directional(&self, string: &str, direction: Direction, glyphs: &mut Vec<Glyph>) {
+        let mut runs: Vec<_> = self.runs(string).collect();
+        // In visual order, the last run of right-to-left text is furthest to the left
+        if direction == Direction::RightToLeft {
+            runs.reverse();
+        }
+        for (index, range) in runs {
+            let start = glyphs.len();
+            self.providers[index].glyphs_directional(&string[range], direction, glyphs);
+            for glyph in &mut glyphs[start..] {
+                *glyph = self.chain_glyph(index, *glyph);
+            }
+        }
+    }
+
+    fn glyphs_with_clusters(
+        &self,
+        string: &str,
+        glyphs: &mut Vec<Glyph>,
+        clusters: &mut Vec<Range<usize>>,
+    ) {
+        for (index, range) in self.runs(string) {
+            let start = glyphs.len();
+            let cluster_start = clusters.len();
+            self.providers[index].glyphs_with_clusters(&string[range.clone()], glyphs, clusters);
+            for glyph in &mut glyphs[start..] {
+                *glyph = self.chain_glyph(index, *glyph);
+            }
+            for cluster in &mut clusters[cluster_start..] {
+                *cluster = cluster.start + range.start..cluster.end + range.start;
+            }
+        }
+    }
+
+    fn line_height(&self) -> f32 {
+        self.providers[0].line_height()
+    }
+
+    fn ascent(&self) -> f32 {
+        self.providers[0].ascent()
+    }
+
+    fn descent(&self) -> f32 {
+        self.providers[0].descent()
+    }
+
+    fn vertical_line_width(&self) -> f32 {
+        self.providers[0].vertical_line_width()
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.providers[0].scale_factor()
+    }
+
+    fn units_per_em(&self) -> u16 {
+        self.providers[0].units_per_em()
+    }
+
+    fn unscaled_advance(&self, glyph: Glyph) -> Option<f32> {
+        let (provider, inner) = self.route(glyph)?;
+        provider.unscaled_advance(inner)
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        match self.route(glyph) {
+            Some((provider, inner)) => provider.metrics(inner),
+            None => Metrics {
+                bounds: None,
+                bounds_f: None,
+                bearing_x: 0.0,
+                advance_x: 0.0,
+                bearing_y: 0.0,
+                advance_y: 0.0,
+            },
+        }
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        let (provider, inner) = self
+            .route(glyph)
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+        provider.rasterize(inner)
+    }
+
+    fn rasterize_into(&self, glyph: Glyph, out: &mut Vec<u8>) -> Result<(), CacheError> {
+        let (provider, inner) = self
+            .route(glyph)
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+        provider.rasterize_into(inner, out)
+    }
+
+    #[cfg(feature = "async")]
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        match self.route(glyph) {
+            Some((provider, inner)) => provider.rasterize_async(inner),
+            None => Box::pin(core::future::ready(Err(CacheError::NonRenderableGlyph(
+                glyph,
+            )))),
+        }
+    }
+
+    fn bounds_phased(&self, glyph: Glyph, x_phase: f32, y_phase: f32) -> Option<Bounds> {
+        let (provider, inner) = self.route(glyph)?;
+        provider.bounds_phased(inner, x_phase, y_phase)
+    }
+
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        x_phase: f32,
+        y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        let (provider, inner) = self
+            .route(glyph)
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+        provider.rasterize_phased(inner, x_phase, y_phase)
+    }
+
+    fn kerning(&self, a: Glyph, b: Glyph) -> f32 {
+        let (a_index, a_inner) = Self::decode(a);
+        let (b_index, b_inner) = Self::decode(b);
+        match self.providers.get(a_index) {
+            Some(provider) if a_index == b_index => provider.kerning(a_inner, b_inner),
+            _ => 0.0,
+        }
+    }
+}
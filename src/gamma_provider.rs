@@ -0,0 +1,160 @@
+use super::*;
+
+use libm::powf;
+
+/// Applies gamma correction to the coverage of an Alpha provider's glyphs
+///
+/// Rasterizers produce linear coverage, which can make text look too thin or too heavy once it's
+/// blended, depending on how the renderer treats alpha. Each coverage value is mapped through
+/// `out = (in / 255) ^ gamma * 255`, so a gamma above 1.0 makes partially covered pixels lighter
+/// (thinner text) and a gamma below 1.0 makes them heavier. A gamma of 1.0 leaves the glyphs
+/// untouched.
+///
+/// Only glyphs with a [`glyph_pixel_type`](FontProvider::glyph_pixel_type) of
+/// [`PixelType::Alpha`] are corrected; every other glyph, and everything besides rasterizing, is
+/// passed straight through.
+pub struct GammaProvider<P: FontProvider> {
+    inner: P,
+    gamma: f32,
+    table: [u8; 256],
+}
+
+impl<P: FontProvider> GammaProvider<P> {
+    /// # Panics
+    ///
+    /// If the gamma isn't positive
+    pub fn new(inner: P, gamma: f32) -> Self {
+        assert!(gamma > 0.0);
+
+        GammaProvider {
+            inner,
+            gamma,
+            table: gamma_table(gamma),
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+}
+
+impl<P: FontProvider> FontProvider for GammaProvider<P> {
+    delegate_to_inner!(
+        pixel_type,
+        glyph_pixel_type,
+        is_color_glyph,
+        single_glyph,
+        has_glyph,
+        covered_chars,
+        notdef,
+        glyphs,
+        glyphs_directional,
+        glyphs_with_clusters,
+        line_height,
+        ascent,
+        descent,
+        vertical_line_width,
+        scale_factor,
+        units_per_em,
+        unscaled_advance,
+        metrics,
+        bounds_phased,
+        kerning,
+    );
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        let mut data = Vec::new();
+        self.rasterize_into(glyph, &mut data)?;
+
+        Ok(data)
+    }
+
+    fn rasterize_into(&self, glyph: Glyph, out: &mut Vec<u8>) -> Result<(), CacheError> {
+        let start = out.len();
+        self.inner.rasterize_into(glyph, out)?;
+        if self.inner.glyph_pixel_type(glyph) == PixelType::Alpha {
+            for value in out[start..].iter_mut() {
+                *value = self.table[*value as usize];
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        let future = self.inner.rasterize_async(glyph);
+        if self.inner.glyph_pixel_type(glyph) != PixelType::Alpha {
+            return future;
+        }
+        let table = self.table;
+
+        Box::pin(async move {
+            let mut data = future.await?;
+            for value in data.iter_mut() {
+                *value = table[*value as usize];
+            }
+
+            Ok(data)
+        })
+    }
+
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        x_phase: f32,
+        y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        let mut data = self.inner.rasterize_phased(glyph, x_phase, y_phase)?;
+        if self.inner.glyph_pixel_type(glyph) == PixelType::Alpha {
+            for value in data.iter_mut() {
+                *value = self.table[*value as usize];
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// The corrected value of every coverage value
+fn gamma_table(gamma: f32) -> [u8; 256] {
+    let mut table = [0; 256];
+    for (value, corrected) in table.iter_mut().enumerate() {
+        let coverage = value as f32 / 255.0;
+        *corrected = (powf(coverage, gamma) * 255.0 + 0.5) as u8;
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamma_above_one_lowers_mid_gray() {
+        let table = gamma_table(2.0);
+        assert_eq!(table[0], 0);
+        assert_eq!(table[255], 255);
+        // (128 / 255)^2 * 255 is about 64
+        assert_eq!(table[128], 64);
+        assert!(table.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn gamma_of_one_changes_nothing() {
+        let table = gamma_table(1.0);
+        assert!(table
+            .iter()
+            .enumerate()
+            .all(|(value, corrected)| *corrected as usize == value));
+    }
+}
@@ -0,0 +1,168 @@
+//! A [`Texture`] that stores its glyphs in an OpenGL texture through glow
+//!
+//! Alpha and Luma glyphs are stored in a `GL_R8` texture, with the value in the red channel.
+//! Every other type of glyph is stored in a `GL_RGBA8` texture.
+//!
+//! # Safety
+//!
+//! Every OpenGL call is unsafe, and needs the context to be current on the calling thread. By
+//! creating a [`GlowTexture`], you promise that its context is current whenever the texture is
+//! used, which includes every [`FontCache`] method that can render glyphs. The texture is also
+//! left bound to `GL_TEXTURE_2D` after each upload, which other code shouldn't rely on being
+//! preserved.
+
+use super::*;
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use glow::{Context, HasContext, PixelUnpackData};
+
+pub struct GlowTexture {
+    gl: Arc<Context>,
+    texture: glow::Texture,
+    width: u32,
+    height: u32,
+    pixel_type: PixelType,
+}
+
+impl GlowTexture {
+    /// Allocate an immutable texture of the given size, to store glyphs of the given type
+    ///
+    /// The texture uses linear filtering, and has no mipmaps.
+    ///
+    /// # Safety
+    ///
+    /// The context must be current, both now and whenever the texture is used. See the [module
+    /// documentation](self).
+    pub unsafe fn new(
+        gl: Arc<Context>,
+        width: u32,
+        height: u32,
+        pixel_type: PixelType,
+    ) -> Result<Self, String> {
+        let texture = gl.create_texture()?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        let internal_format = match pixel_type {
+            PixelType::Alpha | PixelType::Luma => glow::R8,
+            _ => glow::RGBA8,
+        };
+        gl.tex_storage_2d(
+            glow::TEXTURE_2D,
+            1,
+            internal_format,
+            width as i32,
+            height as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::LINEAR as i32,
+        );
+
+        Ok(GlowTexture {
+            gl,
+            texture,
+            width,
+            height,
+            pixel_type,
+        })
+    }
+
+    pub fn texture(&self) -> glow::Texture {
+        self.texture
+    }
+
+    pub fn pixel_type(&self) -> PixelType {
+        self.pixel_type
+    }
+
+    /// Delete the OpenGL texture
+    ///
+    /// Dropping a `GlowTexture` leaves the texture alive, because the context might not be current
+    /// at that point.
+    ///
+    /// # Safety
+    ///
+    /// The context must be current, and the texture must not be used afterwards.
+    pub unsafe fn delete(self) {
+        self.gl.delete_texture(self.texture);
+    }
+}
+
+impl Texture for GlowTexture {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn put_rect(&mut self, pixel: PixelType, data: &[u8], gpu: &TextureGlyph) {
+        let bounds = &gpu.bounds;
+        if bounds.width == 0 || bounds.height == 0 {
+            return;
+        }
+        assert!(bounds.x >= 0);
+        assert!(bounds.y >= 0);
+
+        // Glyphs that don't match the texture format are converted, the same way the image
+        // texture treats them
+        let data = convert_pixels(pixel, self.pixel_type, data);
+        let format = match self.pixel_type {
+            PixelType::Alpha | PixelType::Luma => glow::RED,
+            _ => glow::RGBA,
+        };
+
+        // Safety: the creator of the texture promised the context is current (see GlowTexture::new)
+        unsafe {
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            // Rows of glyph data are tightly packed, which the default 4-byte alignment breaks
+            self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            self.gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                bounds.x,
+                bounds.y,
+                bounds.width as i32,
+                bounds.height as i32,
+                format,
+                glow::UNSIGNED_BYTE,
+                PixelUnpackData::Slice(Some(&data)),
+            );
+            self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+        }
+    }
+
+    fn clear(&mut self, _pixel: PixelType) {
+        // Write zeroes in the format the texture stores, so they aren't converted
+        let storage = match self.pixel_type {
+            PixelType::Alpha | PixelType::Luma => self.pixel_type,
+            _ => PixelType::RGBA,
+        };
+        let bounds = Bounds {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        };
+        let data =
+            alloc::vec![0; (bounds.width * bounds.height) as usize * storage.bytes_per_pixel()];
+        self.put_rect(
+            storage,
+            &data,
+            &TextureGlyph {
+                glyph: Glyph(0),
+                page: 0,
+                bounds,
+
+                rotated: false,
+            },
+        );
+    }
+}
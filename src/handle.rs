@@ -0,0 +1,80 @@
+use super::*;
+
+/// A rendered glyph that stays in the cache for as long as a copy of the handle is alive
+///
+/// Handles are returned by [`FontCache::render_glyph_handle`]. While any clone of a glyph's
+/// handle exists, [`EvictionPolicy::Lru`] won't evict the glyph. Once every clone is dropped, the
+/// glyph can be reclaimed: when the cache runs out of space, it first removes the glyphs whose
+/// handles are all gone, before adding a page or evicting anything else. This suits transient
+/// text, where the glyphs of a label can be freed along with the label.
+///
+/// Handles only hold a reference count, so cloning and dropping them is cheap, but reclaiming
+/// goes through every cached glyph each time the cache runs out of space. Glyphs that were only
+/// rendered without handles are never reclaimed. Handles don't stop [`FontCache::clear`],
+/// [`FontCache::remove_glyph`], or [`FontCache::compact`] from removing or moving the glyph, and
+/// the [`TextureGlyph`] in a handle is where the glyph was when it was rendered.
+#[derive(Clone, Debug)]
+pub struct GlyphHandle {
+    texture_glyph: TextureGlyph,
+    metrics: Metrics,
+    _refs: Arc<()>,
+}
+
+impl GlyphHandle {
+    pub fn texture_glyph(&self) -> &TextureGlyph {
+        &self.texture_glyph
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+impl<T: Texture> FontCache<T> {
+    /// Render a glyph like [`render_glyph`](FontCache::render_glyph), returning a handle that
+    /// keeps it from being reclaimed
+    ///
+    /// See [`GlyphHandle`] for how glyphs with handles are freed.
+    pub fn render_glyph_handle(&mut self, glyph: Glyph) -> Result<GlyphHandle, CacheError> {
+        let key = GlyphKey::new(glyph);
+        self.cache.start_window();
+        let (metrics, texture_glyph) = self.cache.render_key(key)?;
+        let refs = match self.cache.map.get_mut(&key) {
+            Some(cached) => cached.refs.get_or_insert_with(|| Arc::new(())).clone(),
+            // Glyphs with nothing to draw aren't stored, so there's nothing to reclaim
+            None => Arc::new(()),
+        };
+
+        Ok(GlyphHandle {
+            texture_glyph,
+            metrics,
+            _refs: refs,
+        })
+    }
+}
+
+impl<T: Texture> Cache<T> {
+    /// Remove every glyph whose handles have all been dropped, returning whether any were
+    /// removed
+    ///
+    /// Like with eviction, glyphs used in the current window are kept, since they may already
+    /// have been handed out.
+    pub(crate) fn reclaim(&mut self) -> bool {
+        let unreferenced: Vec<_> = self
+            .map
+            .iter()
+            .filter(|(_, cached)| {
+                cached.refs.is_some()
+                    && !cached.is_referenced()
+                    && !cached.placed
+                    && cached.last_used < self.window_start
+            })
+            .map(|(key, _)| *key)
+            .collect();
+        for key in unreferenced.iter() {
+            self.remove(*key);
+        }
+
+        !unreferenced.is_empty()
+    }
+}
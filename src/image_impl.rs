@@ -1,6 +1,6 @@
 use super::*;
 
-use image::{ImageBuffer, Rgba};
+use image::{DynamicImage, ImageBuffer, Rgba};
 
 impl Texture for ImageBuffer<Rgba<u8>, Vec<u8>> {
     fn width(&self) -> u32 {
@@ -19,27 +19,205 @@ impl Texture for ImageBuffer<Rgba<u8>, Vec<u8>> {
         let bx = gpu.bounds.x as u32;
         let by = gpu.bounds.y as u32;
 
-        match pixel {
-            Alpha => {
-                for x in 0..gpu.bounds.width {
-                    for y in 0..gpu.bounds.height {
-                        for i in 0..3 {
-                            self.get_pixel_mut(bx + x, by + y).0[i] = 255;
-                        }
-                        self.get_pixel_mut(bx + x, by + y).0[3] =
-                            data[(x + y * gpu.bounds.width) as usize];
+        let bytes_per_pixel = pixel.bytes_per_pixel();
+        for x in 0..gpu.bounds.width {
+            for y in 0..gpu.bounds.height {
+                let index = (x + y * gpu.bounds.width) as usize * bytes_per_pixel;
+                let value = &data[index..index + bytes_per_pixel];
+                let color = match pixel {
+                    Alpha => [255, 255, 255, value[0]],
+                    Luma => [value[0], value[0], value[0], 255],
+                    RGB => [value[0], value[1], value[2], 255],
+                    RGBA => [value[0], value[1], value[2], value[3]],
+                    RGBSubpixel => {
+                        let alpha = value[0].max(value[1]).max(value[2]);
+                        [value[0], value[1], value[2], alpha]
                     }
-                }
+                };
+                self.put_pixel(bx + x, by + y, Rgba(color));
             }
-            RGBA => {
-                for x in bx..gpu.bounds.width {
-                    for y in by..gpu.bounds.height {
-                        let index = ((x + y * gpu.bounds.height) * 4) as usize;
-                        let pixel = &data[index..(4 + index)];
-                        self.get_pixel_mut(x, y).0.clone_from_slice(pixel);
-                    }
+        }
+    }
+
+    fn clear(&mut self, _pixel: PixelType) {
+        for pixel in self.pixels_mut() {
+            *pixel = Rgba([0, 0, 0, 0]);
+        }
+    }
+
+    fn copy_from(&mut self, src: &Self) -> Result<(), CacheError> {
+        for y in 0..src.height().min(self.height()) {
+            for x in 0..src.width().min(self.width()) {
+                self.put_pixel(x, y, *src.get_pixel(x, y));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_rect(
+        &self,
+        pixel: PixelType,
+        bounds: &Bounds,
+        out: &mut Vec<u8>,
+    ) -> Result<(), CacheError> {
+        assert!(bounds.x >= 0);
+        assert!(bounds.y >= 0);
+        let bx = bounds.x as u32;
+        let by = bounds.y as u32;
+
+        for y in by..by + bounds.height {
+            for x in bx..bx + bounds.width {
+                let value = self.get_pixel(x, y).0;
+                match pixel {
+                    PixelType::Alpha => out.push(value[3]),
+                    PixelType::RGBA => out.extend_from_slice(&value),
+                    PixelType::RGB | PixelType::RGBSubpixel => out.extend_from_slice(&value[..3]),
+                    PixelType::Luma => out.push(value[0]),
                 }
             }
         }
+
+        Ok(())
+    }
+}
+
+/// An image texture that grows whenever a glyph is written past its edges
+///
+/// Writing outside of the image reallocates it to the next power of two that's big enough in
+/// each direction, and copies the existing contents over. [`Texture::width`] and
+/// [`Texture::height`] report the current size.
+///
+/// A [`FontCache`] only packs glyphs into the area the texture had when the cache was created
+/// (or last given a texture), so on its own a cache never writes past the edges. To let the
+/// cache use more room, pass a larger texture to [`FontCache::grow_texture`], which keeps the
+/// cached glyphs. This texture is most useful as a scratch target for callers that write glyphs
+/// themselves, or that lay out their own atlas before handing it to the cache.
+#[derive(Clone, Debug)]
+pub struct GrowableImageTexture {
+    image: ImageBuffer<Rgba<u8>, Vec<u8>>,
+}
+
+impl GrowableImageTexture {
+    pub fn new(width: u32, height: u32) -> Self {
+        GrowableImageTexture {
+            image: ImageBuffer::new(width, height),
+        }
+    }
+
+    pub fn from_image(image: ImageBuffer<Rgba<u8>, Vec<u8>>) -> Self {
+        GrowableImageTexture { image }
+    }
+
+    pub fn image(&self) -> &ImageBuffer<Rgba<u8>, Vec<u8>> {
+        &self.image
+    }
+
+    pub fn into_image(self) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        self.image
+    }
+
+    /// Grow the image so it's at least the given size
+    fn reserve(&mut self, width: u32, height: u32) {
+        let (old_width, old_height) = self.image.dimensions();
+        if width <= old_width && height <= old_height {
+            return;
+        }
+        let grow = |old: u32, needed: u32| {
+            if needed <= old {
+                old
+            } else {
+                needed.next_power_of_two()
+            }
+        };
+        let mut image = ImageBuffer::new(grow(old_width, width), grow(old_height, height));
+        Texture::copy_from(&mut image, &self.image).expect("Images can always be copied");
+        self.image = image;
+    }
+}
+
+impl Texture for GrowableImageTexture {
+    fn width(&self) -> u32 {
+        self.image.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.image.height()
+    }
+
+    fn put_rect(&mut self, pixel: PixelType, data: &[u8], gpu: &TextureGlyph) {
+        assert!(gpu.bounds.x >= 0);
+        assert!(gpu.bounds.y >= 0);
+        self.reserve(
+            gpu.bounds.x as u32 + gpu.bounds.width,
+            gpu.bounds.y as u32 + gpu.bounds.height,
+        );
+        self.image.put_rect(pixel, data, gpu);
+    }
+
+    fn clear(&mut self, pixel: PixelType) {
+        Texture::clear(&mut self.image, pixel);
+    }
+
+    fn copy_from(&mut self, src: &Self) -> Result<(), CacheError> {
+        Texture::copy_from(&mut self.image, &src.image)
+    }
+
+    fn read_rect(
+        &self,
+        pixel: PixelType,
+        bounds: &Bounds,
+        out: &mut Vec<u8>,
+    ) -> Result<(), CacheError> {
+        self.image.read_rect(pixel, bounds, out)
+    }
+}
+
+impl<T: Texture> FontCache<T> {
+    /// Read the first texture page back into an image, for debugging or for saving a baked atlas
+    ///
+    /// The pixels are read with [`Texture::read_rect`] in the font's
+    /// [`pixel_type`](FontProvider::pixel_type), so this returns [`CacheError::Unsupported`] if
+    /// the texture can't be read from. Alpha coverage becomes white with the coverage as alpha,
+    /// Luma and RGB are copied as they are, RGBA stays RGBA, and subpixel coverage becomes RGBA
+    /// with the highest of the three coverages as alpha.
+    pub fn to_image(&self) -> Result<DynamicImage, CacheError> {
+        let texture = self.texture();
+        let (width, height) = (texture.width(), texture.height());
+        let pixel_type = self.font().pixel_type();
+        let mut data = Vec::new();
+        let bounds = Bounds {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        texture.read_rect(pixel_type, &bounds, &mut data)?;
+        check_length(&bounds, pixel_type, &data)?;
+
+        let image = match pixel_type {
+            PixelType::Alpha => {
+                let data = data.iter().flat_map(|alpha| [255, *alpha]).collect();
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLumaA8)
+            }
+            PixelType::Luma => {
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma8)
+            }
+            PixelType::RGB => {
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb8)
+            }
+            PixelType::RGBA => {
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+            }
+            PixelType::RGBSubpixel => {
+                let data = data
+                    .chunks_exact(3)
+                    .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], rgb[0].max(rgb[1]).max(rgb[2])])
+                    .collect();
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+            }
+        };
+
+        Ok(image.expect("The length of the data was already checked"))
     }
 }
@@ -0,0 +1,242 @@
+use super::*;
+
+/// A rendered glyph, along with where to draw it on a line of text
+#[derive(Clone, Debug)]
+pub struct PositionedGlyph {
+    pub texture_glyph: TextureGlyph,
+    pub metrics: Metrics,
+    /// The position of the pen when the glyph is drawn, relative to the start of the line
+    pub x: f32,
+    /// The position of the pen when the glyph is drawn, relative to the baseline
+    pub y: f32,
+    /// The [`scale_factor`](FontProvider::scale_factor) of the glyph's font, which turns the
+    /// pen position (in logical pixels) into the physical pixels of the glyph's bounds
+    pub scale_factor: f32,
+}
+
+impl PositionedGlyph {
+    /// Where the glyph should be drawn, with the pen position rounded to the nearest pixel
+    ///
+    /// This is the glyph's [`Metrics::bounds`] moved to the pen, or `None` if the glyph has
+    /// nothing to draw. Like the bounds, it's in physical pixels.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let bounds = self.metrics.bounds?;
+
+        Some(Bounds {
+            x: bounds.x + round(self.x * self.scale_factor),
+            y: bounds.y + round(self.y * self.scale_factor),
+            ..bounds
+        })
+    }
+}
+
+/// Everything needed to draw one glyph of a line laid out by [`FontCache::layout_line`]
+#[derive(Copy, Clone, Debug)]
+pub struct GlyphInstance {
+    pub texture_glyph: TextureGlyph,
+    /// Where to draw the glyph, in the same space as the origin of the line, scaled to physical
+    /// pixels by the font's [`scale_factor`](FontProvider::scale_factor)
+    pub rect: RectF,
+    /// The texture coordinates of the glyph, from [`FontCache::uv_for`]
+    ///
+    /// If the glyph is [`rotated`](TextureGlyph::rotated), these have to be turned to match.
+    pub uv: [f32; 4],
+}
+
+/// A glyph that has already been positioned by a shaping library (like rustybuzz or cosmic-text)
+///
+/// See [`FontCache::render_shaped`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShapedGlyph {
+    pub glyph: Glyph,
+    /// The position of the pen when the glyph is drawn, relative to the start of the line
+    pub x: f32,
+    /// The position of the pen when the glyph is drawn, relative to the baseline
+    ///
+    /// Like [`Metrics::bounds`], this increases downwards, which is the opposite of HarfBuzz.
+    pub y: f32,
+}
+
+impl<T: Texture> FontCache<T> {
+    /// Render a string, and lay its glyphs out along a single line
+    ///
+    /// The pen starts at the origin, and moves by each glyph's advance, adjusted by the
+    /// [`FontProvider::kerning`] between each pair of glyphs. The string is processed the same
+    /// way as in [`render_string`], except that whitespace is always kept, because spaces still
+    /// move the pen. This doesn't break lines: newlines are treated like any other glyph.
+    ///
+    /// [`render_string`]: FontCache::render_string
+    pub fn layout_string(&mut self, string: &str) -> Result<Vec<PositionedGlyph>, CacheError> {
+        let string = self.prepare_string_with(string, true);
+        let cache = &mut self.cache;
+        cache.start_window();
+        let glyph_buffer = &mut self.glyph_buffer;
+        self.shaping
+            .glyphs(FontId::DEFAULT, cache.font.as_ref(), &string, glyph_buffer);
+
+        let scale_factor = cache.font.scale_factor();
+        let mut x = 0.0;
+        let mut y = 0.0;
+        let mut previous = None;
+        let mut positioned = Vec::with_capacity(glyph_buffer.len());
+        for glyph in glyph_buffer.drain(..) {
+            if let Some(previous) = previous {
+                x += cache.font.kerning(previous, glyph);
+            }
+            let (metrics, texture_glyph) = cache.render_glyph(glyph)?;
+            let (advance_x, advance_y) = (metrics.advance_x, metrics.advance_y);
+            positioned.push(PositionedGlyph {
+                texture_glyph,
+                metrics,
+                x,
+                y,
+                scale_factor,
+            });
+            x += advance_x;
+            y += advance_y;
+            previous = Some(glyph);
+        }
+
+        Ok(positioned)
+    }
+
+    /// Render a string and lay it out like [`layout_string`], resolving each glyph to a rectangle
+    /// to draw it in and its texture coordinates
+    ///
+    /// `origin` is the top-left of the line, in screen space with y increasing downwards. The
+    /// baseline is the font's [`ascent`](FontProvider::ascent) below it, so the next line starts
+    /// [`line_height`](FontProvider::line_height) further down. Every pen position is rounded to
+    /// a whole pixel, so glyphs line up with the screen's pixels if the origin does. Glyphs with
+    /// nothing to draw (like spaces) still move the pen, but aren't included.
+    ///
+    /// The origin and the pen are in logical pixels, and the rects are in physical pixels: for a
+    /// font with a [`scale_factor`](FontProvider::scale_factor), each pen position is multiplied
+    /// by it before the glyph's bounds are added.
+    ///
+    /// This covers the common case of a single line of UI text; for anything else, use
+    /// [`layout_string`] or [`render_string`](FontCache::render_string) and position the glyphs
+    /// yourself.
+    ///
+    /// [`layout_string`]: FontCache::layout_string
+    pub fn layout_line(
+        &mut self,
+        string: &str,
+        origin: (f32, f32),
+    ) -> Result<Vec<GlyphInstance>, CacheError> {
+        let baseline = origin.1 + self.cache.font.ascent();
+        let positioned = self.layout_string(string)?;
+
+        Ok(positioned
+            .iter()
+            .filter_map(|glyph| {
+                let pen = (origin.0 + glyph.x, baseline + glyph.y);
+                self.instance(&glyph.texture_glyph, &glyph.metrics, pen)
+            })
+            .collect())
+    }
+
+    /// Place a glyph with its pen at the given position, rounded to a whole pixel, or return
+    /// `None` if it has nothing to draw
+    pub(crate) fn instance(
+        &self,
+        texture_glyph: &TextureGlyph,
+        metrics: &Metrics,
+        pen: (f32, f32),
+    ) -> Option<GlyphInstance> {
+        let bounds = metrics.bounds?;
+        let scale_factor = self.cache.font.scale_factor();
+        let x = round(pen.0 * scale_factor) + bounds.x;
+        let y = round(pen.1 * scale_factor) + bounds.y;
+
+        Some(GlyphInstance {
+            texture_glyph: *texture_glyph,
+            rect: RectF::from(Bounds { x, y, ..bounds }),
+            uv: self.uv_for(texture_glyph),
+        })
+    }
+
+    /// Render glyphs that were shaped and positioned elsewhere
+    ///
+    /// Shaping is out of scope for this library, so this is the way to render the output of a
+    /// shaping library: convert its glyph ids and positions (scaled to pixels) into
+    /// [`ShapedGlyph`]s, and render them with any provider that uses the same font at the same
+    /// size. Only the provider's [`metrics`](FontProvider::metrics) and
+    /// [`rasterize`](FontProvider::rasterize) are used; the string never goes through
+    /// [`glyphs`](FontProvider::glyphs), so it isn't normalized or stripped of whitespace
+    /// either.
+    pub fn render_shaped(
+        &mut self,
+        glyphs: &[ShapedGlyph],
+    ) -> Result<Vec<PositionedGlyph>, CacheError> {
+        self.cache.start_window();
+        let scale_factor = self.cache.font.scale_factor();
+        glyphs
+            .iter()
+            .map(|shaped| {
+                let (metrics, texture_glyph) = self.cache.render_glyph(shaped.glyph)?;
+
+                Ok(PositionedGlyph {
+                    texture_glyph,
+                    metrics,
+                    x: shaped.x,
+                    y: shaped.y,
+                    scale_factor,
+                })
+            })
+            .collect()
+    }
+
+    /// Render a string, and lay its glyphs out down a single column of vertical text
+    ///
+    /// The pen starts at the origin and moves down by each glyph's [`Metrics::advance_y`].
+    /// Providers that don't have vertical metrics for a glyph report an `advance_y` of 0, and in
+    /// that case the pen moves by the [`FontProvider::line_height`] instead, which gives every
+    /// glyph a square-ish cell. Each glyph is centered on the column by moving the pen left by
+    /// half of its horizontal advance, so `x` is the center of the column. Neighboring columns
+    /// should be [`FontProvider::vertical_line_width`] apart.
+    ///
+    /// None of the included providers read vertical metrics yet, so they all use the fallback.
+    /// Glyphs aren't kerned or rotated, which suits CJK text but not sideways Latin text. The
+    /// string is processed the same way as in [`layout_string`](FontCache::layout_string).
+    pub fn layout_vertical(&mut self, string: &str) -> Result<Vec<PositionedGlyph>, CacheError> {
+        let string = self.prepare_string_with(string, true);
+        let cache = &mut self.cache;
+        cache.start_window();
+        let glyph_buffer = &mut self.glyph_buffer;
+        self.shaping
+            .glyphs(FontId::DEFAULT, cache.font.as_ref(), &string, glyph_buffer);
+
+        let line_height = cache.font.line_height();
+        let scale_factor = cache.font.scale_factor();
+        let mut y = 0.0;
+        let mut positioned = Vec::with_capacity(glyph_buffer.len());
+        for glyph in glyph_buffer.drain(..) {
+            let (metrics, texture_glyph) = cache.render_glyph(glyph)?;
+            let advance = if metrics.advance_y != 0.0 {
+                metrics.advance_y
+            } else {
+                line_height
+            };
+            let x = -metrics.advance_x / 2.0;
+            positioned.push(PositionedGlyph {
+                texture_glyph,
+                metrics,
+                x,
+                y,
+                scale_factor,
+            });
+            y += advance;
+        }
+
+        Ok(positioned)
+    }
+}
+
+/// Round to the nearest integer, without needing std or libm
+fn round(value: f32) -> i32 {
+    if value < 0.0 {
+        (value - 0.5) as i32
+    } else {
+        (value + 0.5) as i32
+    }
+}
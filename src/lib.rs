@@ -10,28 +10,80 @@
 //! - DO support various font libraries / types of fonts (TTFs, bitmap fonts)
 //! - DO support whatever backend (rendering to an image, GPU frameworks, etc.)
 //! - DON'T handle complex tasks like shaping. The font stack should handle that elsewhere, and
-//! provide this library the glyphs to render
+//!   provide this library the glyphs to render
 //! - DON'T handle layout. This can be taken care of by the client
-//! application when rendering.
+//!   application when rendering. (The optional `layout` feature adds simple line wrapping as a
+//!   convenience, with `FontCache::layout_paragraph`.)
 //!
-//! Support is available out-of-the-box for software rendering via `image`, rendering via
-//! `rusttype`, and performing automatic unicode normalization. All of these are optional features.
+//! Support is available out-of-the-box for software rendering via `image`, GPU textures via
+//! `wgpu` or `glow`, rendering via
+//! `rusttype`, `ab_glyph`, or `swash` (which supports color emoji), and performing automatic
+//! unicode normalization. All of these are optional features.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
 
+#[cfg(feature = "ab_glyph")]
+pub mod abglyph_provider;
+#[cfg(feature = "async")]
+mod async_render;
+pub mod bitmap_provider;
+mod builder;
+#[macro_use]
+mod delegate;
+pub mod fallback_provider;
+#[cfg(feature = "gamma")]
+pub mod gamma_provider;
+#[cfg(feature = "glow")]
+pub mod glow_texture;
+mod handle;
 #[cfg(feature = "image")]
 mod image_impl;
+mod layout;
+pub mod metrics_cache_provider;
+mod null_texture;
+pub mod outline_provider;
+mod packer;
+#[cfg(feature = "layout")]
+mod paragraph;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "serde")]
+mod persist;
+pub mod premultiply_provider;
 #[cfg(feature = "rusttype")]
 pub mod rusttype_provider;
+#[cfg(feature = "sdf")]
+pub mod sdf_provider;
+mod shaping_cache;
+#[cfg(feature = "swash")]
+pub mod swash_provider;
+#[cfg(feature = "synthetic_style")]
+pub mod synthetic_style_provider;
+pub mod tint_provider;
+#[cfg(feature = "wgpu")]
+pub mod wgpu_texture;
 
-#[cfg(not(feature = "unicode-normalization"))]
+#[cfg(any(feature = "glow", feature = "wgpu"))]
+use alloc::borrow::Cow;
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
-#[cfg(feature = "unicode-normalization")]
 use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+#[cfg(feature = "async")]
+pub use async_render::{RasterizeFuture, RasterizedGlyphs};
+pub use builder::FontCacheBuilder;
+use core::ops::Range;
+pub use handle::GlyphHandle;
 use hashbrown::HashMap;
+#[cfg(feature = "image")]
+pub use image_impl::GrowableImageTexture;
+pub use layout::{GlyphInstance, PositionedGlyph, ShapedGlyph};
+pub use null_texture::NullTexture;
+use packer::Packer;
+pub use packer::Packing;
+use shaping_cache::ShapingCache;
 
 /// Any object that can turn characters into glyphs and render them can be a FontProvider
 ///
@@ -43,27 +95,219 @@ use hashbrown::HashMap;
 /// It is assumed that a given font provider will operate at a fixed size. For a variable-sized
 /// source (like a TTF font), the font size can be paired with the font data to produce a single
 /// FontProvider.
-pub trait FontProvider {
+///
+/// Providers have to be `Send` and `Sync`, so that a [`FontCache`] can be shared between threads
+/// (and, with the "rayon" feature, rasterize glyphs on several threads at once.)
+pub trait FontProvider: Send + Sync {
     /// The format of the data generated by the FontProvider
     fn pixel_type(&self) -> PixelType;
+    /// The format of the data generated for a specific glyph
+    ///
+    /// Some fonts mix formats, like color emoji fonts that also have monochrome outlines. By
+    /// default, every glyph uses [`pixel_type`].
+    ///
+    /// [`pixel_type`]: FontProvider::pixel_type
+    fn glyph_pixel_type(&self, _glyph: Glyph) -> PixelType {
+        self.pixel_type()
+    }
+    /// Whether the glyph has its own colors (like an emoji), rather than being a shape to be
+    /// drawn in the color of the text
+    ///
+    /// A renderer can use this to pick a shader that keeps the glyph's colors instead of tinting
+    /// it. By default no glyph is a color glyph, which is true of every provider in this library
+    /// except the swash provider.
+    fn is_color_glyph(&self, _glyph: Glyph) -> bool {
+        false
+    }
     /// Convert a single character into a Glyph
     ///
     /// Generally you should use [`glyphs`], but when rendering just one character this method can
     /// be useful
     fn single_glyph(&self, character: char) -> Glyph;
+    /// Whether the font has a glyph for the character, for deciding when to fall back to another
+    /// font
+    ///
+    /// By default, this checks whether [`single_glyph`] returns the [`notdef`] glyph. Providers
+    /// that follow that convention don't need to do anything, but a provider that uses no glyph
+    /// at all for missing characters should override this.
+    ///
+    /// [`single_glyph`]: FontProvider::single_glyph
+    /// [`notdef`]: FontProvider::notdef
+    fn has_glyph(&self, character: char) -> bool {
+        self.single_glyph(character) != self.notdef()
+    }
+    /// Push every character the font has a glyph for onto the end of `out`, in no particular
+    /// order
+    ///
+    /// This is for building fallback chains or showing what a font supports. By default nothing
+    /// is pushed, for providers that have no way to list their characters.
+    fn covered_chars(&self, _out: &mut Vec<char>) {}
+    /// The glyph that characters the font doesn't have are converted to
+    ///
+    /// By default, this is [`Glyph::NOTDEF`], the convention for TrueType and OpenType fonts.
+    fn notdef(&self) -> Glyph {
+        Glyph::NOTDEF
+    }
     /// Convert the string into glyphs, and push the glyphs into the provided buffer
     ///
     /// This is not necessarily the same as running `single_glyph` over every character in the
-    /// string! Text is hard.
+    /// string! Text is hard. A provider may push fewer glyphs than there are characters (like a
+    /// single glyph for an emoji ZWJ sequence, or for a ligature), or more. The cache keeps
+    /// whatever the provider produces: [`FontCache::render_string`] yields one item per glyph,
+    /// so a provider backed by a shaping library can be plugged in to render composed glyphs.
+    ///
+    /// The included font providers don't shape text, and map each character to a glyph on its
+    /// own. They do leave out zero width joiners and variation selectors that the font has no
+    /// glyph for, so an emoji sequence at least doesn't draw ".notdef" boxes between its parts.
     fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>);
+    /// Convert the string into glyphs like [`glyphs`], for text with the given base direction
+    ///
+    /// The glyphs should come out in visual order (left to right on the screen), so a provider
+    /// backed by a shaping library can pass the direction along and reverse the glyphs of
+    /// right-to-left text. The cache never reorders glyphs itself, and doesn't do bidirectional
+    /// layout, so a string that mixes directions should be split into runs by the caller. By
+    /// default the direction is ignored and this is the same as [`glyphs`], which is what the
+    /// included providers do, since they don't shape text.
+    ///
+    /// [`glyphs`]: FontProvider::glyphs
+    fn glyphs_directional(&self, string: &str, _direction: Direction, glyphs: &mut Vec<Glyph>) {
+        self.glyphs(string, glyphs);
+    }
+    /// Convert the string into glyphs like [`glyphs`], and also push the byte range of the
+    /// string that each glyph came from into `clusters`
+    ///
+    /// By default, this assumes that [`glyphs`] produces one glyph per character. If it doesn't,
+    /// every glyph is attributed to the whole string, so providers that shape text should
+    /// override this with their real clusters.
+    ///
+    /// [`glyphs`]: FontProvider::glyphs
+    fn glyphs_with_clusters(
+        &self,
+        string: &str,
+        glyphs: &mut Vec<Glyph>,
+        clusters: &mut Vec<Range<usize>>,
+    ) {
+        let start = glyphs.len();
+        self.glyphs(string, glyphs);
+        let count = glyphs.len() - start;
+        if count == string.chars().count() {
+            clusters.extend(
+                string
+                    .char_indices()
+                    .map(|(index, c)| index..index + c.len_utf8()),
+            );
+        } else {
+            clusters.extend(core::iter::repeat_n(0..string.len(), count));
+        }
+    }
     /// How much space to include between baselines of the given font
     fn line_height(&self) -> f32;
+    /// How far the tallest glyphs of the font reach above the baseline
+    ///
+    /// By default, the whole line is treated as being above the baseline, so this is the
+    /// [`line_height`](FontProvider::line_height).
+    fn ascent(&self) -> f32 {
+        self.line_height()
+    }
+    /// How far the lowest glyphs of the font reach below the baseline, as a negative number
+    ///
+    /// By default, this is 0. The line height is usually the ascent minus the descent, plus any
+    /// gap between lines.
+    fn descent(&self) -> f32 {
+        0.0
+    }
+    /// The distance between the centers of neighboring columns of vertical text
+    ///
+    /// None of the included providers read vertical metrics from their fonts, so they all use
+    /// this default, which is the [`line_height`](FontProvider::line_height).
+    fn vertical_line_width(&self) -> f32 {
+        self.line_height()
+    }
+    /// How many physical pixels make up a logical pixel, for providers that rasterize at a
+    /// higher resolution than they lay text out at (like on HiDPI displays)
+    ///
+    /// Line metrics, advances, bearings and kerning are in logical pixels. Everything that
+    /// describes the rasterized image is in physical pixels: the [`bounds`](Metrics::bounds) and
+    /// [`bounds_f`](Metrics::bounds_f) of a glyph, the phases of
+    /// [`bounds_phased`](FontProvider::bounds_phased), and the data itself. A glyph with its pen
+    /// at `x` logical pixels is drawn at `x * scale_factor + bounds.x` physical pixels, which is
+    /// how the cache's layout methods place glyphs. By default, this is 1.0, so both are the
+    /// same.
+    fn scale_factor(&self) -> f32 {
+        1.0
+    }
+    /// How many font design units make up an em, for callers that scale design metrics
+    /// themselves
+    ///
+    /// By default, this is 1000, which is common for fonts without TrueType outlines.
+    fn units_per_em(&self) -> u16 {
+        1000
+    }
+    /// The horizontal advance of a glyph in font design units, independent of the size it's
+    /// rasterized at
+    ///
+    /// Dividing by [`units_per_em`](FontProvider::units_per_em) and multiplying by a size in
+    /// pixels per em gives the advance at that size. By default, this is `None`, for providers
+    /// that don't have design metrics (like bitmap fonts).
+    fn unscaled_advance(&self, _glyph: Glyph) -> Option<f32> {
+        None
+    }
     /// Get the metrics of a character (how to space it, where to include it on a line, etc.)
     fn metrics(&self, glyph: Glyph) -> Metrics;
-    /// Convert a character into image bytes, with the format determined by [`pixel_type`]
+    /// Convert a character into image bytes, with the format determined by [`glyph_pixel_type`]
     ///
-    /// [`pixel_type`]: FontProvider::pixel_type
+    /// [`glyph_pixel_type`]: FontProvider::glyph_pixel_type
     fn rasterize(&self, glpyh: Glyph) -> Result<Vec<u8>, CacheError>;
+    /// Rasterize a glyph like [`rasterize`], pushing the data onto the end of `out`
+    ///
+    /// The cache rasterizes glyphs into a buffer it reuses, so providers that can write their data
+    /// directly (like ones that copy pre-rendered glyphs) should override this to avoid
+    /// allocating. By default, this calls [`rasterize`] and copies the result.
+    ///
+    /// [`rasterize`]: FontProvider::rasterize
+    fn rasterize_into(&self, glyph: Glyph, out: &mut Vec<u8>) -> Result<(), CacheError> {
+        out.extend_from_slice(&self.rasterize(glyph)?);
+        Ok(())
+    }
+    /// Rasterize a glyph like [`rasterize`], returning a future for the data
+    ///
+    /// This is what [`FontCache::rasterize_string_async`] uses, so that providers can rasterize
+    /// without blocking the thread that draws (for example, by sending the glyph to a web
+    /// worker). By default, this rasterizes right away with [`rasterize`] and returns a future
+    /// that's already complete.
+    ///
+    /// [`rasterize`]: FontProvider::rasterize
+    #[cfg(feature = "async")]
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        Box::pin(core::future::ready(self.rasterize(glyph)))
+    }
+    /// The bounds of a glyph shifted right and down by fractions of a pixel (each from 0 to 1)
+    ///
+    /// A shifted glyph can cover an extra column or row of pixels, so this is the size of the data
+    /// from [`rasterize_phased`]. By default, the phase is ignored and this is the glyph's
+    /// [`Metrics::bounds`], matching the default of [`rasterize_phased`].
+    ///
+    /// [`rasterize_phased`]: FontProvider::rasterize_phased
+    fn bounds_phased(&self, glyph: Glyph, _x_phase: f32, _y_phase: f32) -> Option<Bounds> {
+        self.metrics(glyph).bounds
+    }
+    /// Rasterize a glyph shifted right and down by fractions of a pixel (each from 0 to 1)
+    ///
+    /// This is what [`FontCache::render_glyph_subpixel`] uses to render a glyph at several
+    /// positions within a pixel. The data has to fit the [`bounds_phased`] for the same phase.
+    /// By default, the phase is ignored and this calls [`rasterize`], so providers that can't
+    /// shift glyphs still work, just without the extra precision.
+    ///
+    /// [`bounds_phased`]: FontProvider::bounds_phased
+    /// [`rasterize`]: FontProvider::rasterize
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        _x_phase: f32,
+        _y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        self.rasterize(glyph)
+    }
     /// Optionally expose extra kerning information for glyphs
     ///
     /// By default, this is always 0.0. Some font providers may add more information here,
@@ -82,6 +326,56 @@ pub trait Texture {
     fn height(&self) -> u32;
     /// Write the data from a font into a texture
     fn put_rect(&mut self, pixel: PixelType, data: &[u8], gpu: &TextureGlyph);
+    /// Erase every pixel of the texture
+    ///
+    /// The texture is being used to store glyphs of the given type. By default, this writes a
+    /// glyph of that type covering the whole texture, with every byte set to zero. Textures that
+    /// can clear themselves more directly (or that convert glyph data such that zeroes don't stay
+    /// zero) should override this.
+    fn clear(&mut self, pixel: PixelType) {
+        let bounds = Bounds {
+            x: 0,
+            y: 0,
+            width: self.width(),
+            height: self.height(),
+        };
+        let data =
+            alloc::vec![0; (bounds.width * bounds.height) as usize * pixel.bytes_per_pixel()];
+        self.put_rect(
+            pixel,
+            &data,
+            &TextureGlyph {
+                glyph: Glyph(0),
+                page: 0,
+                bounds,
+                rotated: false,
+            },
+        );
+    }
+    /// Copy the contents of another texture into this one, with both aligned at the top-left
+    ///
+    /// This is used by [`FontCache::grow_texture`], so `src` will be no larger than `self`. Not
+    /// every texture can do this (for example, if the data lives on the GPU with no way to read
+    /// it back), so by default this returns [`CacheError::Unsupported`].
+    fn copy_from(&mut self, _src: &Self) -> Result<(), CacheError>
+    where
+        Self: Sized,
+    {
+        Err(CacheError::Unsupported)
+    }
+    /// Read an area of the texture back, pushing its pixels into `out` in the given format
+    ///
+    /// The pixels are pushed row by row, so exactly `width * height` pixels are added to `out`.
+    /// Not every texture can be read from (for example, if the data lives on the GPU), so by
+    /// default this returns [`CacheError::Unsupported`].
+    fn read_rect(
+        &self,
+        _pixel: PixelType,
+        _bounds: &Bounds,
+        _out: &mut Vec<u8>,
+    ) -> Result<(), CacheError> {
+        Err(CacheError::Unsupported)
+    }
 }
 
 /// The main structure for maintaing a cache of rendered glyphs
@@ -91,35 +385,267 @@ pub trait Texture {
 /// handle sending glyphs to the GPU: if you want to do that, provide a [`Texture`] that stores its
 /// data on the GPU. What it does do is keep track of which glyphs have already been rendered, where
 /// they were stored, and provide a consistent API over a variety of ways of rendering characters.
+///
+/// A `FontCache` is `Send` and `Sync` whenever its texture is.
 pub struct FontCache<T: Texture> {
     glyph_buffer: Vec<Glyph>,
+    cluster_buffer: Vec<Range<usize>>,
+    keep_whitespace: bool,
+    normalization: Option<NormalizationForm>,
+    shaping: ShapingCache,
     cache: Cache<T>,
 }
 
+// Make sure a FontCache can be shared between threads whenever its texture can
+#[allow(dead_code)]
+fn assert_send_sync<T: Texture + Send + Sync>() {
+    fn check<S: Send + Sync>() {}
+    check::<FontCache<T>>();
+}
+
 struct Cache<T: Texture> {
     font: Box<dyn FontProvider>,
+    /// The fonts added with [`FontCache::add_font`], where the font with ID `n` is at `n - 1`
+    extra_fonts: Vec<Box<dyn FontProvider>>,
+    pages: Vec<Page<T>>,
+    new_page: Option<Box<dyn Fn() -> T + Send + Sync>>,
+    map: HashMap<GlyphKey, CachedGlyph>,
+    padding: u32,
+    packing: Packing,
+    allow_rotation: bool,
+    max_glyph_dimensions: Option<(u32, u32)>,
+    missing_glyph: MissingGlyphPolicy,
+    origin: TextureOrigin,
+    eviction: EvictionPolicy,
+    clock: u64,
+    /// The clock when the current call into the cache started, so LRU eviction can leave alone
+    /// the glyphs that call has already handed out
+    window_start: u64,
+    /// How many glyphs have been written to the textures since the count was last taken
+    misses: usize,
+    /// Reused between glyphs, to avoid allocating for every rasterized glyph
+    scratch: Vec<u8>,
+}
+
+struct Page<T: Texture> {
     texture: T,
-    map: HashMap<Glyph, TextureGlyph>,
-    h_cursor: u32,
-    v_cursor: u32,
-    current_line_height: u32,
+    packer: Packer,
+    dirty: Option<Bounds>,
+}
+
+impl<T: Texture> Page<T> {
+    fn new(texture: T, padding: u32, packing: Packing) -> Self {
+        let packer = Packer::new(texture.width(), texture.height(), padding, packing);
+
+        Page {
+            texture,
+            packer,
+            dirty: None,
+        }
+    }
+}
+
+struct CachedGlyph {
+    texture_glyph: TextureGlyph,
+    /// Kept so that cache hits don't have to ask the font again
+    metrics: Metrics,
+    last_used: u64,
+    /// Whether the glyph was placed by the caller with [`FontCache::put_glyph_at`], so its space
+    /// doesn't belong to the packer
+    placed: bool,
+    /// Shared with every [`GlyphHandle`] for the glyph, if any were handed out
+    refs: Option<Arc<()>>,
+}
+
+impl CachedGlyph {
+    /// Whether a [`GlyphHandle`] for the glyph is still alive
+    fn is_referenced(&self) -> bool {
+        self.refs
+            .as_ref()
+            .is_some_and(|refs| Arc::strong_count(refs) > 1)
+    }
+}
+
+/// What the cache should do when it runs out of space for a new glyph
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EvictionPolicy {
+    /// Never remove glyphs on its own; [`CacheError::OutOfSpace`] is returned instead
+    Never,
+    /// Remove the least-recently-used glyphs until the new glyph fits
+    ///
+    /// The oldest glyph that takes up at least as much space as the new one is removed first,
+    /// since its space is enough on its own. If every such glyph was used more recently (or there
+    /// are none), the oldest glyphs are removed one by one until their freed space merges into a
+    /// big enough area, which for a glyph bigger than any other can mean removing all of them.
+    ///
+    /// Once a glyph is evicted, any [`TextureGlyph`] previously returned for it is invalid: its
+    /// area of the texture may be overwritten by another glyph at any time. If the glyph is
+    /// needed again, it will be re-rendered (likely to a different location.) Glyphs used in the
+    /// current call are never evicted, so the glyphs a single call to
+    /// [`FontCache::render_string`] (or [`FontCache::layout_string`], and so on) hands out stay
+    /// valid until the next call; if a string needs more room than that leaves,
+    /// [`CacheError::OutOfSpace`] is returned instead. Only rely on the glyphs of the most recent
+    /// call.
+    Lru,
+}
+
+/// What the cache should do with characters the font doesn't have, which are converted to the
+/// font's [`notdef`](FontProvider::notdef) glyph
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MissingGlyphPolicy {
+    /// Render the font's own notdef glyph, whatever it looks like (some fonts leave it empty)
+    #[default]
+    Render,
+    /// Return [`CacheError::MissingGlyph`]
+    Error,
+    /// Draw nothing: the glyph has no bounds, like a space, but keeps the notdef glyph's advance
+    Skip,
+    /// Draw a rectangle outline (a "tofu") sized to the font's ascent, so missing characters are
+    /// always visible
+    Tofu,
+}
+
+/// Which corner of the texture the [`bounds`](TextureGlyph::bounds) of glyphs start from
+///
+/// Glyphs are packed from the top-left corner either way; this only changes how their positions
+/// are reported and written. With [`BottomLeft`](TextureOrigin::BottomLeft), each glyph's `y` is
+/// measured up from the bottom edge of its page's texture to the glyph's bottom edge, and its
+/// rows are written bottom to top, which matches OpenGL's texture coordinates. The first two
+/// values of [`TextureGlyph::uv`] are then the glyph's bottom-left corner instead of its top-left.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextureOrigin {
+    /// `y` grows downwards from the top-left corner, like image coordinates
+    #[default]
+    TopLeft,
+    /// `y` grows upwards from the bottom-left corner, like OpenGL
+    ///
+    /// The flipped positions depend on the texture's height, so this doesn't work with textures
+    /// that change height as glyphs are written (like `GrowableImageTexture`), and
+    /// [`FontCache::grow_texture`] can only make the texture wider.
+    BottomLeft,
+}
+
+/// How full a [`FontCache`] is, returned by [`FontCache::stats`]
+///
+/// Every count covers all of the cache's pages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CacheStats {
+    /// How many glyphs are cached
+    pub glyphs: usize,
+    /// How many texture pages the cache has
+    pub pages: u32,
+    /// How many pixels the cached glyphs cover
+    pub used_pixels: u64,
+    /// How many pixels the textures have in total
+    pub texture_pixels: u64,
+    /// How many rows the packer has started, which is always 0 for strategies other than
+    /// [`Packing::Shelf`] and [`Packing::ShelfBucketed`]
+    pub rows: u32,
+    /// An estimate of how many pixels the packer has passed over without filling them with a
+    /// glyph, like the padding between glyphs and the space above short glyphs in a row
+    pub wasted_pixels: u64,
+}
+
+/// A row of glyphs started by the packer, returned by [`FontCache::rows`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RowInfo {
+    /// The index of the texture page the row is on
+    pub page: u32,
+    /// The top edge of the row, in the same coordinates as [`TextureGlyph::bounds`] (so with
+    /// [`TextureOrigin::BottomLeft`], its bottom edge)
+    pub y: i32,
+    /// How tall the row is, which is the height of its tallest glyph for [`Packing::Shelf`], or
+    /// the rounded height of the row's bucket for [`Packing::ShelfBucketed`]
+    pub height: u32,
+    /// How far from the left edge of the texture the row's glyphs reach
+    pub used_width: u32,
+}
+
+/// A unicode normalization form to apply to strings before converting them to glyphs
+///
+/// Normalizing only happens if the "unicode-normalization" feature is activated. See [UAX #15]
+/// for what each form does.
+///
+/// [UAX #15]: https://www.unicode.org/reports/tr15/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalizationForm {
+    /// Canonical composition, which combines characters with their accents where possible
+    Nfc,
+    /// Canonical decomposition, which splits accented characters into a base and combining marks
+    Nfd,
+    /// Compatibility composition
+    Nfkc,
+    /// Compatibility decomposition
+    Nfkd,
+}
+
+/// The base direction of a run of text, as a hint to providers that shape text
+///
+/// See [`FontProvider::glyphs_directional`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Like Latin or Cyrillic text
+    #[default]
+    LeftToRight,
+    /// Like Arabic or Hebrew text
+    RightToLeft,
 }
 
 impl<T: Texture> FontCache<T> {
     /// Create a new FontCache that pulls from the given provider and renders to the provided
     /// texture
+    ///
+    /// To configure the cache, use [`FontCache::builder`] instead.
     pub fn new(font: Box<dyn FontProvider>, texture: T) -> Self {
-        FontCache {
-            glyph_buffer: Vec::new(),
-            cache: Cache {
-                font,
-                texture,
-                map: HashMap::new(),
-                h_cursor: 0,
-                v_cursor: 0,
-                current_line_height: 0,
-            },
-        }
+        FontCacheBuilder::new(font, texture).build()
+    }
+
+    /// Start configuring a FontCache that pulls from the given provider and renders to the
+    /// provided texture
+    pub fn builder(font: Box<dyn FontProvider>, texture: T) -> FontCacheBuilder<T> {
+        FontCacheBuilder::new(font, texture)
+    }
+
+    /// Create a new FontCache that adds another texture page whenever the existing pages are full
+    ///
+    /// The first page is created right away. Each [`TextureGlyph`] records the index of the page
+    /// it was stored on, which can be used to look up the texture with [`FontCache::page`].
+    pub fn with_pages(
+        font: Box<dyn FontProvider>,
+        new_page: impl 'static + Send + Sync + Fn() -> T,
+    ) -> Self {
+        let texture = new_page();
+        FontCacheBuilder::new(font, texture).pages(new_page).build()
+    }
+
+    /// Create a new FontCache that frees up space according to the given policy when the texture
+    /// fills up
+    ///
+    /// See [`EvictionPolicy`] for the effect of each policy.
+    pub fn with_eviction(
+        font: Box<dyn FontProvider>,
+        texture: T,
+        eviction: EvictionPolicy,
+    ) -> Self {
+        FontCacheBuilder::new(font, texture)
+            .eviction(eviction)
+            .build()
+    }
+
+    /// Create a new FontCache that places glyphs in the texture with the given strategy
+    ///
+    /// See [`Packing`] for the trade-offs of each strategy.
+    pub fn with_packing(font: Box<dyn FontProvider>, texture: T, packing: Packing) -> Self {
+        FontCacheBuilder::new(font, texture)
+            .packing(packing)
+            .build()
     }
 
     /// Forget the position of the characters in the texture, and re-set the cursor.
@@ -130,195 +656,2107 @@ impl<T: Texture> FontCache<T> {
         self.cache.clear();
     }
 
-    /// Render a glyph to the texture
-    pub fn render_glyph(&mut self, key: Glyph) -> Result<(Metrics, TextureGlyph), CacheError> {
-        self.cache.render_glyph(key)
+    /// Like [`clear`], but also erase the pixels of every texture page with [`Texture::clear`]
+    ///
+    /// This prevents stale glyph data from showing up around new glyphs that are smaller than the
+    /// ones they replace. Each page is marked as dirty (see [`take_dirty_region`]).
+    ///
+    /// [`clear`]: FontCache::clear
+    /// [`take_dirty_region`]: FontCache::take_dirty_region
+    pub fn clear_and_zero(&mut self) {
+        self.clear();
+        let pixel_type = self.cache.font.pixel_type();
+        for page in self.cache.pages.iter_mut() {
+            page.texture.clear(pixel_type);
+            page.dirty = Some(Bounds {
+                x: 0,
+                y: 0,
+                width: page.texture.width(),
+                height: page.texture.height(),
+            });
+        }
     }
 
-    /// Attempt to convert a string into a series of glyphs or errors
+    /// Change how many pixels are left empty between neighboring glyphs in the texture
     ///
-    /// Before being converted, the string is normalized if the "unicode-normalilzation" feature is
-    /// activated, and whitespace characters are removed.
-    pub fn render_string<'a>(
-        &'a mut self,
-        string: &str,
-    ) -> impl 'a + Iterator<Item = Result<(Metrics, TextureGlyph), CacheError>> {
-        #[cfg(feature = "unicode-normalization")]
-        let mut string = {
-            use unicode_normalization::UnicodeNormalization;
-            string.nfc().collect::<String>()
-        };
-        #[cfg(not(feature = "unicode-normalization"))]
-        let mut string = string.to_owned();
-        string.retain(|c| !c.is_whitespace());
-        let glyph_buffer = &mut self.glyph_buffer;
-        let cache = &mut self.cache;
-        cache.font.glyphs(&string, glyph_buffer);
-        glyph_buffer
-            .drain(..)
-            .map(move |glyph| cache.render_glyph(glyph))
+    /// The padding applies both horizontally and vertically, and defaults to 1. Increasing it
+    /// avoids glyphs bleeding into each other with bilinear filtering or mipmaps. Like
+    /// [`replace_texture`], this clears the cache.
+    ///
+    /// [`replace_texture`]: FontCache::replace_texture
+    pub fn set_padding(&mut self, padding: u32) {
+        self.clear();
+        self.cache.padding = padding;
+        for page in self.cache.pages.iter_mut() {
+            page.packer.set_padding(padding);
+        }
     }
 
-    /// Cache a string or return an error if one occurred
+    /// Let the packer turn glyphs sideways when they fit better that way (off by default)
     ///
-    /// This can be useful if the entire domain of the possible glyphs is known beforehand (like a
-    /// bitmap font.) Under the hood, this just calls [`render_string`] and ignores the returned
-    /// glyphs.
-    pub fn cache_string(&mut self, string: &str) -> Result<(), CacheError> {
-        self.render_string(string).map(|r| r.map(|_| ())).collect()
+    /// This helps with glyphs that are much taller than they are wide (or the reverse), which
+    /// otherwise leave gaps next to them. Glyphs stored this way have
+    /// [`TextureGlyph::rotated`] set, and have to be drawn with rotated texture coordinates, so
+    /// only turn this on if the renderer handles that. Glyphs that are already cached stay where
+    /// they are.
+    pub fn set_allow_rotation(&mut self, allow_rotation: bool) {
+        self.cache.allow_rotation = allow_rotation;
     }
 
-    /// Swap out the internal texture for another one
+    /// Refuse to cache glyphs wider or taller than the given width and height (there's no limit
+    /// by default)
     ///
-    /// This will clear the cache automatically, to avoid holding references to invalid areas of
-    /// the texture
-    pub fn replace_texture(&mut self, mut texture: T) -> T {
-        self.clear();
-        core::mem::swap(&mut self.cache.texture, &mut texture);
+    /// Glyphs over the limit return [`CacheError::GlyphTooLarge`] instead of taking up a large
+    /// part of the texture. This guards against a huge font size or an unusually large glyph
+    /// pushing out everything else. Glyphs that are already cached stay in the cache.
+    pub fn set_max_glyph_dimensions(&mut self, max: Option<(u32, u32)>) {
+        self.cache.max_glyph_dimensions = max;
+    }
 
-        texture
+    pub fn max_glyph_dimensions(&self) -> Option<(u32, u32)> {
+        self.cache.max_glyph_dimensions
     }
 
-    pub fn texture(&self) -> &T {
-        &self.cache.texture
+    /// Choose what to do with characters the font doesn't have ([`MissingGlyphPolicy::Render`]
+    /// by default)
+    ///
+    /// The notdef glyphs that are already cached are removed, so the next one rendered follows
+    /// the new policy.
+    pub fn set_missing_glyph_policy(&mut self, policy: MissingGlyphPolicy) {
+        self.cache.missing_glyph = policy;
+        let cache = &self.cache;
+        let notdefs: Vec<_> = cache
+            .map
+            .keys()
+            .filter(|key| key.glyph == cache.provider(key.font).notdef())
+            .copied()
+            .collect();
+        for key in notdefs {
+            self.cache.remove(key);
+        }
     }
 
-    pub fn font(&self) -> &dyn FontProvider {
-        self.cache.font.as_ref()
+    pub fn missing_glyph_policy(&self) -> MissingGlyphPolicy {
+        self.cache.missing_glyph
     }
-}
 
-impl<T: Texture> Cache<T> {
-    fn clear(&mut self) {
-        self.map.clear();
-        self.h_cursor = 0;
-        self.v_cursor = 0;
-        self.current_line_height = 0;
+    /// Which corner of the texture glyph positions start from, which is set with
+    /// [`FontCacheBuilder::texture_origin`]
+    pub fn texture_origin(&self) -> TextureOrigin {
+        self.cache.origin
     }
 
-    fn render_glyph(&mut self, glyph: Glyph) -> Result<(Metrics, TextureGlyph), CacheError> {
-        if let Some(tex_glyph) = self.map.get(&glyph) {
-            return Ok((self.font.metrics(glyph), *tex_glyph));
-        }
-        let metrics = self.font.metrics(glyph);
-        let bounds = metrics.bounds.unwrap();
-        if bounds.width > self.texture.width() || bounds.height > self.texture.height() {
-            return Err(CacheError::TextureTooSmall);
-        }
-        if bounds.width + self.h_cursor > self.texture.width() {
-            self.h_cursor = 0;
-            self.v_cursor += self.current_line_height + 1;
-            self.current_line_height = 0;
-        }
-        if bounds.height + self.v_cursor > self.texture.height() {
-            return Err(CacheError::OutOfSpace);
+    /// Remove a single glyph from the cache, returning where it was stored in the texture
+    ///
+    /// Like [`clear`], this doesn't set any data in the Texture; the caller can clear the returned
+    /// area if that's important. The space is handed back to the packer: later glyphs that fit
+    /// inside of it will be placed there before the packer moves on to unused parts of the
+    /// texture. Neighboring removed glyphs are joined into larger areas, but the packer never
+    /// moves its cursor backwards, so removing glyphs doesn't make room for glyphs that are
+    /// bigger than the hole they leave.
+    ///
+    /// Every subpixel variant of the glyph (see [`render_glyph_subpixel`]) is removed, and the
+    /// first one found is returned.
+    ///
+    /// [`clear`]: FontCache::clear
+    /// [`render_glyph_subpixel`]: FontCache::render_glyph_subpixel
+    pub fn remove_glyph(&mut self, glyph: Glyph) -> Option<TextureGlyph> {
+        let mut first = None;
+        for subpixel in 0..GlyphKey::SUBPIXEL_VARIANTS {
+            let removed = self.cache.remove(GlyphKey {
+                font: FontId::DEFAULT,
+                glyph,
+                subpixel,
+            });
+            first = first.or(removed);
         }
-        let pixel_type = self.font.pixel_type();
-        let data = self.font.rasterize(glyph)?;
-        let gpu = TextureGlyph {
-            glyph,
-            bounds: Bounds {
-                x: self.h_cursor as i32,
-                y: self.v_cursor as i32,
-                width: bounds.width,
-                height: bounds.height,
-            },
-        };
-        self.texture.put_rect(pixel_type, &data[..], &gpu);
-        self.h_cursor += gpu.bounds.width + 1;
-        self.current_line_height = self.current_line_height.max(gpu.bounds.height);
-        self.map.insert(glyph, gpu);
 
-        Ok((self.font.metrics(glyph), gpu))
+        first
     }
-}
 
-/// The index of the font character to render
-///
-/// Glyphs are what actually gets rendered to the screen. It might be tempting to think of a glyph
-/// like a 'rendered character.' In specific scripts, this is often the case. 'A' and 'a' have
-/// distinct glyphs, and are unconditionally the same glyph. In others, this might not be true. See
-/// ['Text Rendering Hates You'](https://gankra.github.io/blah/text-hates-you) for more information
-/// on why text is complicated.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Glyph(pub u32);
+    /// Check whether a glyph is already in the cache, without rendering it
+    ///
+    /// This only checks the glyph rendered at a whole pixel; subpixel variants can be checked
+    /// with [`contains_key`](FontCache::contains_key).
+    pub fn contains(&self, glyph: Glyph) -> bool {
+        self.contains_key(GlyphKey::new(glyph))
+    }
 
-/// The relevant information for a glyph stored on the texture
-#[derive(Copy, Clone, Debug)]
-pub struct TextureGlyph {
-    pub glyph: Glyph,
-    pub bounds: Bounds,
-}
+    /// Check whether a subpixel variant of a glyph is already in the cache
+    pub fn contains_key(&self, key: GlyphKey) -> bool {
+        self.cache.map.contains_key(&key)
+    }
 
-/// The layout information for a glyph
-#[non_exhaustive]
-#[derive(Clone, Debug)]
-pub struct Metrics {
-    pub bounds: Option<Bounds>,
-    pub bearing_x: f32,
-    pub advance_x: f32,
-    pub bearing_y: f32,
-    pub advance_y: f32,
-}
+    /// Look up where a glyph is stored, if it's already in the cache
+    ///
+    /// Unlike [`render_glyph`], this never renders the glyph, and doesn't count as a use of it for
+    /// [`EvictionPolicy::Lru`]. Like [`contains`], this only looks up the glyph of the main font
+    /// rendered at a whole pixel; other fonts and subpixel variants can be looked up with
+    /// [`get_key`].
+    ///
+    /// [`render_glyph`]: FontCache::render_glyph
+    /// [`contains`]: FontCache::contains
+    /// [`get_key`]: FontCache::get_key
+    pub fn get(&self, glyph: Glyph) -> Option<TextureGlyph> {
+        self.get_key(GlyphKey::new(glyph))
+    }
 
-#[derive(Copy, Clone, Debug)]
-pub struct Bounds {
-    pub x: i32,
-    pub y: i32,
-    pub width: u32,
-    pub height: u32,
-}
+    /// Look up where any glyph is stored, including subpixel variants and the glyphs of fonts
+    /// added with [`add_font`](FontCache::add_font)
+    pub fn get_key(&self, key: GlyphKey) -> Option<TextureGlyph> {
+        self.cache.map.get(&key).map(|cached| cached.texture_glyph)
+    }
 
-/// An error generated during a cache operation
-#[derive(Copy, Clone, Debug)]
-pub enum CacheError {
-    /// No matter what, the texture is too small to render the glyph (even when empty)
+    /// Iterate over every glyph in the cache, along with where it's stored
     ///
-    /// To fix this error, expand the texture. Make sure to clear the cache if the texture data is
-    /// also invalidated
-    TextureTooSmall,
-    /// The cache cannot store the current request without clearing it first
-    OutOfSpace,
-    /// A glyph was passed to a render method but it could not be rendered
+    /// The glyphs come in no particular order. Each subpixel variant of a glyph is stored
+    /// separately, so a glyph rendered at several offsets appears once for each of them, and the
+    /// glyphs of every font (see [`add_font`]) are included. Each glyph comes with its
+    /// [`GlyphKey`], which tells them apart and can be passed to [`get_key`]. Like [`get`], this
+    /// doesn't count as a use of the glyphs for [`EvictionPolicy::Lru`].
     ///
-    /// For example, unsized glyphs (glyphs with None for their [`bounds`]) cannot be rendered
-    ///
-    /// [`bounds`]: Metrics::bounds
-    NonRenderableGlyph(Glyph),
-}
+    /// [`get`]: FontCache::get
+    /// [`get_key`]: FontCache::get_key
+    /// [`add_font`]: FontCache::add_font
+    pub fn iter(&self) -> impl '_ + Iterator<Item = (GlyphKey, &TextureGlyph)> {
+        self.cache
+            .map
+            .iter()
+            .map(|(key, cached)| (*key, &cached.texture_glyph))
+    }
 
-#[cfg(feature = "std")]
-use std::{error::Error, fmt};
+    /// How many glyphs are stored in the cache
+    pub fn len(&self) -> usize {
+        self.cache.map.len()
+    }
 
-#[cfg(feature = "std")]
-impl fmt::Display for CacheError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            CacheError::TextureTooSmall => {
-                write!(f, "The texture is too small to render the given input")
-            }
-            CacheError::OutOfSpace => write!(
-                f,
-                "The cache is out of space, and must be cleared before more rendering"
-            ),
-            CacheError::NonRenderableGlyph(glyph) => {
-                write!(f, "Attempted to render an un-renderable glyph: {:?}", glyph)
-            }
-        }
+    /// Whether the cache has no glyphs stored
+    pub fn is_empty(&self) -> bool {
+        self.cache.map.is_empty()
     }
-}
 
-#[cfg(feature = "std")]
-impl Error for CacheError {
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
+    /// Render a glyph to the texture
+    ///
+    /// Glyphs with nothing to draw (like spaces) aren't stored in the texture. They still return
+    /// their metrics, paired with a [`TextureGlyph`] with empty bounds.
+    pub fn render_glyph(&mut self, key: Glyph) -> Result<(Metrics, TextureGlyph), CacheError> {
+        self.cache.start_window();
+        self.cache.render_glyph(key)
     }
-}
 
-/// How the pixels of the rasterized font are represented
-pub enum PixelType {
-    /// A series of values representing the alpha with no associated color
-    Alpha,
-    /// A series of complete color values
-    RGBA,
+    /// Like [`render_glyph`], but for a glyph of a font added with [`add_font`]
+    ///
+    /// # Panics
+    ///
+    /// If the font wasn't added to this cache
+    ///
+    /// [`render_glyph`]: FontCache::render_glyph
+    /// [`add_font`]: FontCache::add_font
+    pub fn render_glyph_with(
+        &mut self,
+        font: FontId,
+        glyph: Glyph,
+    ) -> Result<(Metrics, TextureGlyph), CacheError> {
+        self.cache.start_window();
+        self.cache.render_key(GlyphKey::new(glyph).with_font(font))
+    }
+
+    /// Render a glyph for a pen at a fractional horizontal position
+    ///
+    /// The fractional part of `x` is rounded to the nearest of [`GlyphKey::SUBPIXEL_VARIANTS`]
+    /// offsets, and the glyph is rendered shifted by that much with
+    /// [`FontProvider::rasterize_phased`]. Each offset is cached separately, so text drawn at
+    /// fractional positions stays crisp, at the cost of storing up to that many copies of each
+    /// glyph.
+    ///
+    /// The bounds in the returned metrics are the shifted glyph's, relative to the pen position
+    /// rounded down to a whole pixel, so the glyph should be drawn at those bounds moved by
+    /// `x.floor()`. Providers that don't support shifting glyphs render every offset the same.
+    /// Like the bounds, `x` is in physical pixels: for a font with a
+    /// [`scale_factor`](FontProvider::scale_factor), it's the logical pen position times the
+    /// scale factor.
+    pub fn render_glyph_subpixel(
+        &mut self,
+        glyph: Glyph,
+        x: f32,
+    ) -> Result<(Metrics, TextureGlyph), CacheError> {
+        let (key, carry) = GlyphKey::at_offset(glyph, x);
+        self.cache.start_window();
+        let (mut metrics, texture_glyph) = self.cache.render_key(key)?;
+        if let Some(bounds) = metrics.bounds.as_mut() {
+            bounds.x += carry;
+        }
+        if let Some(bounds) = metrics.bounds_f.as_mut() {
+            bounds.x += carry as f32;
+        }
+
+        Ok((metrics, texture_glyph))
+    }
+
+    /// Render a glyph into a specific area of the first page, instead of letting the packer
+    /// choose where it goes
+    ///
+    /// This is for callers that decide where glyphs go themselves, like ones with their own
+    /// packer or a fixed layout. The area has to be the same size as the glyph's bounds, and
+    /// fit inside the texture. The packer doesn't know about the area, so it may put later
+    /// glyphs on top of this one: either place every glyph this way, or keep the areas out of
+    /// the packer's way (for example, by giving placed glyphs their own texture). If the glyph
+    /// was already cached, it's moved to the new area.
+    ///
+    /// Placed glyphs are never evicted, and removing them doesn't give their space to the
+    /// packer.
+    ///
+    /// Returns [`CacheError::NonRenderableGlyph`] if the glyph has nothing to draw,
+    /// [`CacheError::WrongAreaSize`] if the area isn't the size of the glyph, and
+    /// [`CacheError::TextureTooSmall`] if the area doesn't fit in the texture.
+    pub fn put_glyph_at(
+        &mut self,
+        glyph: Glyph,
+        bounds: Bounds,
+    ) -> Result<TextureGlyph, CacheError> {
+        let cache = &mut self.cache;
+        let key = GlyphKey::new(glyph);
+        let metrics = cache.metrics(key);
+        let size = match metrics.bounds {
+            Some(glyph_bounds) => (glyph_bounds.width, glyph_bounds.height),
+            None => return Err(CacheError::NonRenderableGlyph(glyph)),
+        };
+        if size != (bounds.width, bounds.height) {
+            return Err(CacheError::WrongAreaSize {
+                glyph,
+                size,
+                area: (bounds.width, bounds.height),
+            });
+        }
+        let texture = &cache.pages[0].texture;
+        let fits_in = |start: i32, length: u32, available: u32| {
+            start >= 0
+                && (start as u32)
+                    .checked_add(length)
+                    .is_some_and(|end| end <= available)
+        };
+        let fits = fits_in(bounds.x, bounds.width, texture.width())
+            && fits_in(bounds.y, bounds.height, texture.height());
+        if !fits {
+            return Err(CacheError::TextureTooSmall {
+                glyph,
+                needed: (bounds.width, bounds.height),
+                available: (texture.width(), texture.height()),
+            });
+        }
+        let pixel_type = cache.font.glyph_pixel_type(glyph);
+        let data = cache.rasterize(key, &bounds, pixel_type)?;
+        cache.remove(key);
+        cache.clock += 1;
+        // The area is in the texture's coordinates, but `store` takes the packer's
+        let bounds = cache.to_origin(0, &bounds);
+        let texture_glyph = cache.store(key, metrics, (0, bounds, false), pixel_type, &data);
+        cache.scratch = data;
+        if let Some(cached) = cache.map.get_mut(&key) {
+            cached.placed = true;
+        }
+
+        Ok(texture_glyph)
+    }
+
+    /// Choose how strings are normalized before being converted to glyphs, or `None` to skip it
+    ///
+    /// This defaults to [`NormalizationForm::Nfc`] if the "unicode-normalization" feature is
+    /// activated, and `None` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// If a form is given without the "unicode-normalization" feature
+    pub fn set_normalization(&mut self, normalization: Option<NormalizationForm>) {
+        assert!(
+            cfg!(feature = "unicode-normalization") || normalization.is_none(),
+            "Normalization requires the unicode-normalization feature"
+        );
+        self.normalization = normalization;
+    }
+
+    /// The form strings are normalized to before they're converted to glyphs, if any
+    pub fn normalization(&self) -> Option<NormalizationForm> {
+        self.normalization
+    }
+
+    /// Choose whether [`render_string`] keeps whitespace characters instead of removing them
+    ///
+    /// By default whitespace is removed. Keeping it is useful when doing layout, because spaces
+    /// still report their advance.
+    ///
+    /// [`render_string`]: FontCache::render_string
+    pub fn set_keep_whitespace(&mut self, keep_whitespace: bool) {
+        self.keep_whitespace = keep_whitespace;
+    }
+
+    /// Remember the glyphs of up to `capacity` recently rendered strings (0 by default)
+    ///
+    /// Converting a string into glyphs can be expensive for providers that shape text, and UI
+    /// often renders the same labels every frame. With this enabled, [`render_string`] and the
+    /// layout methods look the string up among the most recently rendered ones before asking
+    /// the provider for its glyphs. Each remembered string keeps a copy of the string and its
+    /// glyphs, so this is off unless a capacity is set. Setting a capacity of 0 turns it off
+    /// again and forgets every string.
+    ///
+    /// [`render_string`]: FontCache::render_string
+    pub fn set_shaping_cache(&mut self, capacity: usize) {
+        self.shaping.set_capacity(capacity);
+    }
+
+    /// How many strings the shaping cache remembers (see [`set_shaping_cache`])
+    ///
+    /// [`set_shaping_cache`]: FontCache::set_shaping_cache
+    pub fn shaping_cache(&self) -> usize {
+        self.shaping.capacity()
+    }
+
+    /// Convert a string into the glyphs [`render_string`] would render, without rendering them
+    ///
+    /// The string is prepared the same way, so the glyphs match one for one: it's normalized,
+    /// stripped of whitespace unless [`set_keep_whitespace`] is enabled, and goes through the
+    /// shaping cache. The texture and the cached glyphs aren't touched. This is for things that
+    /// only need the glyph IDs, like hit-testing or handing them to another system.
+    ///
+    /// [`render_string`]: FontCache::render_string
+    /// [`set_keep_whitespace`]: FontCache::set_keep_whitespace
+    pub fn shape<'a>(&'a mut self, string: &str) -> impl 'a + Iterator<Item = Glyph> {
+        let string = self.prepare_string(string);
+        self.shaping.glyphs(
+            FontId::DEFAULT,
+            self.cache.font.as_ref(),
+            &string,
+            &mut self.glyph_buffer,
+        );
+        self.glyph_buffer.drain(..)
+    }
+
+    /// Attempt to convert a string into a series of glyphs or errors
+    ///
+    /// Before being converted, the string is normalized (see [`set_normalization`]), and
+    /// whitespace characters are removed (unless this is disabled with [`set_keep_whitespace`].)
+    ///
+    /// Every glyph produced by the provider yields one item. Glyphs with nothing to draw (like
+    /// spaces) still yield their metrics, but don't take up space in the texture: they are paired
+    /// with a [`TextureGlyph`] with empty bounds.
+    ///
+    /// Characters the font doesn't have are yielded as the provider's [`notdef`] glyph, which is
+    /// rendered like any other. To skip them or substitute something else, compare each
+    /// [`TextureGlyph::glyph`] against the `notdef` glyph, fetched (with [`font`]) before
+    /// rendering.
+    ///
+    /// [`set_normalization`]: FontCache::set_normalization
+    /// [`set_keep_whitespace`]: FontCache::set_keep_whitespace
+    /// [`notdef`]: FontProvider::notdef
+    /// [`font`]: FontCache::font
+    pub fn render_string<'a>(
+        &'a mut self,
+        string: &str,
+    ) -> impl 'a + Iterator<Item = Result<(Metrics, TextureGlyph), CacheError>> {
+        let string = self.prepare_string(string);
+        let glyph_buffer = &mut self.glyph_buffer;
+        let cache = &mut self.cache;
+        cache.start_window();
+        self.shaping
+            .glyphs(FontId::DEFAULT, cache.font.as_ref(), &string, glyph_buffer);
+        glyph_buffer
+            .drain(..)
+            .map(move |glyph| cache.render_glyph(glyph))
+    }
+
+    /// Like [`render_string`], but with a font added with [`add_font`]
+    ///
+    /// The string is prepared the same way, and the glyphs go into the same texture as the
+    /// glyphs of every other font.
+    ///
+    /// # Panics
+    ///
+    /// If the font wasn't added to this cache
+    ///
+    /// [`render_string`]: FontCache::render_string
+    /// [`add_font`]: FontCache::add_font
+    pub fn render_string_with<'a>(
+        &'a mut self,
+        font: FontId,
+        string: &str,
+    ) -> impl 'a + Iterator<Item = Result<(Metrics, TextureGlyph), CacheError>> {
+        let string = self.prepare_string(string);
+        let glyph_buffer = &mut self.glyph_buffer;
+        let cache = &mut self.cache;
+        cache.start_window();
+        self.shaping
+            .glyphs(font, cache.provider(font), &string, glyph_buffer);
+        glyph_buffer
+            .drain(..)
+            .map(move |glyph| cache.render_key(GlyphKey::new(glyph).with_font(font)))
+    }
+
+    /// Like [`render_string`], but also yield the byte range each glyph came from
+    ///
+    /// The ranges index into the string after it has been processed for rendering (normalized,
+    /// and stripped of whitespace unless [`set_keep_whitespace`] is enabled), so they only line up
+    /// with the input string when that processing doesn't change it. The processed string is
+    /// available from [`normalize_for_render`].
+    ///
+    /// [`render_string`]: FontCache::render_string
+    /// [`set_keep_whitespace`]: FontCache::set_keep_whitespace
+    /// [`normalize_for_render`]: FontCache::normalize_for_render
+    pub fn render_string_with_clusters<'a>(
+        &'a mut self,
+        string: &str,
+    ) -> impl 'a + Iterator<Item = Result<(Range<usize>, Metrics, TextureGlyph), CacheError>> {
+        let string = self.prepare_string(string);
+        let glyph_buffer = &mut self.glyph_buffer;
+        let cluster_buffer = &mut self.cluster_buffer;
+        let cache = &mut self.cache;
+        cache.start_window();
+        cache
+            .font
+            .glyphs_with_clusters(&string, glyph_buffer, cluster_buffer);
+        glyph_buffer
+            .drain(..)
+            .zip(cluster_buffer.drain(..))
+            .map(move |(glyph, cluster)| {
+                let (metrics, texture_glyph) = cache.render_glyph(glyph)?;
+                Ok((cluster, metrics, texture_glyph))
+            })
+    }
+
+    /// Get the metrics of a glyph without rendering or caching it
+    ///
+    /// This never touches the texture, so it works even when the cache is out of space.
+    pub fn measure(&self, glyph: Glyph) -> Metrics {
+        self.cache.font.metrics(glyph)
+    }
+
+    /// Get how far a glyph moves the pen horizontally and vertically, without rendering or
+    /// caching it
+    ///
+    /// This works the same for glyphs with nothing to draw, like spaces, and for glyphs that
+    /// draw something without moving the pen, like combining marks (which have an advance of 0).
+    pub fn advance(&self, glyph: Glyph) -> (f32, f32) {
+        let metrics = self.cache.font.metrics(glyph);
+
+        (metrics.advance_x, metrics.advance_y)
+    }
+
+    /// Rasterize a glyph into a buffer instead of the texture, for callers that want the bitmap
+    /// itself
+    ///
+    /// `out` is cleared and filled with the glyph's data, in its
+    /// [`glyph_pixel_type`](FontProvider::glyph_pixel_type), row by row over its bounds. Nothing
+    /// is packed or cached, so this works even when the cache is out of space, and the
+    /// [`MissingGlyphPolicy`] is applied the same way as when rendering. Glyphs with nothing to
+    /// draw (like spaces) leave `out` empty and return their metrics.
+    pub fn rasterize_glyph(&self, glyph: Glyph, out: &mut Vec<u8>) -> Result<Metrics, CacheError> {
+        out.clear();
+        let font = self.cache.font.as_ref();
+        let pixel_type = font.glyph_pixel_type(glyph);
+        let mut metrics = font.metrics(glyph);
+        if glyph == font.notdef() {
+            match self.cache.missing_glyph {
+                MissingGlyphPolicy::Render => {}
+                MissingGlyphPolicy::Error => return Err(CacheError::MissingGlyph(glyph)),
+                MissingGlyphPolicy::Skip => {
+                    metrics.bounds = None;
+                    metrics.bounds_f = None;
+                }
+                MissingGlyphPolicy::Tofu => {
+                    let (metrics, data) = tofu(font, pixel_type);
+                    out.extend_from_slice(&data);
+                    return Ok(metrics);
+                }
+            }
+        }
+        if let Some(bounds) = metrics.bounds.as_ref() {
+            let result = font
+                .rasterize_into(glyph, out)
+                .and_then(|()| check_length(bounds, pixel_type, out));
+            if let Err(err) = result {
+                out.clear();
+                return Err(err);
+            }
+        }
+
+        Ok(metrics)
+    }
+
+    /// Get the metrics of each glyph in a string without rendering or caching them
+    ///
+    /// The string is processed the same way as in [`render_string`], so this produces the same
+    /// glyphs that rendering it would.
+    ///
+    /// [`render_string`]: FontCache::render_string
+    pub fn measure_string<'a>(&'a self, string: &str) -> impl 'a + Iterator<Item = Metrics> {
+        let string = self.prepare_string(string);
+        let font = self.cache.font.as_ref();
+        let mut glyphs = Vec::new();
+        font.glyphs(&string, &mut glyphs);
+        glyphs.into_iter().map(move |glyph| font.metrics(glyph))
+    }
+
+    /// Measure the box a string's ink covers when it's drawn on a single line, without rendering
+    /// or caching it
+    ///
+    /// The glyphs are the same as [`measure_string`]'s, placed one after another by their
+    /// advances and kerning, with the pen starting at (0, 0) on the baseline. The result is the
+    /// union of their [`bounds_f`](Metrics::bounds_f) at those positions: `y` is negative for
+    /// ink above the baseline, and descenders reach below 0. Like the advances, it's in logical
+    /// pixels, so for a font with a [`scale_factor`](FontProvider::scale_factor) the bounds are
+    /// divided by it. Returns `None` if no glyph has anything to draw (like a string of spaces).
+    ///
+    /// [`measure_string`]: FontCache::measure_string
+    pub fn measure_string_bounds(&self, string: &str) -> Option<RectF> {
+        let string = self.prepare_string(string);
+        let font = self.cache.font.as_ref();
+        let scale_factor = font.scale_factor();
+        let mut glyphs = Vec::new();
+        font.glyphs(&string, &mut glyphs);
+
+        let mut x = 0.0;
+        let mut previous = None;
+        let mut extents: Option<(f32, f32, f32, f32)> = None;
+        for glyph in glyphs {
+            if let Some(previous) = previous {
+                x += font.kerning(previous, glyph);
+            }
+            let metrics = font.metrics(glyph);
+            if let Some(bounds) = metrics.bounds_f {
+                let (left, top) = (x + bounds.x / scale_factor, bounds.y / scale_factor);
+                let right = left + bounds.width / scale_factor;
+                let bottom = top + bounds.height / scale_factor;
+                extents = Some(match extents {
+                    Some((min_x, min_y, max_x, max_y)) => (
+                        min_x.min(left),
+                        min_y.min(top),
+                        max_x.max(right),
+                        max_y.max(bottom),
+                    ),
+                    None => (left, top, right, bottom),
+                });
+            }
+            x += metrics.advance_x;
+            previous = Some(glyph);
+        }
+
+        extents.map(|(min_x, min_y, max_x, max_y)| RectF {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x,
+            height: max_y - min_y,
+        })
+    }
+
+    /// Cache a string or return an error if one occurred
+    ///
+    /// This can be useful if the entire domain of the possible glyphs is known beforehand (like a
+    /// bitmap font.) Under the hood, this just calls [`render_string`] and ignores the returned
+    /// glyphs.
+    pub fn cache_string(&mut self, string: &str) -> Result<(), CacheError> {
+        self.render_string(string).try_for_each(|r| r.map(|_| ()))
+    }
+
+    /// Cache every character from `start` to `end` (inclusive), like the printable ASCII range
+    /// or a Unicode block
+    ///
+    /// Each character is converted to a glyph on its own with [`FontProvider::single_glyph`].
+    /// The surrogate code points aren't characters, so they're skipped if the range spans them,
+    /// and whitespace is skipped unless the cache [keeps it](FontCache::set_keep_whitespace).
+    /// Like [`cache_string`](FontCache::cache_string), this stops at the first error.
+    pub fn cache_range(&mut self, start: char, end: char) -> Result<(), CacheError> {
+        let keep_whitespace = self.keep_whitespace;
+        self.cache.start_window();
+        for c in (start..=end).filter(|c| keep_whitespace || !c.is_whitespace()) {
+            let glyph = self.cache.font.single_glyph(c);
+            self.cache.render_glyph(glyph)?;
+        }
+
+        Ok(())
+    }
+
+    /// Cache a string like [`cache_string`], but try every glyph instead of stopping at the
+    /// first error
+    ///
+    /// Each glyph of the string is paired with the outcome of rendering it, which makes it easy to
+    /// log the glyphs that couldn't be cached and carry on.
+    ///
+    /// [`cache_string`]: FontCache::cache_string
+    pub fn cache_string_report(
+        &mut self,
+        string: &str,
+    ) -> Vec<(Glyph, Result<TextureGlyph, CacheError>)> {
+        let string = self.prepare_string(string);
+        let cache = &mut self.cache;
+        let glyph_buffer = &mut self.glyph_buffer;
+        cache.start_window();
+        cache.font.glyphs(&string, glyph_buffer);
+        glyph_buffer
+            .drain(..)
+            .map(|glyph| {
+                let result = cache
+                    .render_glyph(glyph)
+                    .map(|(_, texture_glyph)| texture_glyph);
+                (glyph, result)
+            })
+            .collect()
+    }
+
+    /// Make room for at least `additional` more glyphs, so caching them doesn't have to grow
+    /// the cache's bookkeeping along the way
+    ///
+    /// This is useful before preloading a large set of glyphs, like a whole font. It also grows
+    /// the buffer that strings are converted into glyphs with, so a string of up to `additional`
+    /// glyphs can be rendered without allocating for it. It doesn't make room in the texture.
+    pub fn reserve(&mut self, additional: usize) {
+        self.cache.map.reserve(additional);
+        self.glyph_buffer.reserve(additional);
+    }
+
+    /// Free the memory held by the cache's internal buffers
+    ///
+    /// Converting strings into glyphs and rasterizing them goes through buffers that the cache
+    /// reuses from call to call, and never shrinks, so rendering doesn't allocate once they're big
+    /// enough for the longest string and largest glyph. After preloading a lot of text at once,
+    /// this releases that memory; the buffers grow again as needed.
+    pub fn shrink_to_fit_buffers(&mut self) {
+        self.glyph_buffer = Vec::new();
+        self.cluster_buffer = Vec::new();
+        self.cache.scratch = Vec::new();
+    }
+
+    /// Swap out the internal texture for another one
+    ///
+    /// This will clear the cache automatically, to avoid holding references to invalid areas of
+    /// the texture. If the cache has more than one page, the others are dropped, and the first
+    /// page is replaced.
+    pub fn replace_texture(&mut self, mut texture: T) -> T {
+        self.clear();
+        self.cache.pages.truncate(1);
+        let page = &mut self.cache.pages[0];
+        page.packer.reset(texture.width(), texture.height());
+        core::mem::swap(&mut page.texture, &mut texture);
+
+        texture
+    }
+
+    /// Swap out the font the cache was created with for another one, returning the old font
+    ///
+    /// With `clear`, the cache is cleared like [`clear`](FontCache::clear), because glyph IDs
+    /// from one font mean nothing in another. Without it, the cached glyphs, their metrics, and
+    /// the shaping cache are kept as they are, which is only correct if the new font has the
+    /// same glyphs with the same IDs and sizes. That's meant for reloading the same font file,
+    /// like when hot-reloading assets during development; it's up to the caller to make sure
+    /// the fonts match. Fonts added with [`FontCache::add_font`] stay, though clearing also
+    /// removes their glyphs.
+    pub fn replace_font(
+        &mut self,
+        mut font: Box<dyn FontProvider>,
+        clear: bool,
+    ) -> Box<dyn FontProvider> {
+        if clear {
+            self.clear();
+            self.shaping.forget(FontId::DEFAULT);
+        }
+        core::mem::swap(&mut self.cache.font, &mut font);
+
+        font
+    }
+
+    /// Swap the internal texture for a larger one, keeping the glyphs that are already cached
+    ///
+    /// Unlike [`replace_texture`], this doesn't clear the cache. Instead, the contents of the old
+    /// texture are copied into the new one with [`Texture::copy_from`], and packing continues
+    /// where it left off with the extra space available. If the cache has more than one page,
+    /// this grows the first page. The old texture is returned.
+    ///
+    /// Returns [`CacheError::Unsupported`], leaving the cache as it was, if the new texture is
+    /// smaller than the old one in either dimension, the texture doesn't support
+    /// [`Texture::copy_from`], or the height changes with [`TextureOrigin::BottomLeft`].
+    ///
+    /// [`replace_texture`]: FontCache::replace_texture
+    pub fn grow_texture(&mut self, mut texture: T) -> Result<T, CacheError> {
+        let page = &mut self.cache.pages[0];
+        let smaller =
+            texture.width() < page.texture.width() || texture.height() < page.texture.height();
+        // Every glyph's flipped position would move with the bottom edge
+        let flipped_height_changes = self.cache.origin == TextureOrigin::BottomLeft
+            && texture.height() != page.texture.height();
+        if smaller || flipped_height_changes {
+            return Err(CacheError::Unsupported);
+        }
+        texture.copy_from(&page.texture)?;
+        page.packer.grow(texture.width(), texture.height());
+        core::mem::swap(&mut page.texture, &mut texture);
+
+        Ok(texture)
+    }
+
+    /// Where the next glyph of the given size would be placed, without placing it
+    ///
+    /// Only the width and height of `needed` are used, so the [`bounds`](Metrics::bounds) of a
+    /// glyph can be passed as they are. The prediction matches the glyph's
+    /// [`bounds`](TextureGlyph::bounds) if it's the next thing rendered, including when it starts
+    /// a new row or fills a hole left by a removed glyph, and has its width and height swapped if
+    /// it would be [rotated](TextureGlyph::rotated). With several pages, it's on the first page
+    /// that has room. Returns `None` if no page has room, even if the cache could make room by
+    /// adding a page or evicting glyphs.
+    pub fn next_position(&self, needed: Bounds) -> Option<Bounds> {
+        self.cache.next_position(needed.width, needed.height)
+    }
+
+    /// Pack the cached glyphs tightly again, reclaiming the holes left by removed or evicted
+    /// glyphs
+    ///
+    /// Every glyph is rasterized again and written to a new position, tallest first, so the
+    /// [`TextureGlyph`]s handed out before this are no longer valid and should be fetched again
+    /// (like with [`FontCache::get`]). The dirty regions cover everything that moved. Glyphs placed
+    /// with [`FontCache::put_glyph_at`] stay where they are.
+    ///
+    /// If nothing has been removed since the texture was last filled from empty, there are no
+    /// holes to reclaim and this does nothing. If a glyph fails to rasterize or no longer fits
+    /// after the glyphs before it, it's left out of the cache, the rest are still packed, and
+    /// the first error is returned.
+    pub fn compact(&mut self) -> Result<(), CacheError> {
+        self.cache.compact()
+    }
+
+    /// The texture of the first page, which is the only page unless the cache was created with
+    /// [`FontCache::with_pages`]
+    pub fn texture(&self) -> &T {
+        &self.cache.pages[0].texture
+    }
+
+    /// The texture coordinates of a glyph, in the texture of the page it's stored on
+    ///
+    /// See [`TextureGlyph::uv`].
+    ///
+    /// # Panics
+    ///
+    /// If the glyph's page isn't one of this cache's pages
+    pub fn uv_for(&self, glyph: &TextureGlyph) -> [f32; 4] {
+        self.uv_inset_for(glyph, 0.0)
+    }
+
+    /// The texture coordinates of a glyph, moved `texel_margin` pixels inside of its edges
+    ///
+    /// See [`TextureGlyph::uv_inset`].
+    ///
+    /// # Panics
+    ///
+    /// If the glyph's page isn't one of this cache's pages
+    pub fn uv_inset_for(&self, glyph: &TextureGlyph, texel_margin: f32) -> [f32; 4] {
+        let texture = &self.cache.pages[glyph.page as usize].texture;
+
+        glyph.uv_inset(texture.width(), texture.height(), texel_margin)
+    }
+
+    /// The texture of the given page, if the cache has that many pages
+    pub fn page(&self, page: u32) -> Option<&T> {
+        self.cache
+            .pages
+            .get(page as usize)
+            .map(|page| &page.texture)
+    }
+
+    /// Take the area of the first page that has been written to since the last call, if any
+    ///
+    /// This is the union of every glyph rendered to the texture since the dirty region was last
+    /// taken, which lets a GPU-backed texture that batches its writes upload only the part that
+    /// changed. Taking the region resets it, so if nothing has been rendered since, this returns
+    /// `None`. For caches with more than one page, use [`FontCache::take_dirty_regions`].
+    pub fn take_dirty_region(&mut self) -> Option<Bounds> {
+        self.cache.pages[0].dirty.take()
+    }
+
+    /// Take the dirty region of each page that has been written to since the last call
+    ///
+    /// Each region is paired with the index of its page. See [`FontCache::take_dirty_region`].
+    pub fn take_dirty_regions(&mut self) -> Vec<(u32, Bounds)> {
+        self.cache
+            .pages
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, page)| Some((index as u32, page.dirty.take()?)))
+            .collect()
+    }
+
+    /// Take the number of glyphs written to the texture since the last call
+    ///
+    /// Every cache miss that rasterizes a glyph counts, along with glyphs moved by
+    /// [`FontCache::compact`] and placed with [`FontCache::put_glyph_at`]. Cache hits and glyphs
+    /// with nothing to draw don't count, so rendering text that's already cached leaves this at
+    /// 0. Taking the count resets it, which makes it easy to tell which frames changed the
+    /// texture.
+    pub fn take_miss_count(&mut self) -> usize {
+        core::mem::take(&mut self.cache.misses)
+    }
+
+    /// Measure how full the cache is
+    ///
+    /// This is meant for tuning: if the texture is often full, consider making it larger, and if
+    /// a lot of the claimed space is wasted, consider trying another [`Packing`] strategy.
+    pub fn stats(&self) -> CacheStats {
+        let pages = &self.cache.pages;
+        let used_pixels = self
+            .cache
+            .map
+            .values()
+            .map(|cached| {
+                let bounds = &cached.texture_glyph.bounds;
+                bounds.width as u64 * bounds.height as u64
+            })
+            .sum();
+        let claimed: u64 = pages.iter().map(|page| page.packer.claimed_area()).sum();
+
+        CacheStats {
+            glyphs: self.cache.map.len(),
+            pages: pages.len() as u32,
+            used_pixels,
+            texture_pixels: pages
+                .iter()
+                .map(|page| page.texture.width() as u64 * page.texture.height() as u64)
+                .sum(),
+            rows: pages.iter().map(|page| page.packer.rows()).sum(),
+            wasted_pixels: claimed.saturating_sub(used_pixels),
+        }
+    }
+
+    /// The fraction of the texture area covered by cached glyphs, from 0 to 1
+    ///
+    /// This is [`CacheStats::used_pixels`] over [`CacheStats::texture_pixels`], which makes it
+    /// easy to compare packing strategies on the same set of glyphs.
+    pub fn occupancy(&self) -> f32 {
+        let stats = self.stats();
+        if stats.texture_pixels == 0 {
+            return 0.0;
+        }
+
+        (stats.used_pixels as f64 / stats.texture_pixels as f64) as f32
+    }
+
+    /// How many pixels the packer has passed over without filling them, the same as
+    /// [`CacheStats::wasted_pixels`]
+    pub fn wasted_area(&self) -> u64 {
+        self.stats().wasted_pixels
+    }
+
+    /// List the rows the packer has started on each page, for drawing them in a debug overlay
+    ///
+    /// Rows are listed page by page, from the top of each texture to the bottom. Only the shelf
+    /// strategies ([`Packing::Shelf`] and [`Packing::ShelfBucketed`]) pack glyphs in rows, so
+    /// with any other strategy this is empty. Space is left in a row where glyphs were removed,
+    /// so the used width is only how far the row was filled.
+    pub fn rows(&self) -> impl '_ + Iterator<Item = RowInfo> {
+        let cache = &self.cache;
+        cache
+            .pages
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, page)| {
+                page.packer
+                    .row_extents()
+                    .into_iter()
+                    .map(move |(y, height, used_width)| {
+                        let bounds = Bounds {
+                            x: 0,
+                            y: y as i32,
+                            width: used_width,
+                            height,
+                        };
+                        RowInfo {
+                            page: index as u32,
+                            y: cache.to_origin(index, &bounds).y,
+                            height,
+                            used_width,
+                        }
+                    })
+            })
+    }
+
+    /// How many texture pages the cache currently has
+    pub fn page_count(&self) -> u32 {
+        self.cache.pages.len() as u32
+    }
+
+    pub fn font(&self) -> &dyn FontProvider {
+        self.cache.font.as_ref()
+    }
+
+    /// Add another font to the cache, which shares the texture with the cache's main font
+    ///
+    /// Providers have a fixed size, so this is how one cache can hold text at several sizes (or
+    /// in several typefaces): render with the returned ID using [`render_string_with`] and
+    /// [`render_glyph_with`]. The rest of the cache's methods keep using the main font. The
+    /// provider should produce the same kind of pixels as the main font, since they end up in
+    /// the same texture.
+    ///
+    /// [`render_string_with`]: FontCache::render_string_with
+    /// [`render_glyph_with`]: FontCache::render_glyph_with
+    pub fn add_font(&mut self, font: Box<dyn FontProvider>) -> FontId {
+        self.cache.extra_fonts.push(font);
+
+        FontId(self.cache.extra_fonts.len() as u32)
+    }
+
+    /// The provider of one of the cache's fonts, or `None` if the ID isn't from this cache
+    pub fn font_by_id(&self, font: FontId) -> Option<&dyn FontProvider> {
+        self.cache.font_by_id(font)
+    }
+
+    /// The height of a line of text, from [`FontProvider::line_height`]
+    pub fn line_height(&self) -> f32 {
+        self.cache.font.line_height()
+    }
+
+    /// How far glyphs reach above the baseline, from [`FontProvider::ascent`]
+    pub fn ascent(&self) -> f32 {
+        self.cache.font.ascent()
+    }
+
+    /// How far glyphs reach below the baseline (as a negative number), from
+    /// [`FontProvider::descent`]
+    pub fn descent(&self) -> f32 {
+        self.cache.font.descent()
+    }
+
+    /// Whether a glyph is the one the provider uses for characters it doesn't have
+    ///
+    /// See [`FontProvider::notdef`].
+    pub fn is_notdef(&self, glyph: Glyph) -> bool {
+        glyph == self.cache.font.notdef()
+    }
+
+    /// The string that [`render_string`] would convert into glyphs, after normalizing it and
+    /// removing its whitespace (as configured by [`set_normalization`] and
+    /// [`set_keep_whitespace`])
+    ///
+    /// This is useful for lining glyphs up with the characters they came from, like with the
+    /// ranges from [`render_string_with_clusters`].
+    ///
+    /// [`render_string`]: FontCache::render_string
+    /// [`set_normalization`]: FontCache::set_normalization
+    /// [`set_keep_whitespace`]: FontCache::set_keep_whitespace
+    /// [`render_string_with_clusters`]: FontCache::render_string_with_clusters
+    pub fn normalize_for_render(&self, string: &str) -> String {
+        self.prepare_string(string)
+    }
+
+    fn prepare_string(&self, string: &str) -> String {
+        self.prepare_string_with(string, self.keep_whitespace)
+    }
+
+    /// Normalize a string, and strip its whitespace unless `keep_whitespace` is set
+    fn prepare_string_with(&self, string: &str, keep_whitespace: bool) -> String {
+        #[cfg(feature = "unicode-normalization")]
+        let mut string = {
+            use unicode_normalization::UnicodeNormalization;
+            match self.normalization {
+                Some(NormalizationForm::Nfc) => string.nfc().collect::<String>(),
+                Some(NormalizationForm::Nfd) => string.nfd().collect::<String>(),
+                Some(NormalizationForm::Nfkc) => string.nfkc().collect::<String>(),
+                Some(NormalizationForm::Nfkd) => string.nfkd().collect::<String>(),
+                None => string.to_owned(),
+            }
+        };
+        #[cfg(not(feature = "unicode-normalization"))]
+        let mut string = string.to_owned();
+        if !keep_whitespace {
+            string.retain(|c| !c.is_whitespace());
+        }
+
+        string
+    }
+}
+
+impl<T: Texture> Cache<T> {
+    fn clear(&mut self) {
+        self.map.clear();
+        for page in self.pages.iter_mut() {
+            page.packer.clear();
+        }
+    }
+
+    fn next_position(&self, width: u32, height: u32) -> Option<Bounds> {
+        if let Some(max) = self.max_glyph_dimensions {
+            if width > max.0 || height > max.1 {
+                return None;
+            }
+        }
+        self.pages.iter().enumerate().find_map(|(index, page)| {
+            let mut packer = page.packer.clone();
+            let (x, y, rotated) = packer.allocate(width, height, self.allow_rotation)?;
+            let (width, height) = if rotated {
+                (height, width)
+            } else {
+                (width, height)
+            };
+            let bounds = Bounds {
+                x: x as i32,
+                y: y as i32,
+                width,
+                height,
+            };
+
+            Some(self.to_origin(index, &bounds))
+        })
+    }
+
+    fn compact(&mut self) -> Result<(), CacheError> {
+        if !self.pages.iter().any(|page| page.packer.is_fragmented()) {
+            return Ok(());
+        }
+        let mut moving: Vec<_> = self
+            .map
+            .iter()
+            .filter(|(_, cached)| !cached.placed)
+            .map(|(key, cached)| {
+                let moved = (
+                    cached.metrics.clone(),
+                    cached.last_used,
+                    cached.refs.clone(),
+                );
+                (*key, moved)
+            })
+            .collect();
+        self.map.retain(|_, cached| cached.placed);
+        for page in self.pages.iter_mut() {
+            page.packer.clear();
+        }
+        moving.sort_by_key(|(_, (metrics, _, _))| {
+            core::cmp::Reverse(metrics.bounds.map_or(0, |bounds| bounds.height))
+        });
+
+        let mut result = Ok(());
+        for (key, (metrics, last_used, refs)) in moving {
+            let bounds = match metrics.bounds {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            // Tofus don't come from the font, so they're drawn again instead of rasterized
+            let placed = if self.is_tofu(key) {
+                self.place_tofu(key).map(|_| ())
+            } else {
+                self.place(key, metrics, &bounds).map(|_| ())
+            };
+            match placed {
+                Ok(()) => {
+                    if let Some(cached) = self.map.get_mut(&key) {
+                        cached.last_used = last_used;
+                        cached.refs = refs;
+                    }
+                }
+                Err(err) if result.is_ok() => result = Err(err),
+                Err(_) => {}
+            }
+        }
+
+        result
+    }
+
+    fn render_glyph(&mut self, glyph: Glyph) -> Result<(Metrics, TextureGlyph), CacheError> {
+        self.render_key(GlyphKey::new(glyph))
+    }
+
+    /// Mark a glyph as just used, if it's cached, like rendering it would
+    fn touch(&mut self, key: GlyphKey) {
+        self.clock += 1;
+        if let Some(cached) = self.map.get_mut(&key) {
+            cached.last_used = self.clock;
+        }
+    }
+
+    fn render_key(&mut self, key: GlyphKey) -> Result<(Metrics, TextureGlyph), CacheError> {
+        let glyph = key.glyph;
+        self.touch(key);
+        if let Some(cached) = self.map.get(&key) {
+            return Ok((cached.metrics.clone(), cached.texture_glyph));
+        }
+        if glyph == self.provider(key.font).notdef() {
+            if let Some(result) = self.render_missing(key) {
+                return result;
+            }
+        }
+        let metrics = self.metrics(key);
+        let bounds = match metrics.bounds {
+            Some(bounds) => bounds,
+            None => return Ok((metrics, TextureGlyph::empty(glyph))),
+        };
+        let gpu = self.place(key, metrics.clone(), &bounds)?;
+
+        Ok((metrics, gpu))
+    }
+
+    /// Render a notdef glyph according to the [`MissingGlyphPolicy`], or return `None` if it
+    /// should be rendered like any other glyph
+    fn render_missing(
+        &mut self,
+        key: GlyphKey,
+    ) -> Option<Result<(Metrics, TextureGlyph), CacheError>> {
+        let font = self.provider(key.font);
+        let result = match self.missing_glyph {
+            MissingGlyphPolicy::Render => return None,
+            MissingGlyphPolicy::Error => Err(CacheError::MissingGlyph(key.glyph)),
+            MissingGlyphPolicy::Skip => {
+                let metrics = Metrics {
+                    bounds: None,
+                    bounds_f: None,
+                    ..font.metrics(key.glyph)
+                };
+                Ok((metrics, TextureGlyph::empty(key.glyph)))
+            }
+            MissingGlyphPolicy::Tofu => self.place_tofu(key),
+        };
+
+        Some(result)
+    }
+
+    /// Whether a glyph is drawn as a tofu by [`MissingGlyphPolicy::Tofu`], instead of being
+    /// rasterized by its font
+    fn is_tofu(&self, key: GlyphKey) -> bool {
+        self.missing_glyph == MissingGlyphPolicy::Tofu
+            && key.glyph == self.provider(key.font).notdef()
+    }
+
+    /// Find room for a tofu standing in for a missing glyph, and store it there
+    fn place_tofu(&mut self, key: GlyphKey) -> Result<(Metrics, TextureGlyph), CacheError> {
+        let font = self.provider(key.font);
+        let pixel_type = font.glyph_pixel_type(key.glyph);
+        let (metrics, data) = tofu(font, pixel_type);
+        let bounds = metrics.bounds.expect("A tofu always has bounds");
+        let placement = self.allocate(key.glyph, &bounds)?;
+        let gpu = self.store(key, metrics.clone(), placement, pixel_type, &data);
+
+        Ok((metrics, gpu))
+    }
+
+    /// Find room for a glyph that isn't cached, then rasterize and store it there
+    fn place(
+        &mut self,
+        key: GlyphKey,
+        metrics: Metrics,
+        bounds: &Bounds,
+    ) -> Result<TextureGlyph, CacheError> {
+        let (page, bounds, rotated) = self.allocate(key.glyph, bounds)?;
+        let pixel_type = self.provider(key.font).glyph_pixel_type(key.glyph);
+        let data = match self.rasterize(key, &bounds, pixel_type) {
+            Ok(data) => data,
+            Err(err) => {
+                self.pages[page].packer.free(&bounds);
+                return Err(err);
+            }
+        };
+        let gpu = self.store(key, metrics, (page, bounds, rotated), pixel_type, &data[..]);
+        self.scratch = data;
+
+        Ok(gpu)
+    }
+
+    /// The provider for one of the cache's fonts
+    ///
+    /// # Panics
+    ///
+    /// If the font wasn't added to the cache
+    fn provider(&self, font: FontId) -> &dyn FontProvider {
+        self.font_by_id(font)
+            .expect("The font wasn't added to this cache")
+    }
+
+    fn font_by_id(&self, font: FontId) -> Option<&dyn FontProvider> {
+        match font.0 {
+            0 => Some(self.font.as_ref()),
+            id => self
+                .extra_fonts
+                .get(id as usize - 1)
+                .map(|font| font.as_ref()),
+        }
+    }
+
+    /// The metrics of a glyph, with the bounds of the subpixel variant
+    fn metrics(&self, key: GlyphKey) -> Metrics {
+        let font = self.provider(key.font);
+        let mut metrics = font.metrics(key.glyph);
+        if key.subpixel != 0 {
+            let phase = key.phase();
+            metrics.bounds = font.bounds_phased(key.glyph, phase, 0.0);
+            if let Some(bounds) = metrics.bounds_f.as_mut() {
+                bounds.x += phase;
+            }
+        }
+
+        metrics
+    }
+
+    /// Rasterize a glyph into the scratch buffer, and make sure it's the right length
+    ///
+    /// The buffer is taken out of the cache, so it should be put back in `scratch` once the data
+    /// has been stored.
+    fn rasterize(
+        &mut self,
+        key: GlyphKey,
+        bounds: &Bounds,
+        pixel_type: PixelType,
+    ) -> Result<Vec<u8>, CacheError> {
+        let mut data = core::mem::take(&mut self.scratch);
+        data.clear();
+        let font = self.provider(key.font);
+        let rasterized = if key.subpixel == 0 {
+            font.rasterize_into(key.glyph, &mut data)
+        } else {
+            font.rasterize_phased(key.glyph, key.phase(), 0.0)
+                .map(|phased| data.extend_from_slice(&phased))
+        };
+        let result = rasterized.and_then(|()| check_length(bounds, pixel_type, &data));
+        match result {
+            Ok(()) => Ok(data),
+            Err(err) => {
+                self.scratch = data;
+                Err(err)
+            }
+        }
+    }
+
+    /// Find space for a glyph with the given bounds, adding pages or evicting glyphs if needed
+    ///
+    /// Returns the index of the page, the area of it to store the glyph in, and whether the glyph
+    /// has to be rotated to fit that area.
+    fn allocate(
+        &mut self,
+        glyph: Glyph,
+        bounds: &Bounds,
+    ) -> Result<(usize, Bounds, bool), CacheError> {
+        if let Some(max) = self.max_glyph_dimensions {
+            if bounds.width > max.0 || bounds.height > max.1 {
+                return Err(CacheError::GlyphTooLarge {
+                    glyph,
+                    size: (bounds.width, bounds.height),
+                    max,
+                });
+            }
+        }
+        if !self
+            .pages
+            .iter()
+            .any(|page| self.fits(bounds.width, bounds.height, &page.texture))
+        {
+            let textures = self.pages.iter().map(|page| &page.texture);
+            return Err(CacheError::TextureTooSmall {
+                glyph,
+                needed: (bounds.width, bounds.height),
+                available: (
+                    textures.clone().map(T::width).max().unwrap_or(0),
+                    textures.map(T::height).max().unwrap_or(0),
+                ),
+            });
+        }
+        let allow_rotation = self.allow_rotation;
+        loop {
+            let position = self.pages.iter_mut().enumerate().find_map(|(index, page)| {
+                let (x, y, rotated) =
+                    page.packer
+                        .allocate(bounds.width, bounds.height, allow_rotation)?;
+                Some((index, x, y, rotated))
+            });
+            if let Some((page, x, y, rotated)) = position {
+                let (width, height) = if rotated {
+                    (bounds.height, bounds.width)
+                } else {
+                    (bounds.width, bounds.height)
+                };
+                return Ok((
+                    page,
+                    Bounds {
+                        x: x as i32,
+                        y: y as i32,
+                        width,
+                        height,
+                    },
+                    rotated,
+                ));
+            }
+            if !self.reclaim()
+                && !self.add_page(bounds.width, bounds.height)
+                && !self.evict(bounds.width, bounds.height)
+            {
+                return Err(CacheError::OutOfSpace {
+                    glyph,
+                    needed: *bounds,
+                });
+            }
+        }
+    }
+
+    /// Write a rasterized glyph into the space [allocated](Cache::allocate) for it, and remember
+    /// where it is
+    fn store(
+        &mut self,
+        key: GlyphKey,
+        metrics: Metrics,
+        (page, bounds, rotated): (usize, Bounds, bool),
+        pixel_type: PixelType,
+        data: &[u8],
+    ) -> TextureGlyph {
+        let gpu = TextureGlyph {
+            glyph: key.glyph,
+            page: page as u32,
+            bounds: self.to_origin(page, &bounds),
+            rotated,
+        };
+        let rotated_data;
+        let data = if rotated {
+            rotated_data = rotate_clockwise(data, bounds.height, bounds.width, pixel_type);
+            &rotated_data[..]
+        } else {
+            data
+        };
+        let flipped_data;
+        let data = if self.origin == TextureOrigin::BottomLeft {
+            flipped_data = flip_rows(data, bounds.width, pixel_type);
+            &flipped_data[..]
+        } else {
+            data
+        };
+        let page = &mut self.pages[page];
+        page.texture.put_rect(pixel_type, data, &gpu);
+        page.dirty = Some(match page.dirty {
+            Some(dirty) => packer::union(&dirty, &gpu.bounds),
+            None => gpu.bounds,
+        });
+        self.misses += 1;
+        self.map.insert(
+            key,
+            CachedGlyph {
+                texture_glyph: gpu,
+                metrics,
+                last_used: self.clock,
+                placed: false,
+                refs: None,
+            },
+        );
+
+        gpu
+    }
+
+    /// Add a new page big enough for a glyph of the given size, if the cache has a way to
+    /// create pages
+    fn add_page(&mut self, width: u32, height: u32) -> bool {
+        let texture = match &self.new_page {
+            Some(new_page) => new_page(),
+            None => return false,
+        };
+        if !self.fits(width, height, &texture) {
+            return false;
+        }
+        self.pages
+            .push(Page::new(texture, self.padding, self.packing));
+
+        true
+    }
+
+    /// Whether a glyph of the given size fits in a texture, turned sideways if rotation is allowed
+    fn fits(&self, width: u32, height: u32, texture: &T) -> bool {
+        let upright = width <= texture.width() && height <= texture.height();
+        let sideways = height <= texture.width() && width <= texture.height();
+
+        upright || (self.allow_rotation && sideways)
+    }
+
+    /// Start a new call into the cache: until the next one starts, the glyphs it uses can't be
+    /// evicted
+    fn start_window(&mut self) {
+        self.window_start = self.clock + 1;
+    }
+
+    /// Free up space for a glyph of the given size according to the eviction policy, returning
+    /// false if nothing was freed
+    fn evict(&mut self, width: u32, height: u32) -> bool {
+        match self.eviction {
+            EvictionPolicy::Never => false,
+            EvictionPolicy::Lru => {
+                let allow_rotation = self.allow_rotation;
+                let fits = |bounds: &Bounds| {
+                    let upright = bounds.width >= width && bounds.height >= height;
+                    let sideways = bounds.width >= height && bounds.height >= width;
+                    upright || (allow_rotation && sideways)
+                };
+                // Removing a placed glyph wouldn't give the packer any room, and the glyphs used
+                // in the current window may already have been handed out
+                let mut oldest = None;
+                let mut oldest_fitting = None;
+                let candidates = self.map.iter().filter(|(_, cached)| {
+                    !cached.placed
+                        && !cached.is_referenced()
+                        && cached.last_used < self.window_start
+                });
+                for (key, cached) in candidates {
+                    let older = |current: Option<(GlyphKey, u64)>| {
+                        current.is_none_or(|(_, last_used)| cached.last_used < last_used)
+                    };
+                    if older(oldest) {
+                        oldest = Some((*key, cached.last_used));
+                    }
+                    if fits(&cached.texture_glyph.bounds) && older(oldest_fitting) {
+                        oldest_fitting = Some((*key, cached.last_used));
+                    }
+                }
+                // The space of a glyph at least as big as the new one is enough on its own, so
+                // only fall back to the oldest glyph when there isn't one
+                match oldest_fitting.or(oldest) {
+                    Some((key, _)) => {
+                        self.remove(key);
+                        true
+                    }
+                    None => false,
+                }
+            }
+        }
+    }
+
+    /// Convert bounds on a page between the packer's coordinates, which start at the top-left
+    /// corner, and the [`TextureOrigin`]'s (flipping them is the same both ways)
+    fn to_origin(&self, page: usize, bounds: &Bounds) -> Bounds {
+        match self.origin {
+            TextureOrigin::TopLeft => *bounds,
+            TextureOrigin::BottomLeft => Bounds {
+                y: self.pages[page].texture.height() as i32 - bounds.y - bounds.height as i32,
+                ..*bounds
+            },
+        }
+    }
+
+    fn remove(&mut self, key: GlyphKey) -> Option<TextureGlyph> {
+        let cached = self.map.remove(&key)?;
+        let texture_glyph = cached.texture_glyph;
+        if self.map.is_empty() {
+            // Nothing is left in the texture, so start over with a clean slate instead of a
+            // fragmented free-list
+            self.clear();
+        } else if !cached.placed {
+            let page = texture_glyph.page as usize;
+            let bounds = self.to_origin(page, &texture_glyph.bounds);
+            self.pages[page].packer.free(&bounds);
+        }
+
+        Some(texture_glyph)
+    }
+}
+
+/// The index of the font character to render
+///
+/// Glyphs are what actually gets rendered to the screen. It might be tempting to think of a glyph
+/// like a 'rendered character.' In specific scripts, this is often the case. 'A' and 'a' have
+/// distinct glyphs, and are unconditionally the same glyph. In others, this might not be true. See
+/// ['Text Rendering Hates You'](https://gankra.github.io/blah/text-hates-you) for more information
+/// on why text is complicated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Glyph(pub u32);
+
+impl Glyph {
+    /// Glyph 0, which fonts use for characters they don't have
+    ///
+    /// In TrueType and OpenType fonts, glyph 0 is the ".notdef" glyph, which is usually drawn as
+    /// an empty box or a box with a question mark in it (a "tofu"). Every provider in this library
+    /// uses glyph 0 for missing characters (see [`FontProvider::notdef`].)
+    pub const NOTDEF: Glyph = Glyph(0);
+
+    /// Whether this is [`Glyph::NOTDEF`]
+    pub fn is_notdef(self) -> bool {
+        self == Glyph::NOTDEF
+    }
+}
+
+/// A glyph, along with the horizontal offset within a pixel it's rendered at
+///
+/// Most glyphs are rendered at whole pixels, with a `subpixel` of 0. See
+/// [`FontCache::render_glyph_subpixel`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlyphKey {
+    /// The font the glyph comes from, which is [`FontId::DEFAULT`] unless it was rendered with
+    /// one of the fonts added by [`FontCache::add_font`]
+    pub font: FontId,
+    pub glyph: Glyph,
+    /// Which offset the glyph is rendered at: variant `n` is shifted right by
+    /// `n / SUBPIXEL_VARIANTS` of a pixel
+    pub subpixel: u8,
+}
+
+impl GlyphKey {
+    /// How many horizontal offsets within a pixel glyphs can be rendered at
+    pub const SUBPIXEL_VARIANTS: u8 = 3;
+
+    /// The key of a glyph from the cache's main font, rendered at a whole pixel
+    pub fn new(glyph: Glyph) -> Self {
+        GlyphKey {
+            font: FontId::DEFAULT,
+            glyph,
+            subpixel: 0,
+        }
+    }
+
+    /// The same key, for the glyph from another font
+    pub fn with_font(self, font: FontId) -> Self {
+        GlyphKey { font, ..self }
+    }
+
+    /// The key of a glyph drawn with its pen at `x`, with the fractional part of `x` rounded to
+    /// the nearest variant
+    ///
+    /// Rounding can reach the next whole pixel, so this also returns how many pixels to move the
+    /// glyph past `x.floor()` (either 0 or 1).
+    pub fn at_offset(glyph: Glyph, x: f32) -> (Self, i32) {
+        let mut floor = x as i32;
+        if floor as f32 > x {
+            floor -= 1;
+        }
+        let variants = Self::SUBPIXEL_VARIANTS;
+        let subpixel = ((x - floor as f32) * variants as f32 + 0.5) as u8;
+        if subpixel >= variants {
+            (GlyphKey::new(glyph), 1)
+        } else {
+            (
+                GlyphKey {
+                    subpixel,
+                    ..GlyphKey::new(glyph)
+                },
+                0,
+            )
+        }
+    }
+
+    /// How far right the glyph is shifted, as a fraction of a pixel
+    pub fn phase(self) -> f32 {
+        self.subpixel as f32 / Self::SUBPIXEL_VARIANTS as f32
+    }
+}
+
+/// Which of a cache's fonts a glyph belongs to
+///
+/// The font a cache is created with is [`FontId::DEFAULT`], and [`FontCache::add_font`] hands
+/// out an ID for each font added after it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontId(pub u32);
+
+impl FontId {
+    /// The font the cache was created with
+    pub const DEFAULT: FontId = FontId(0);
+}
+
+impl From<Glyph> for GlyphKey {
+    fn from(glyph: Glyph) -> Self {
+        GlyphKey::new(glyph)
+    }
+}
+
+/// The relevant information for a glyph stored on the texture
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureGlyph {
+    pub glyph: Glyph,
+    /// The index of the texture page the glyph is stored on
+    ///
+    /// This is always 0 unless the cache was created with [`FontCache::with_pages`]
+    pub page: u32,
+    pub bounds: Bounds,
+    /// Whether the glyph is stored turned 90° clockwise, so its top-left corner is at the
+    /// top-right corner of `bounds`
+    ///
+    /// `bounds` is the area the glyph covers in the texture, so when this is set its width is the
+    /// glyph's height and vice versa. A renderer should rotate the texture coordinates it draws
+    /// the glyph with to match. This is always false unless rotation is turned on with
+    /// [`FontCache::set_allow_rotation`].
+    pub rotated: bool,
+}
+
+impl TextureGlyph {
+    /// A glyph that takes up no space in the texture, for glyphs with nothing to draw
+    fn empty(glyph: Glyph) -> Self {
+        TextureGlyph {
+            glyph,
+            page: 0,
+            bounds: Bounds {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+            },
+            rotated: false,
+        }
+    }
+
+    /// The area the glyph covers as texture coordinates, for a texture of the given size
+    ///
+    /// Returns `[u0, v0, u1, v1]`: the top-left and bottom-right corners of `bounds`, each
+    /// divided by the texture's size so they go from 0 to 1. The corners are on the edges of
+    /// the glyph's outermost pixels, so the whole glyph is drawn. For a [`rotated`] glyph this
+    /// is still the area in the texture, and the renderer has to rotate it.
+    ///
+    /// [`FontCache::uv_for`] does the same with the size of the texture the glyph is stored on.
+    /// To keep bilinear filtering from blending in neighboring pixels, see
+    /// [`uv_inset`](TextureGlyph::uv_inset).
+    ///
+    /// [`rotated`]: TextureGlyph::rotated
+    pub fn uv(&self, texture_width: u32, texture_height: u32) -> [f32; 4] {
+        self.uv_inset(texture_width, texture_height, 0.0)
+    }
+
+    /// Like [`uv`](TextureGlyph::uv), but with each edge moved `texel_margin` pixels inwards
+    ///
+    /// With bilinear filtering, a sample on the edge of a pixel is an average of the pixels on
+    /// both sides of it, so coordinates exactly on the glyph's edges pick up whatever is next to
+    /// the glyph in the texture: padding, stale data, or another glyph. A margin of 0.5 moves the
+    /// edges to the centers of the outermost pixels, so only the glyph's own pixels are sampled
+    /// when it's drawn at its rasterized size. The cost is that half of each outermost pixel is
+    /// cropped, which is usually faint anti-aliasing. The margin is limited to half the size of
+    /// the glyph, so the area never turns inside out.
+    pub fn uv_inset(&self, texture_width: u32, texture_height: u32, texel_margin: f32) -> [f32; 4] {
+        let (width, height) = (texture_width as f32, texture_height as f32);
+        let bounds = &self.bounds;
+        let margin_x = texel_margin.min(bounds.width as f32 / 2.0);
+        let margin_y = texel_margin.min(bounds.height as f32 / 2.0);
+
+        [
+            (bounds.x as f32 + margin_x) / width,
+            (bounds.y as f32 + margin_y) / height,
+            ((bounds.x + bounds.width as i32) as f32 - margin_x) / width,
+            ((bounds.y + bounds.height as i32) as f32 - margin_y) / height,
+        ]
+    }
+}
+
+/// The layout information for a glyph
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    pub bounds: Option<Bounds>,
+    /// The exact box around the glyph's shape, before it's rounded out to whole pixels
+    ///
+    /// [`bounds`](Metrics::bounds) always covers this box, and is what the glyph is packed and
+    /// rasterized with, so the rasterized glyph is drawn at the integer bounds. This is for
+    /// layout that wants to do its own rounding (like at high DPI), or measure ink precisely.
+    /// Providers that only know the integer bounds (like bitmap fonts) copy them here.
+    pub bounds_f: Option<RectF>,
+    pub bearing_x: f32,
+    pub advance_x: f32,
+    pub bearing_y: f32,
+    pub advance_y: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A rectangle with fractional coordinates, in the same space as [`Bounds`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RectF {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl From<Bounds> for RectF {
+    fn from(bounds: Bounds) -> Self {
+        RectF {
+            x: bounds.x as f32,
+            y: bounds.y as f32,
+            width: bounds.width as f32,
+            height: bounds.height as f32,
+        }
+    }
+}
+
+/// An error generated during a cache operation
+#[derive(Clone, Debug)]
+pub enum CacheError {
+    /// No matter what, the texture is too small to render the glyph (even when empty)
+    ///
+    /// `needed` is the width and height of the glyph, and `available` is the width and height of
+    /// the largest texture page. To fix this error, expand the texture. Make sure to clear the
+    /// cache if the texture data is also invalidated
+    TextureTooSmall {
+        glyph: Glyph,
+        needed: (u32, u32),
+        available: (u32, u32),
+    },
+    /// The cache cannot store the current request without clearing it first
+    ///
+    /// `needed` is the bounds of the glyph that didn't fit, from its [`Metrics`]. With
+    /// [`EvictionPolicy::Lru`], this is only returned if evicting every glyph that wasn't used in
+    /// the current call still didn't make enough room.
+    OutOfSpace { glyph: Glyph, needed: Bounds },
+    /// The glyph is bigger than the limit set with [`FontCache::set_max_glyph_dimensions`]
+    ///
+    /// `size` is the width and height of the glyph, and `max` is the limit.
+    GlyphTooLarge {
+        glyph: Glyph,
+        size: (u32, u32),
+        max: (u32, u32),
+    },
+    /// The area passed to [`FontCache::put_glyph_at`] isn't the same size as the glyph
+    ///
+    /// `size` is the width and height of the glyph's [`Metrics::bounds`], and `area` is the
+    /// width and height of the area.
+    WrongAreaSize {
+        glyph: Glyph,
+        size: (u32, u32),
+        area: (u32, u32),
+    },
+    /// A glyph was passed to a render method but it could not be rendered
+    ///
+    /// Font providers return this from [`FontProvider::rasterize`] for glyphs they can't draw,
+    /// like unsized glyphs (glyphs with None for their [`bounds`]). The cache itself doesn't try
+    /// to rasterize unsized glyphs.
+    ///
+    /// [`bounds`]: Metrics::bounds
+    NonRenderableGlyph(Glyph),
+    /// The operation isn't supported by the texture or font provider
+    Unsupported,
+    /// Saved cache data couldn't be loaded, because it's corrupt, from an incompatible version,
+    /// or doesn't match the texture
+    InvalidSnapshot,
+    /// The font provider rasterized a glyph into the wrong amount of data for its bounds and
+    /// pixel type, which is a bug in the provider
+    MalformedGlyphData { expected: usize, actual: usize },
+    /// Font data passed to a provider's `from_bytes` couldn't be parsed as a font
+    InvalidFont,
+    /// A character the font doesn't have was rendered with [`MissingGlyphPolicy::Error`]
+    ///
+    /// The glyph is the font's [`notdef`](FontProvider::notdef) glyph.
+    MissingGlyph(Glyph),
+    /// The font provider failed for a reason none of the other variants describe, like an error
+    /// from the library it wraps
+    ///
+    /// Providers can create this from any error with [`CacheError::provider`]. The error is kept
+    /// in an `Arc` so that `CacheError` can still be cloned.
+    Provider(Arc<dyn core::error::Error + Send + Sync>),
+}
+
+impl CacheError {
+    /// Wrap an error from a font provider, or the library behind it
+    pub fn provider(error: impl core::error::Error + Send + Sync + 'static) -> Self {
+        CacheError::Provider(Arc::new(error))
+    }
+}
+
+/// Reverse the order of the rows of glyph data, for textures with [`TextureOrigin::BottomLeft`]
+fn flip_rows(data: &[u8], width: u32, pixel_type: PixelType) -> Vec<u8> {
+    let row_length = width as usize * pixel_type.bytes_per_pixel();
+    if row_length == 0 {
+        return data.to_vec();
+    }
+
+    data.rchunks(row_length).flatten().copied().collect()
+}
+
+/// Turn glyph data of the given size 90° clockwise, so its rows become the columns of the
+/// result from right to left
+fn rotate_clockwise(data: &[u8], width: u32, height: u32, pixel_type: PixelType) -> Vec<u8> {
+    let bytes_per_pixel = pixel_type.bytes_per_pixel();
+    let (width, height) = (width as usize, height as usize);
+    let mut rotated = alloc::vec![0; data.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let source = (x + y * width) * bytes_per_pixel;
+            // The result is `height` pixels wide
+            let dest = (height - 1 - y + x * height) * bytes_per_pixel;
+            rotated[dest..dest + bytes_per_pixel]
+                .copy_from_slice(&data[source..source + bytes_per_pixel]);
+        }
+    }
+
+    rotated
+}
+
+/// A rectangle outline to stand in for a missing glyph, and its metrics
+///
+/// The box is three quarters of the font's ascent tall (about the height of a capital letter),
+/// with a pixel of space on either side. Like the font's own glyphs, the box is in physical
+/// pixels and its advance in logical pixels. Every channel of the outline is fully set, so it's
+/// opaque white in color formats.
+fn tofu(font: &dyn FontProvider, pixel_type: PixelType) -> (Metrics, Vec<u8>) {
+    let scale_factor = font.scale_factor();
+    let height = ((font.ascent() * scale_factor * 0.75 + 0.5) as u32).max(3);
+    let width = (height * 3 / 5).max(3);
+    let bytes_per_pixel = pixel_type.bytes_per_pixel();
+    let mut data = alloc::vec![0; (width * height) as usize * bytes_per_pixel];
+    for y in 0..height {
+        for x in 0..width {
+            if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                let start = (x + y * width) as usize * bytes_per_pixel;
+                data[start..start + bytes_per_pixel].fill(255);
+            }
+        }
+    }
+    let bounds = Bounds {
+        x: 1,
+        y: -(height as i32),
+        width,
+        height,
+    };
+    let metrics = Metrics {
+        bounds: Some(bounds),
+        bounds_f: Some(RectF::from(bounds)),
+        bearing_x: 1.0 / scale_factor,
+        advance_x: (width + 2) as f32 / scale_factor,
+        bearing_y: 0.0,
+        advance_y: 0.0,
+    };
+
+    (metrics, data)
+}
+
+/// Make sure rasterized data is the right length before it's written to a texture
+fn check_length(bounds: &Bounds, pixel_type: PixelType, data: &[u8]) -> Result<(), CacheError> {
+    let expected = (bounds.width * bounds.height) as usize * pixel_type.bytes_per_pixel();
+    if data.len() != expected {
+        return Err(CacheError::MalformedGlyphData {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Convert a string into glyphs one character at a time, leaving out joiners and variation
+/// selectors that map to [`Glyph::NOTDEF`]
+///
+/// Those characters only change how their neighbors are drawn, so without shaping the best
+/// thing to do is skip them. Each skipped character joins the cluster of the character before it.
+#[cfg(any(feature = "ab_glyph", feature = "rusttype", feature = "swash"))]
+pub(crate) fn char_glyphs(
+    string: &str,
+    glyphs: &mut Vec<Glyph>,
+    mut clusters: Option<&mut Vec<Range<usize>>>,
+    glyph: impl Fn(char) -> Glyph,
+) {
+    let start = glyphs.len();
+    for (index, character) in string.char_indices() {
+        let end = index + character.len_utf8();
+        let id = glyph(character);
+        if id.is_notdef() && is_joiner(character) {
+            if glyphs.len() > start {
+                if let Some(clusters) = clusters.as_mut().and_then(|clusters| clusters.last_mut()) {
+                    clusters.end = end;
+                }
+            }
+            continue;
+        }
+        glyphs.push(id);
+        if let Some(clusters) = clusters.as_mut() {
+            clusters.push(index..end);
+        }
+    }
+}
+
+/// Whether a character is a zero width (non-)joiner or a variation selector
+#[cfg(any(feature = "ab_glyph", feature = "rusttype", feature = "swash"))]
+fn is_joiner(character: char) -> bool {
+    matches!(
+        character,
+        '\u{200C}' | '\u{200D}' | '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+#[cfg(feature = "std")]
+use std::{error::Error, fmt};
+
+#[cfg(feature = "std")]
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::TextureTooSmall {
+                glyph,
+                needed,
+                available,
+            } => write!(
+                f,
+                "The texture is too small to render {:?}, which is {}x{} while the texture is {}x{}",
+                glyph, needed.0, needed.1, available.0, available.1
+            ),
+            CacheError::OutOfSpace { glyph, needed } => write!(
+                f,
+                "The cache is out of space for {:?} ({}x{}), and must be cleared before more \
+                 rendering",
+                glyph, needed.width, needed.height
+            ),
+            CacheError::GlyphTooLarge { glyph, size, max } => write!(
+                f,
+                "{:?} is {}x{}, which is larger than the maximum glyph size of {}x{}",
+                glyph, size.0, size.1, max.0, max.1
+            ),
+            CacheError::WrongAreaSize { glyph, size, area } => write!(
+                f,
+                "{:?} is {}x{}, but the area to put it in is {}x{}",
+                glyph, size.0, size.1, area.0, area.1
+            ),
+            CacheError::NonRenderableGlyph(glyph) => {
+                write!(f, "Attempted to render an un-renderable glyph: {:?}", glyph)
+            }
+            CacheError::Unsupported => write!(f, "The operation is not supported"),
+            CacheError::InvalidSnapshot => {
+                write!(
+                    f,
+                    "The saved cache data is invalid or doesn't match the texture"
+                )
+            }
+            CacheError::MalformedGlyphData { expected, actual } => write!(
+                f,
+                "The font provider produced {} bytes for a glyph, instead of {}",
+                actual, expected
+            ),
+            CacheError::InvalidFont => write!(f, "The font data couldn't be parsed"),
+            CacheError::MissingGlyph(glyph) => write!(
+                f,
+                "The font doesn't have a glyph for a character, so it was converted to {:?}",
+                glyph
+            ),
+            CacheError::Provider(error) => write!(f, "The font provider failed: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for CacheError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CacheError::Provider(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// How the pixels of the rasterized font are represented
+///
+/// Every channel is one byte. More types may be added in the future, so matches on this should
+/// have a fallback.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PixelType {
+    /// A series of values representing the alpha with no associated color
+    Alpha,
+    /// A series of complete color values
+    RGBA,
+    /// A series of color values with no alpha, for opaque color glyphs
+    RGB,
+    /// A series of grayscale values, which (unlike [`PixelType::Alpha`]) are colors rather than
+    /// coverage
+    Luma,
+    /// A series of red, green, and blue coverage values, for subpixel anti-aliasing on LCD
+    /// displays with a horizontal RGB layout
+    ///
+    /// Each pixel is 3 bytes: how much of the pixel's red, green, and blue subpixels the glyph
+    /// covers. Textures that store RGBA put the coverage in the color channels, and the highest
+    /// of the three in the alpha channel.
+    RGBSubpixel,
+}
+
+impl PixelType {
+    /// How many bytes each pixel of this type takes up
+    ///
+    /// A glyph's data should be exactly `width * height * bytes_per_pixel` bytes long.
+    pub fn bytes_per_pixel(self) -> usize {
+        // Every channel is currently a single byte
+        self.channels()
+    }
+
+    /// How many values make up each pixel of this type
+    pub fn channels(self) -> usize {
+        match self {
+            PixelType::Alpha | PixelType::Luma => 1,
+            PixelType::RGB | PixelType::RGBSubpixel => 3,
+            PixelType::RGBA => 4,
+        }
+    }
+}
+
+/// Convert glyph data into the kind of pixels a texture stores: 1 byte per pixel if `to` is
+/// [`PixelType::Alpha`] or [`PixelType::Luma`], or 4 bytes of RGBA otherwise
+///
+/// Single-channel data is stored as-is in 1 byte textures, and other data keeps its alpha.
+#[cfg(any(feature = "glow", feature = "wgpu"))]
+pub(crate) fn convert_pixels(from: PixelType, to: PixelType, data: &[u8]) -> Cow<'_, [u8]> {
+    use PixelType::*;
+
+    let single_channel = matches!(to, Alpha | Luma);
+    let storage_bytes = if single_channel { 1 } else { 4 };
+    if from.bytes_per_pixel() == storage_bytes && (from == to || from == RGBA) {
+        return Cow::Borrowed(data);
+    }
+    let rgba = |pixel: &[u8]| match from {
+        Alpha if to == RGBSubpixel => [pixel[0]; 4],
+        Alpha => [255, 255, 255, pixel[0]],
+        Luma => [pixel[0], pixel[0], pixel[0], 255],
+        RGB => [pixel[0], pixel[1], pixel[2], 255],
+        RGBA => [pixel[0], pixel[1], pixel[2], pixel[3]],
+        RGBSubpixel => [
+            pixel[0],
+            pixel[1],
+            pixel[2],
+            pixel[0].max(pixel[1]).max(pixel[2]),
+        ],
+    };
+    let pixels = data.chunks_exact(from.bytes_per_pixel());
+    Cow::Owned(if single_channel {
+        pixels
+            .map(|pixel| match from {
+                Alpha | Luma => pixel[0],
+                _ => rgba(pixel)[3],
+            })
+            .collect()
+    } else {
+        pixels.flat_map(rgba).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitmap_provider::{BitmapFontProvider, BitmapGlyph};
+
+    /// A font where every lowercase letter is a solid 2x2 block
+    fn blocks() -> BitmapFontProvider {
+        let glyphs = ('a'..='z').map(|character| BitmapGlyph {
+            character,
+            source: Bounds {
+                x: 0,
+                y: 0,
+                width: 2,
+                height: 2,
+            },
+            offset: (0, -2),
+            advance: 2.0,
+        });
+        BitmapFontProvider::new(PixelType::Alpha, 2, 2, alloc::vec![255; 4], 2.0, glyphs)
+    }
+
+    #[test]
+    fn glyph_buffer_keeps_its_capacity() {
+        let mut cache = FontCache::new(Box::new(blocks()), NullTexture::new(64, 64));
+        cache.cache_string("thequickbrownfox").unwrap();
+        let capacity = cache.glyph_buffer.capacity();
+        assert!(capacity >= 16);
+
+        for string in ["thequickbrownfox", "jumps", "thequickbrownfox"] {
+            cache.render_string(string).for_each(|result| {
+                result.unwrap();
+            });
+            cache.cache_string(string).unwrap();
+            cache.layout_string(string).unwrap();
+            assert_eq!(cache.glyph_buffer.capacity(), capacity);
+        }
+
+        cache.shrink_to_fit_buffers();
+        assert_eq!(cache.glyph_buffer.capacity(), 0);
+        assert_eq!(cache.cache.scratch.capacity(), 0);
+    }
 }
@@ -0,0 +1,79 @@
+use super::*;
+
+use spin::Mutex;
+
+/// Remembers the metrics of each glyph the inner provider is asked about
+///
+/// Rendering a glyph that's already in the texture reuses the metrics stored with it, but the
+/// measuring and layout paths (like [`FontCache::measure_string`] and
+/// [`FontCache::measure_string_bounds`]) ask the provider again every time. For providers
+/// where that's expensive (like ones backed by a shaper), this wrapper only asks the inner
+/// provider once per glyph. Everything else is passed straight through.
+///
+/// The metrics are never forgotten on their own, so call [`MetricsCache::clear`] if the inner
+/// provider's metrics can change.
+pub struct MetricsCache<P: FontProvider> {
+    inner: P,
+    metrics: Mutex<HashMap<Glyph, Metrics>>,
+}
+
+impl<P: FontProvider> MetricsCache<P> {
+    pub fn new(inner: P) -> Self {
+        MetricsCache {
+            inner,
+            metrics: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    /// Forget every remembered glyph's metrics
+    pub fn clear(&self) {
+        self.metrics.lock().clear();
+    }
+}
+
+impl<P: FontProvider> FontProvider for MetricsCache<P> {
+    delegate_to_inner!(
+        pixel_type,
+        glyph_pixel_type,
+        is_color_glyph,
+        single_glyph,
+        has_glyph,
+        covered_chars,
+        notdef,
+        glyphs,
+        glyphs_directional,
+        glyphs_with_clusters,
+        line_height,
+        ascent,
+        descent,
+        vertical_line_width,
+        scale_factor,
+        units_per_em,
+        unscaled_advance,
+        rasterize,
+        rasterize_into,
+        rasterize_async,
+        bounds_phased,
+        rasterize_phased,
+        kerning,
+    );
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        if let Some(metrics) = self.metrics.lock().get(&glyph) {
+            return metrics.clone();
+        }
+        // The lock isn't held while the inner provider works, so other threads aren't blocked
+        let metrics = self.inner.metrics(glyph);
+        self.metrics.lock().insert(glyph, metrics.clone());
+
+        metrics
+    }
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+/// A texture with a size but no pixels, for using the cache without storing any glyphs
+///
+/// Writing to it does nothing, so the cache still packs glyphs and hands out their positions
+/// (and [`FontCache::layout_string`] still works), but no texture memory is allocated. This is
+/// useful for measuring text without a renderer, or for testing how glyphs would pack into a
+/// texture of a given size.
+///
+/// Glyphs are still rasterized when they're first cached, because the cache has to check the
+/// provider's data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NullTexture {
+    width: u32,
+    height: u32,
+}
+
+impl NullTexture {
+    pub fn new(width: u32, height: u32) -> Self {
+        NullTexture { width, height }
+    }
+}
+
+impl Texture for NullTexture {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn put_rect(&mut self, _pixel: PixelType, _data: &[u8], _gpu: &TextureGlyph) {}
+
+    fn clear(&mut self, _pixel: PixelType) {}
+
+    fn copy_from(&mut self, _src: &Self) -> Result<(), CacheError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use super::*;
+
+/// Adds an outline around the glyphs of an Alpha provider, for drawing bordered text without
+/// shader tricks
+///
+/// Each glyph is rasterized by the inner provider and dilated by `thickness` pixels, and the
+/// result is stored as [`PixelType::RGBA`]: red holds the glyph's own coverage, green and alpha
+/// hold the coverage of the outline (which includes the glyph itself), and blue is always 0. A
+/// renderer can then blend the outline color by the green channel and the fill color by the
+/// red channel on top of it.
+///
+/// The bounds of every glyph grow by `thickness` on each side so the outline isn't cut off.
+/// Advances, bearings and kerning are left alone, so outlined text is laid out the same as
+/// plain text, and outlines of neighboring glyphs may overlap.
+pub struct OutlineProvider<P: FontProvider> {
+    inner: P,
+    thickness: u32,
+}
+
+impl<P: FontProvider> OutlineProvider<P> {
+    /// Wrap a provider, giving its glyphs outlines that are `thickness` pixels wide
+    ///
+    /// # Panics
+    ///
+    /// If the inner provider doesn't produce Alpha data
+    pub fn new(inner: P, thickness: u32) -> Self {
+        assert_eq!(inner.pixel_type(), PixelType::Alpha);
+
+        OutlineProvider { inner, thickness }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    pub fn thickness(&self) -> u32 {
+        self.thickness
+    }
+
+    /// Grow bounds by the thickness of the outline on each side
+    fn outline_bounds(&self, bounds: &Bounds) -> Bounds {
+        Bounds {
+            x: bounds.x - self.thickness as i32,
+            y: bounds.y - self.thickness as i32,
+            width: bounds.width + self.thickness * 2,
+            height: bounds.height + self.thickness * 2,
+        }
+    }
+}
+
+impl<P: FontProvider> FontProvider for OutlineProvider<P> {
+    delegate_to_inner!(
+        single_glyph,
+        has_glyph,
+        covered_chars,
+        notdef,
+        glyphs,
+        glyphs_directional,
+        glyphs_with_clusters,
+        line_height,
+        ascent,
+        descent,
+        vertical_line_width,
+        scale_factor,
+        units_per_em,
+        unscaled_advance,
+        kerning,
+    );
+
+    fn pixel_type(&self) -> PixelType {
+        PixelType::RGBA
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        let metrics = self.inner.metrics(glyph);
+        let thickness = self.thickness as f32;
+
+        Metrics {
+            bounds: metrics.bounds.map(|bounds| self.outline_bounds(&bounds)),
+            bounds_f: metrics.bounds_f.map(|bounds| RectF {
+                x: bounds.x - thickness,
+                y: bounds.y - thickness,
+                width: bounds.width + thickness * 2.0,
+                height: bounds.height + thickness * 2.0,
+            }),
+            ..metrics
+        }
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        self.rasterize_phased(glyph, 0.0, 0.0)
+    }
+
+    fn bounds_phased(&self, glyph: Glyph, x_phase: f32, y_phase: f32) -> Option<Bounds> {
+        self.inner
+            .bounds_phased(glyph, x_phase, y_phase)
+            .map(|bounds| self.outline_bounds(&bounds))
+    }
+
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        x_phase: f32,
+        y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        if self.inner.glyph_pixel_type(glyph) != PixelType::Alpha {
+            return Err(CacheError::NonRenderableGlyph(glyph));
+        }
+        let bounds = self
+            .inner
+            .bounds_phased(glyph, x_phase, y_phase)
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+        let coverage = self.inner.rasterize_phased(glyph, x_phase, y_phase)?;
+        check_length(&bounds, PixelType::Alpha, &coverage)?;
+
+        Ok(outline(
+            &coverage,
+            bounds.width,
+            bounds.height,
+            self.thickness,
+        ))
+    }
+
+    #[cfg(feature = "async")]
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        let bounds = self.inner.bounds_phased(glyph, 0.0, 0.0);
+        let bounds = match bounds {
+            Some(bounds) if self.inner.glyph_pixel_type(glyph) == PixelType::Alpha => bounds,
+            _ => {
+                return Box::pin(core::future::ready(Err(CacheError::NonRenderableGlyph(
+                    glyph,
+                ))))
+            }
+        };
+        let future = self.inner.rasterize_async(glyph);
+        let thickness = self.thickness;
+
+        Box::pin(async move {
+            let coverage = future.await?;
+            check_length(&bounds, PixelType::Alpha, &coverage)?;
+
+            Ok(outline(&coverage, bounds.width, bounds.height, thickness))
+        })
+    }
+}
+
+/// Turn the coverage of a glyph with the given size into RGBA data outlined `thickness` pixels
+/// wide
+fn outline(coverage: &[u8], width: u32, height: u32, thickness: u32) -> Vec<u8> {
+    let thickness = thickness as i32;
+    let (width, height) = (width as i32, height as i32);
+    let out_width = width + thickness * 2;
+    let out_height = height + thickness * 2;
+    let coverage_at = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x >= width || y >= height {
+            0
+        } else {
+            coverage[(x + y * width) as usize]
+        }
+    };
+
+    let mut output = Vec::with_capacity((out_width * out_height) as usize * 4);
+    for y in 0..out_height {
+        for x in 0..out_width {
+            // Position in the inner glyph
+            let (source_x, source_y) = (x - thickness, y - thickness);
+            // The outline is the most coverage within a circle of `thickness` pixels
+            let mut outline = 0;
+            for dy in -thickness..=thickness {
+                for dx in -thickness..=thickness {
+                    if dx * dx + dy * dy <= thickness * thickness {
+                        outline = outline.max(coverage_at(source_x + dx, source_y + dy));
+                    }
+                }
+            }
+            let fill = coverage_at(source_x, source_y);
+            output.extend_from_slice(&[fill, outline, 0, outline]);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitmap_provider::{BitmapFontProvider, BitmapGlyph};
+
+    #[test]
+    fn outlines_grow_the_glyph() {
+        let block = BitmapGlyph {
+            character: 'a',
+            source: Bounds {
+                x: 0,
+                y: 0,
+                width: 2,
+                height: 2,
+            },
+            offset: (0, -2),
+            advance: 3.0,
+        };
+        let inner =
+            BitmapFontProvider::new(PixelType::Alpha, 2, 2, alloc::vec![255; 4], 3.0, [block]);
+        let font = OutlineProvider::new(inner, 2);
+        let a = font.single_glyph('a');
+
+        let metrics = font.metrics(a);
+        let bounds = metrics.bounds.unwrap();
+        assert_eq!(
+            (bounds.x, bounds.y, bounds.width, bounds.height),
+            (-2, -4, 6, 6)
+        );
+        assert_eq!(metrics.advance_x, 3.0);
+
+        let data = font.rasterize(a).unwrap();
+        assert_eq!(data.len(), 6 * 6 * 4);
+        let at = |x: usize, y: usize| &data[(x + y * 6) * 4..][..4];
+        // Inside the glyph, both the fill and the outline are covered
+        assert_eq!(at(2, 2), [255, 255, 0, 255]);
+        // Just outside its edge, only the outline is
+        assert_eq!(at(1, 2), [0, 255, 0, 255]);
+        assert_eq!(at(2, 0), [0, 255, 0, 255]);
+        // The corners are further than the thickness from the glyph
+        assert_eq!(at(0, 0), [0, 0, 0, 0]);
+    }
+}
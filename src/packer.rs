@@ -0,0 +1,1067 @@
+use crate::Bounds;
+
+use alloc::vec::Vec;
+
+/// The strategy used to decide where new glyphs go in the texture
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Packing {
+    /// Place glyphs left-to-right in rows, starting a new row when the current one is full
+    ///
+    /// Each row is as tall as the tallest glyph in it, so it wastes space when glyph heights vary
+    /// a lot. This is the default.
+    #[default]
+    Shelf,
+    /// Like [`Packing::Shelf`], but with rows for each range of glyph heights, so a short glyph
+    /// never ends up in a tall glyph's row
+    ///
+    /// Glyph heights are rounded up to a multiple of `bucket` pixels, and every glyph goes into
+    /// the first row for its rounded height that has room, starting a new row below the others
+    /// if there is none. Rows stay open after a new one has started, which wastes a little width
+    /// at the end of each row but keeps much less empty space above short glyphs when sizes are
+    /// mixed together. A `bucket` of 1 only shares rows between glyphs of the same height, and 0
+    /// is treated as 1.
+    ShelfBucketed { bucket: u32 },
+    /// Track the height of the packed area across the texture's width (its "skyline"), and place
+    /// each glyph as low as possible on it (bottom-left)
+    ///
+    /// This packs a mix of tall and short glyphs much more tightly than [`Packing::Shelf`], at the
+    /// cost of a little more work per glyph.
+    Skyline,
+    /// Keep a list of the largest free rectangles in the texture, and place each glyph in the
+    /// one it fills the most of (best area fit)
+    ///
+    /// This usually packs the tightest of the three, and it fills gaps left anywhere in the
+    /// texture instead of only along a frontier. In exchange, every glyph has to be checked
+    /// against every free rectangle, which gets slower as the texture fills with small gaps.
+    MaxRects,
+}
+
+/// Decides where in a texture each new glyph is placed
+///
+/// All of the bookkeeping is done in "slot" space: each glyph occupies its own size plus the
+/// padding to its right and bottom, and the packing area is inflated by the same padding. That
+/// way a glyph touching the edge of the texture doesn't need its padding to fit.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone)]
+pub(crate) struct Packer {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    padding: u32,
+    strategy: Strategy,
+    free: Vec<Bounds>,
+    /// Whether any glyphs have been freed since the packer was cleared, since `free` also holds
+    /// the space added by growing
+    freed: bool,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone)]
+enum Strategy {
+    Shelf {
+        h_cursor: u32,
+        v_cursor: u32,
+        current_line_height: u32,
+        /// The rows above the current one
+        finished: Vec<Row>,
+    },
+    ShelfBucketed {
+        bucket: u32,
+        shelves: Vec<Shelf>,
+        /// The top of the next row to be started
+        v_cursor: u32,
+    },
+    Skyline(Vec<Span>),
+    MaxRects {
+        /// Free rectangles, which overlap each other but never a placed glyph
+        free: Vec<Bounds>,
+        /// The total area handed out, since the free rectangles overlap too much to sum
+        used: u64,
+    },
+}
+
+/// A finished row of the shelf packer
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Row {
+    y: u32,
+    height: u32,
+    /// Where the row's last glyph ended
+    h_cursor: u32,
+}
+
+/// A row of the bucketed shelf packer
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Shelf {
+    y: u32,
+    /// The rounded height the row was started for
+    bucket_height: u32,
+    /// The height of the row, which is less than `bucket_height` if it's cut off by the bottom
+    /// of the texture
+    height: u32,
+    h_cursor: u32,
+}
+
+/// A horizontal section of the skyline, at a given height
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Span {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+impl Packer {
+    pub(crate) fn new(width: u32, height: u32, padding: u32, packing: Packing) -> Self {
+        let strategy = match packing {
+            Packing::Shelf => Strategy::Shelf {
+                h_cursor: 0,
+                v_cursor: 0,
+                current_line_height: 0,
+                finished: Vec::new(),
+            },
+            Packing::ShelfBucketed { bucket } => Strategy::ShelfBucketed {
+                bucket: bucket.max(1),
+                shelves: Vec::new(),
+                v_cursor: 0,
+            },
+            Packing::Skyline => Strategy::Skyline(Vec::new()),
+            Packing::MaxRects => Strategy::MaxRects {
+                free: Vec::new(),
+                used: 0,
+            },
+        };
+        let mut packer = Packer {
+            width,
+            height,
+            padding,
+            strategy,
+            free: Vec::new(),
+            freed: false,
+        };
+        packer.clear();
+
+        packer
+    }
+
+    pub(crate) fn clear(&mut self) {
+        let area_width = self.width + self.padding;
+        let area_height = self.height + self.padding;
+        match &mut self.strategy {
+            Strategy::Shelf {
+                h_cursor,
+                v_cursor,
+                current_line_height,
+                finished,
+            } => {
+                *h_cursor = 0;
+                *v_cursor = 0;
+                *current_line_height = 0;
+                finished.clear();
+            }
+            Strategy::ShelfBucketed {
+                shelves, v_cursor, ..
+            } => {
+                shelves.clear();
+                *v_cursor = 0;
+            }
+            Strategy::Skyline(spans) => {
+                spans.clear();
+                spans.push(Span {
+                    x: 0,
+                    y: 0,
+                    width: area_width,
+                });
+            }
+            Strategy::MaxRects { free, used } => {
+                free.clear();
+                free.push(Bounds {
+                    x: 0,
+                    y: 0,
+                    width: area_width,
+                    height: area_height,
+                });
+                *used = 0;
+            }
+        }
+        self.free.clear();
+        self.freed = false;
+    }
+
+    /// Clear the packer and change the gap left between glyphs
+    pub(crate) fn set_padding(&mut self, padding: u32) {
+        self.padding = padding;
+        self.clear();
+    }
+
+    /// Clear the packer and pick up the dimensions of a new texture
+    pub(crate) fn reset(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.clear();
+    }
+
+    /// Extend the packing area to the dimensions of a larger texture, keeping everything that
+    /// has been packed so far
+    pub(crate) fn grow(&mut self, width: u32, height: u32) {
+        let added = width - self.width;
+        let old_area_width = self.width + self.padding;
+        let old_area_height = self.height + self.padding;
+        self.width = width;
+        self.height = height;
+        if let Strategy::MaxRects { free, .. } = &mut self.strategy {
+            // The new column and the new row are both free, and free rectangles can overlap
+            if added > 0 {
+                free.push(Bounds {
+                    x: old_area_width as i32,
+                    y: 0,
+                    width: added,
+                    height: height + self.padding,
+                });
+            }
+            if height + self.padding > old_area_height {
+                free.push(Bounds {
+                    x: 0,
+                    y: old_area_height as i32,
+                    width: width + self.padding,
+                    height: height + self.padding - old_area_height,
+                });
+            }
+            return;
+        }
+        if added == 0 {
+            return;
+        }
+        match &mut self.strategy {
+            Strategy::Shelf { v_cursor, .. } => {
+                // The finished rows can't grow, but the area to their right is free
+                if *v_cursor > 0 {
+                    self.free.push(Bounds {
+                        x: old_area_width as i32,
+                        y: 0,
+                        width: added,
+                        height: *v_cursor,
+                    });
+                }
+            }
+            // Every row stays open, so they all reach into the new area on their own
+            Strategy::ShelfBucketed { .. } => {}
+            Strategy::Skyline(spans) => match spans.last_mut() {
+                Some(last) if last.y == 0 => last.width += added,
+                _ => spans.push(Span {
+                    x: old_area_width,
+                    y: 0,
+                    width: added,
+                }),
+            },
+            Strategy::MaxRects { .. } => unreachable!(),
+        }
+    }
+
+    /// Whether any space has been freed, leaving holes between the glyphs
+    ///
+    /// The space added by [`grow`](Packer::grow) isn't a hole, so it doesn't count.
+    pub(crate) fn is_fragmented(&self) -> bool {
+        self.freed
+    }
+
+    /// How many rows the shelf packers have started, or 0 for other strategies
+    pub(crate) fn rows(&self) -> u32 {
+        match &self.strategy {
+            Strategy::Shelf {
+                h_cursor, finished, ..
+            } => finished.len() as u32 + (*h_cursor > 0) as u32,
+            Strategy::ShelfBucketed { shelves, .. } => shelves.len() as u32,
+            Strategy::Skyline(_) | Strategy::MaxRects { .. } => 0,
+        }
+    }
+
+    /// The top, height and filled width of each row the shelf packers have started, from top to
+    /// bottom, without the padding after the last glyph of each row
+    ///
+    /// The other strategies don't have rows, so they have none.
+    pub(crate) fn row_extents(&self) -> Vec<(u32, u32, u32)> {
+        let padding = self.padding;
+        let without_padding =
+            |y, height: u32, h_cursor: u32| (y, height - padding, h_cursor - padding);
+        match &self.strategy {
+            Strategy::Shelf {
+                h_cursor,
+                v_cursor,
+                current_line_height,
+                finished,
+            } => {
+                let current = Row {
+                    y: *v_cursor,
+                    height: *current_line_height,
+                    h_cursor: *h_cursor,
+                };
+                finished
+                    .iter()
+                    .chain(Some(&current).filter(|row| row.h_cursor > 0))
+                    .map(|row| without_padding(row.y, row.height, row.h_cursor))
+                    .collect()
+            }
+            Strategy::ShelfBucketed { shelves, .. } => {
+                let mut rows: Vec<_> = shelves
+                    .iter()
+                    .map(|shelf| without_padding(shelf.y, shelf.height, shelf.h_cursor))
+                    .collect();
+                rows.sort_by_key(|(y, _, _)| *y);
+                rows
+            }
+            Strategy::Skyline(_) | Strategy::MaxRects { .. } => Vec::new(),
+        }
+    }
+
+    /// The area that the packer has claimed so far, in slot space, leaving out space that has
+    /// been freed and not handed out again
+    pub(crate) fn claimed_area(&self) -> u64 {
+        let area_width = (self.width + self.padding) as u64;
+        let claimed = match &self.strategy {
+            Strategy::Shelf {
+                h_cursor,
+                v_cursor,
+                current_line_height,
+                ..
+            } => *v_cursor as u64 * area_width + *h_cursor as u64 * *current_line_height as u64,
+            Strategy::ShelfBucketed { shelves, .. } => shelves
+                .iter()
+                .map(|shelf| shelf.h_cursor as u64 * shelf.height as u64)
+                .sum(),
+            Strategy::Skyline(spans) => spans
+                .iter()
+                .map(|span| span.width as u64 * span.y as u64)
+                .sum(),
+            Strategy::MaxRects { used, .. } => *used,
+        };
+        let free: u64 = self
+            .free
+            .iter()
+            .map(|rect| rect.width as u64 * rect.height as u64)
+            .sum();
+
+        claimed.saturating_sub(free)
+    }
+
+    /// Find a spot for a rectangle of the given size, returning its top-left corner and whether
+    /// it was turned sideways
+    ///
+    /// With `allow_rotation`, the rectangle is also tried with its width and height swapped, and
+    /// placed that way if it fits better (or only fits) like that. The padding stays to the right
+    /// and bottom of the rotated rectangle.
+    pub(crate) fn allocate(
+        &mut self,
+        width: u32,
+        height: u32,
+        allow_rotation: bool,
+    ) -> Option<(u32, u32, bool)> {
+        let width = width + self.padding;
+        let height = height + self.padding;
+        let rotate = allow_rotation && width != height;
+
+        self.allocate_free(width, height, rotate)
+            .or_else(|| match self.strategy {
+                Strategy::Shelf { .. } => self.allocate_shelf(width, height, rotate),
+                Strategy::ShelfBucketed { .. } => self.allocate_bucketed(width, height, rotate),
+                Strategy::Skyline(_) => self.allocate_skyline(width, height, rotate),
+                Strategy::MaxRects { .. } => self.allocate_max_rects(width, height, rotate),
+            })
+    }
+
+    /// Return the space used by a glyph at the given bounds to the packer
+    ///
+    /// The space is remembered in a free-list, and will be handed out again by later allocations
+    /// that fit inside of it.
+    pub(crate) fn free(&mut self, bounds: &Bounds) {
+        let mut rect = Bounds {
+            x: bounds.x,
+            y: bounds.y,
+            width: bounds.width + self.padding,
+            height: bounds.height + self.padding,
+        };
+        // Stitch the rectangle back together with any neighbors it shares an entire edge with, so
+        // freeing a row of glyphs produces one large rectangle instead of many slivers
+        while let Some(index) = self.free.iter().position(|other| adjacent(&rect, other)) {
+            let other = self.free.swap_remove(index);
+            rect = union(&rect, &other);
+        }
+        self.free.push(rect);
+        self.freed = true;
+    }
+
+    fn allocate_free(&mut self, width: u32, height: u32, rotate: bool) -> Option<(u32, u32, bool)> {
+        let fits = |rect: &Bounds, width, height| rect.width >= width && rect.height >= height;
+        let index = self
+            .free
+            .iter()
+            .enumerate()
+            .filter(|(_, rect)| fits(rect, width, height) || (rotate && fits(rect, height, width)))
+            .min_by_key(|(_, rect)| rect.width as u64 * rect.height as u64)
+            .map(|(index, _)| index)?;
+        let rect = self.free.swap_remove(index);
+        let rotated = !fits(&rect, width, height);
+        let (width, height) = if rotated {
+            (height, width)
+        } else {
+            (width, height)
+        };
+
+        // Split what remains of the rectangle along its longer leftover axis
+        let right = rect.width - width;
+        let below = rect.height - height;
+        let (right, below) = if right > below {
+            (
+                Bounds {
+                    x: rect.x + width as i32,
+                    y: rect.y,
+                    width: right,
+                    height: rect.height,
+                },
+                Bounds {
+                    x: rect.x,
+                    y: rect.y + height as i32,
+                    width,
+                    height: below,
+                },
+            )
+        } else {
+            (
+                Bounds {
+                    x: rect.x + width as i32,
+                    y: rect.y,
+                    width: right,
+                    height,
+                },
+                Bounds {
+                    x: rect.x,
+                    y: rect.y + height as i32,
+                    width: rect.width,
+                    height: below,
+                },
+            )
+        };
+        for leftover in [right, below].iter() {
+            if leftover.width > 0 && leftover.height > 0 {
+                self.free.push(*leftover);
+            }
+        }
+
+        Some((rect.x as u32, rect.y as u32, rotated))
+    }
+
+    /// Whether a rectangle fits in the current row (`Some(false)`) or needs a new one
+    /// (`Some(true)`)
+    fn shelf_row(&self, width: u32, height: u32) -> Option<bool> {
+        let area_width = self.width + self.padding;
+        let area_height = self.height + self.padding;
+        let (h_cursor, v_cursor, current_line_height) = match self.strategy {
+            Strategy::Shelf {
+                h_cursor,
+                v_cursor,
+                current_line_height,
+                ..
+            } => (h_cursor, v_cursor, current_line_height),
+            _ => unreachable!(),
+        };
+        if width + h_cursor <= area_width && height + v_cursor <= area_height {
+            Some(false)
+        } else if width <= area_width && height + v_cursor + current_line_height <= area_height {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    fn allocate_shelf(
+        &mut self,
+        width: u32,
+        height: u32,
+        rotate: bool,
+    ) -> Option<(u32, u32, bool)> {
+        // Staying in the current row is better than starting a new one
+        let upright = self
+            .shelf_row(width, height)
+            .map(|new_row| (new_row, new_row));
+        let sideways = if rotate {
+            self.shelf_row(height, width)
+                .map(|new_row| (new_row, new_row))
+        } else {
+            None
+        };
+        let (new_row, rotated) = pick_orientation(upright, sideways)?;
+        let (width, height) = if rotated {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let (h_cursor, v_cursor, current_line_height, finished) = match &mut self.strategy {
+            Strategy::Shelf {
+                h_cursor,
+                v_cursor,
+                current_line_height,
+                finished,
+            } => (h_cursor, v_cursor, current_line_height, finished),
+            _ => unreachable!(),
+        };
+        if new_row {
+            if *h_cursor > 0 {
+                finished.push(Row {
+                    y: *v_cursor,
+                    height: *current_line_height,
+                    h_cursor: *h_cursor,
+                });
+            }
+            *h_cursor = 0;
+            *v_cursor += *current_line_height;
+            *current_line_height = 0;
+        }
+        let position = (*h_cursor, *v_cursor, rotated);
+        *h_cursor += width;
+        *current_line_height = (*current_line_height).max(height);
+
+        Some(position)
+    }
+
+    /// The first row for a rectangle's rounded height that has room for it (`Some(Some(index))`),
+    /// or `Some(None)` if it needs a new row
+    fn bucketed_row(&self, width: u32, height: u32) -> Option<Option<usize>> {
+        let area_width = self.width + self.padding;
+        let area_height = self.height + self.padding;
+        let (bucket, shelves, v_cursor) = match &self.strategy {
+            Strategy::ShelfBucketed {
+                bucket,
+                shelves,
+                v_cursor,
+            } => (*bucket, shelves, *v_cursor),
+            _ => unreachable!(),
+        };
+        let bucket_height = round_up(height, bucket);
+        let existing = shelves.iter().position(|shelf| {
+            shelf.bucket_height == bucket_height
+                && height <= shelf.height
+                && shelf.h_cursor + width <= area_width
+        });
+        if existing.is_some() {
+            Some(existing)
+        } else if width <= area_width && v_cursor + height <= area_height {
+            Some(None)
+        } else {
+            None
+        }
+    }
+
+    fn allocate_bucketed(
+        &mut self,
+        width: u32,
+        height: u32,
+        rotate: bool,
+    ) -> Option<(u32, u32, bool)> {
+        // Sharing a row is better than starting a new one
+        let score = |width, height| {
+            self.bucketed_row(width, height)
+                .map(|row| (row, row.is_none()))
+        };
+        let upright = score(width, height);
+        let sideways = if rotate { score(height, width) } else { None };
+        let (row, rotated) = pick_orientation(upright, sideways)?;
+        let (width, height) = if rotated {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let area_height = self.height + self.padding;
+        let (bucket, shelves, v_cursor) = match &mut self.strategy {
+            Strategy::ShelfBucketed {
+                bucket,
+                shelves,
+                v_cursor,
+            } => (*bucket, shelves, v_cursor),
+            _ => unreachable!(),
+        };
+        let index = match row {
+            Some(index) => index,
+            None => {
+                let bucket_height = round_up(height, bucket);
+                shelves.push(Shelf {
+                    y: *v_cursor,
+                    bucket_height,
+                    height: bucket_height.min(area_height - *v_cursor),
+                    h_cursor: 0,
+                });
+                *v_cursor += shelves[shelves.len() - 1].height;
+                shelves.len() - 1
+            }
+        };
+        let shelf = &mut shelves[index];
+        let position = (shelf.h_cursor, shelf.y, rotated);
+        shelf.h_cursor += width;
+
+        Some(position)
+    }
+
+    /// The lowest position along the skyline for a rectangle, breaking ties by leftmost, as the
+    /// index of the span it starts on and its top-left corner
+    fn skyline_spot(&self, width: u32, height: u32) -> Option<(usize, u32, u32)> {
+        let area_width = self.width + self.padding;
+        let area_height = self.height + self.padding;
+        let spans = match &self.strategy {
+            Strategy::Skyline(spans) => spans,
+            _ => unreachable!(),
+        };
+
+        (0..spans.len())
+            .filter_map(|index| {
+                let x = spans[index].x;
+                if x + width > area_width {
+                    return None;
+                }
+                // The glyph has to rest on the highest span it covers
+                let mut y = 0;
+                let mut covered = 0;
+                for span in &spans[index..] {
+                    if covered >= width {
+                        break;
+                    }
+                    y = y.max(span.y);
+                    covered += span.width;
+                }
+                if y + height > area_height {
+                    return None;
+                }
+                Some((index, x, y))
+            })
+            .min_by_key(|&(_, x, y)| (y, x))
+    }
+
+    fn allocate_skyline(
+        &mut self,
+        width: u32,
+        height: u32,
+        rotate: bool,
+    ) -> Option<(u32, u32, bool)> {
+        // Compare how far down the rectangle would reach, to keep the skyline as low as possible
+        let score = |width, height| {
+            self.skyline_spot(width, height)
+                .map(|spot| (spot, (spot.2 + height, spot.1)))
+        };
+        let upright = score(width, height);
+        let sideways = if rotate { score(height, width) } else { None };
+        let ((index, x, y), rotated) = pick_orientation(upright, sideways)?;
+        let (width, height) = if rotated {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let spans = match &mut self.strategy {
+            Strategy::Skyline(spans) => spans,
+            _ => unreachable!(),
+        };
+
+        spans.insert(
+            index,
+            Span {
+                x,
+                y: y + height,
+                width,
+            },
+        );
+        // Cut away the parts of the skyline now covered by the new span
+        let right = x + width;
+        while index + 1 < spans.len() && spans[index + 1].x < right {
+            let next = &mut spans[index + 1];
+            let overlap = right - next.x;
+            if next.width <= overlap {
+                spans.remove(index + 1);
+            } else {
+                next.x += overlap;
+                next.width -= overlap;
+                break;
+            }
+        }
+        // Merge neighboring spans at the same height
+        let mut i = 0;
+        while i + 1 < spans.len() {
+            if spans[i].y == spans[i + 1].y {
+                spans[i].width += spans[i + 1].width;
+                spans.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+
+        Some((x, y, rotated))
+    }
+
+    /// The free rectangle with the least area left over after placing a rectangle in it,
+    /// breaking ties by the shorter leftover side, along with that score
+    fn max_rects_spot(&self, width: u32, height: u32) -> Option<(Bounds, (u64, u32))> {
+        let free = match &self.strategy {
+            Strategy::MaxRects { free, .. } => free,
+            _ => unreachable!(),
+        };
+
+        free.iter()
+            .filter(|rect| rect.width >= width && rect.height >= height)
+            .map(|rect| {
+                let leftover =
+                    rect.width as u64 * rect.height as u64 - width as u64 * height as u64;
+                let short_side = (rect.width - width).min(rect.height - height);
+                (*rect, (leftover, short_side))
+            })
+            .min_by_key(|(_, score)| *score)
+    }
+
+    fn allocate_max_rects(
+        &mut self,
+        width: u32,
+        height: u32,
+        rotate: bool,
+    ) -> Option<(u32, u32, bool)> {
+        let upright = self.max_rects_spot(width, height);
+        let sideways = if rotate {
+            self.max_rects_spot(height, width)
+        } else {
+            None
+        };
+        let (rect, rotated) = pick_orientation(upright, sideways)?;
+        let (width, height) = if rotated {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let (free, used) = match &mut self.strategy {
+            Strategy::MaxRects { free, used } => (free, used),
+            _ => unreachable!(),
+        };
+        let placed = Bounds {
+            x: rect.x,
+            y: rect.y,
+            width,
+            height,
+        };
+
+        // Replace every free rectangle the glyph overlaps with the parts of it that are left
+        let mut index = 0;
+        while index < free.len() {
+            if overlaps(&free[index], &placed) {
+                let other = free.swap_remove(index);
+                subtract(&other, &placed, free);
+            } else {
+                index += 1;
+            }
+        }
+        // Drop rectangles that are inside of others, which keeps the list from growing forever
+        let mut index = 0;
+        while index < free.len() {
+            let contained = free
+                .iter()
+                .enumerate()
+                .any(|(other, rect)| other != index && contains(rect, &free[index]));
+            if contained {
+                free.swap_remove(index);
+            } else {
+                index += 1;
+            }
+        }
+        *used += width as u64 * height as u64;
+
+        Some((placed.x as u32, placed.y as u32, rotated))
+    }
+}
+
+/// Choose between an upright and a sideways spot by whichever has the lower score, preferring
+/// upright on ties, and return whether the sideways one was picked
+fn pick_orientation<T, S: Ord>(
+    upright: Option<(T, S)>,
+    sideways: Option<(T, S)>,
+) -> Option<(T, bool)> {
+    match (upright, sideways) {
+        (Some((upright, score)), Some((sideways, sideways_score))) => {
+            if sideways_score < score {
+                Some((sideways, true))
+            } else {
+                Some((upright, false))
+            }
+        }
+        (Some((upright, _)), None) => Some((upright, false)),
+        (None, Some((sideways, _))) => Some((sideways, true)),
+        (None, None) => None,
+    }
+}
+
+/// Round a number up to the nearest multiple of `step`
+fn round_up(value: u32, step: u32) -> u32 {
+    value.div_ceil(step) * step
+}
+
+fn overlaps(a: &Bounds, b: &Bounds) -> bool {
+    a.x < b.x + b.width as i32
+        && b.x < a.x + a.width as i32
+        && a.y < b.y + b.height as i32
+        && b.y < a.y + a.height as i32
+}
+
+/// Whether `outer` covers all of `inner`
+fn contains(outer: &Bounds, inner: &Bounds) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.width as i32 <= outer.x + outer.width as i32
+        && inner.y + inner.height as i32 <= outer.y + outer.height as i32
+}
+
+/// Push the maximal rectangles of `rect` that don't overlap `cut`, on each of its four sides
+fn subtract(rect: &Bounds, cut: &Bounds, out: &mut Vec<Bounds>) {
+    let right = rect.x + rect.width as i32;
+    let bottom = rect.y + rect.height as i32;
+    let cut_right = cut.x + cut.width as i32;
+    let cut_bottom = cut.y + cut.height as i32;
+    let sides = [
+        Bounds {
+            x: rect.x,
+            y: rect.y,
+            width: (cut.x - rect.x).max(0) as u32,
+            height: rect.height,
+        },
+        Bounds {
+            x: cut_right,
+            y: rect.y,
+            width: (right - cut_right).max(0) as u32,
+            height: rect.height,
+        },
+        Bounds {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: (cut.y - rect.y).max(0) as u32,
+        },
+        Bounds {
+            x: rect.x,
+            y: cut_bottom,
+            width: rect.width,
+            height: (bottom - cut_bottom).max(0) as u32,
+        },
+    ];
+
+    out.extend(
+        sides
+            .iter()
+            .filter(|side| side.width > 0 && side.height > 0),
+    );
+}
+
+fn adjacent(a: &Bounds, b: &Bounds) -> bool {
+    let horizontal = a.y == b.y
+        && a.height == b.height
+        && (a.x + a.width as i32 == b.x || b.x + b.width as i32 == a.x);
+    let vertical = a.x == b.x
+        && a.width == b.width
+        && (a.y + a.height as i32 == b.y || b.y + b.height as i32 == a.y);
+
+    horizontal || vertical
+}
+
+pub(crate) fn union(a: &Bounds, b: &Bounds) -> Bounds {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    let right = (a.x + a.width as i32).max(b.x + b.width as i32);
+    let bottom = (a.y + a.height as i32).max(b.y + b.height as i32);
+
+    Bounds {
+        x,
+        y,
+        width: (right - x) as u32,
+        height: (bottom - y) as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn growing_does_not_fragment() {
+        for packing in [
+            Packing::Shelf,
+            Packing::ShelfBucketed { bucket: 4 },
+            Packing::Skyline,
+            Packing::MaxRects,
+        ] {
+            let mut packer = Packer::new(16, 16, 1, packing);
+            let (x, y, _) = packer.allocate(7, 7, false).unwrap();
+            packer.allocate(7, 7, false).unwrap();
+            packer.grow(32, 16);
+            assert!(!packer.is_fragmented());
+
+            packer.free(&Bounds {
+                x: x as i32,
+                y: y as i32,
+                width: 7,
+                height: 7,
+            });
+            assert!(packer.is_fragmented());
+            packer.clear();
+            assert!(!packer.is_fragmented());
+        }
+    }
+
+    /// Glyph sizes from 1 to `max` pixels on each side, from a seeded generator so failures can
+    /// be reproduced
+    fn random_sizes(seed: u64, max: u32) -> impl Iterator<Item = (u32, u32)> {
+        let mut state = seed;
+        let mut next = move || {
+            // The constants of Knuth's MMIX generator
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as u32 % max + 1
+        };
+        core::iter::repeat_with(move || (next(), next()))
+    }
+
+    /// Offer a packer glyphs of random sizes until it turns down enough of them in a row, and
+    /// return the bounds of the ones it placed
+    fn fill(packer: &mut Packer, seed: u64, max: u32, allow_rotation: bool) -> Vec<Bounds> {
+        let mut placed = Vec::new();
+        let mut misses = 0;
+        for (width, height) in random_sizes(seed, max) {
+            match packer.allocate(width, height, allow_rotation) {
+                Some((x, y, rotated)) => {
+                    let (width, height) = if rotated {
+                        (height, width)
+                    } else {
+                        (width, height)
+                    };
+                    placed.push(Bounds {
+                        x: x as i32,
+                        y: y as i32,
+                        width,
+                        height,
+                    });
+                    misses = 0;
+                }
+                None if misses == 50 => break,
+                None => misses += 1,
+            }
+        }
+
+        placed
+    }
+
+    /// Check that every glyph is inside the texture, and that no two glyphs (with their padding)
+    /// overlap
+    fn assert_disjoint(placed: &[Bounds], width: u32, height: u32, padding: u32) {
+        let slots: Vec<_> = placed
+            .iter()
+            .map(|bounds| {
+                assert!(bounds.x >= 0 && bounds.y >= 0);
+                assert!(bounds.x as u32 + bounds.width <= width);
+                assert!(bounds.y as u32 + bounds.height <= height);
+                Bounds {
+                    width: bounds.width + padding,
+                    height: bounds.height + padding,
+                    ..*bounds
+                }
+            })
+            .collect();
+        for (i, a) in slots.iter().enumerate() {
+            for b in &slots[i + 1..] {
+                assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn skyline_never_overlaps() {
+        for seed in 0..20 {
+            let mut packer = Packer::new(64, 64, 1, Packing::Skyline);
+            let placed = fill(&mut packer, seed, 12, false);
+            assert!(placed.len() > 20);
+            assert_disjoint(&placed, 64, 64, 1);
+        }
+    }
+
+    const EVERY_PACKING: [Packing; 4] = [
+        Packing::Shelf,
+        Packing::ShelfBucketed { bucket: 4 },
+        Packing::Skyline,
+        Packing::MaxRects,
+    ];
+
+    #[test]
+    fn no_packing_overlaps() {
+        for packing in EVERY_PACKING {
+            for allow_rotation in [false, true] {
+                for seed in 0..20 {
+                    let mut packer = Packer::new(64, 64, 1, packing);
+                    let mut placed = fill(&mut packer, seed, 12, allow_rotation);
+                    assert!(placed.len() > 20);
+                    assert_disjoint(&placed, 64, 64, 1);
+
+                    // Free every other glyph, and fill the holes again
+                    let mut index = 0;
+                    placed.retain(|bounds| {
+                        index += 1;
+                        if index % 2 == 0 {
+                            packer.free(bounds);
+                        }
+                        index % 2 != 0
+                    });
+                    placed.extend(fill(&mut packer, seed + 100, 12, allow_rotation));
+                    assert_disjoint(&placed, 64, 64, 1);
+                }
+            }
+        }
+    }
+
+    /// Area placed by each strategy on the same glyph sequences, before the
+    /// first 50 misses in a row
+    fn occupancy(packing: Packing) -> u64 {
+        (0..20)
+            .map(|seed| {
+                let mut packer = Packer::new(128, 128, 1, packing);
+                fill(&mut packer, seed, 24, false)
+                    .iter()
+                    .map(|bounds| bounds.width as u64 * bounds.height as u64)
+                    .sum::<u64>()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn better_strategies_place_more() {
+        let shelf = occupancy(Packing::Shelf);
+        let skyline = occupancy(Packing::Skyline);
+        let max_rects = occupancy(Packing::MaxRects);
+        assert!(skyline > shelf, "skyline {} <= shelf {}", skyline, shelf);
+        assert!(
+            max_rects > skyline,
+            "max rects {} <= skyline {}",
+            max_rects,
+            skyline
+        );
+    }
+
+    /// How far down the texture a packer placed glyphs that alternate between short (like
+    /// lowercase letters) and tall (like capitals with descenders)
+    fn alternating_height(packing: Packing) -> u32 {
+        let mut packer = Packer::new(32, 256, 0, packing);
+        (0..64)
+            .map(|index| {
+                let height = if index % 2 == 0 { 3 } else { 8 };
+                let (_, y, _) = packer.allocate(4, height, false).unwrap();
+                y + height
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn buckets_keep_short_glyphs_out_of_tall_rows() {
+        // Every row of plain shelves holds both sizes, so they're all as tall as the tall glyphs
+        assert_eq!(alternating_height(Packing::Shelf), 64);
+        // With buckets, the short and the tall glyphs each get rows of their own
+        assert_eq!(alternating_height(Packing::ShelfBucketed { bucket: 4 }), 48);
+    }
+}
@@ -0,0 +1,113 @@
+use super::*;
+
+impl<T: Texture> FontCache<T> {
+    /// Render a string and lay it out as a paragraph, wrapping lines so they fit in `max_width`
+    ///
+    /// This is a convenience for simple UI text, not a replacement for a real text layout
+    /// library. Lines are broken at newlines, and wrapped at whitespace: a word that would end
+    /// past `max_width` moves to the next line, along with the rest of the line, and the
+    /// whitespace where a line was wrapped is dropped. A word that's wider than `max_width` on
+    /// its own isn't split, and overflows its line. There's no hyphenation, justification, or
+    /// bidirectional text.
+    ///
+    /// Each line is laid out like [`layout_line`](FontCache::layout_line), with the first line
+    /// starting at (0, 0) and each line after it [`line_height`](FontProvider::line_height)
+    /// further down. Whitespace is always kept, since it's needed to find the words, and glyphs
+    /// with nothing to draw aren't included in the result. Glyphs are kerned within a line, and
+    /// widths are measured with the glyphs' advances rather than their ink, so `max_width` is in
+    /// logical pixels.
+    pub fn layout_paragraph(
+        &mut self,
+        string: &str,
+        max_width: f32,
+    ) -> Result<Vec<GlyphInstance>, CacheError> {
+        let string = self.prepare_string_with(string, true);
+        self.cache.start_window();
+        let ascent = self.cache.font.ascent();
+        let line_height = self.cache.font.line_height();
+        let mut glyphs = core::mem::take(&mut self.glyph_buffer);
+        let mut clusters = core::mem::take(&mut self.cluster_buffer);
+        let mut instances = Vec::new();
+        let mut top = 0.0;
+        let mut result = Ok(());
+        for line in string.split('\n') {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            glyphs.clear();
+            clusters.clear();
+            self.cache
+                .font
+                .glyphs_with_clusters(line, &mut glyphs, &mut clusters);
+            let rendered = match glyphs
+                .iter()
+                .map(|glyph| self.cache.render_glyph(*glyph))
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(rendered) => rendered,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            };
+            let is_space: Vec<bool> = clusters
+                .iter()
+                .map(|cluster| line[cluster.clone()].chars().all(char::is_whitespace))
+                .collect();
+
+            let mut x = 0.0;
+            let mut previous = None;
+            let mut wrapped = false;
+            let mut start = 0;
+            while start < glyphs.len() {
+                // Each run of glyphs is either a word or the whitespace between two words
+                let space = is_space[start];
+                let end = (start..glyphs.len())
+                    .find(|index| is_space[*index] != space)
+                    .unwrap_or(glyphs.len());
+                let run = start..end;
+                start = end;
+                if space && wrapped && previous.is_none() {
+                    continue;
+                }
+                if let Some(last) = previous.filter(|_| !space) {
+                    let width = self.run_width(&glyphs[run.clone()], &rendered[run.clone()]);
+                    let kerning = self.cache.font.kerning(last, glyphs[run.start]);
+                    if x + kerning + width > max_width {
+                        top += line_height;
+                        x = 0.0;
+                        previous = None;
+                        wrapped = true;
+                    }
+                }
+                for index in run {
+                    let glyph = glyphs[index];
+                    if let Some(previous) = previous {
+                        x += self.cache.font.kerning(previous, glyph);
+                    }
+                    let (metrics, texture_glyph) = &rendered[index];
+                    instances.extend(self.instance(texture_glyph, metrics, (x, top + ascent)));
+                    x += metrics.advance_x;
+                    previous = Some(glyph);
+                }
+            }
+            top += line_height;
+        }
+        self.glyph_buffer = glyphs;
+        self.cluster_buffer = clusters;
+
+        result.map(|_| instances)
+    }
+
+    /// The total advance of a run of glyphs, including the kerning between them
+    fn run_width(&self, glyphs: &[Glyph], rendered: &[(Metrics, TextureGlyph)]) -> f32 {
+        let kerning: f32 = glyphs
+            .windows(2)
+            .map(|pair| self.cache.font.kerning(pair[0], pair[1]))
+            .sum();
+
+        rendered
+            .iter()
+            .map(|(metrics, _)| metrics.advance_x)
+            .sum::<f32>()
+            + kerning
+    }
+}
@@ -0,0 +1,160 @@
+use super::*;
+
+use serde::{Deserialize, Serialize};
+
+/// Written at the start of every save, followed by the format version
+const MAGIC: &[u8; 4] = b"ELEF";
+const VERSION: u16 = 11;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    keep_whitespace: bool,
+    eviction: EvictionPolicy,
+    padding: u32,
+    packing: Packing,
+    allow_rotation: bool,
+    max_glyph_dimensions: Option<(u32, u32)>,
+    missing_glyph: MissingGlyphPolicy,
+    origin: TextureOrigin,
+    packer: Packer,
+    glyphs: Vec<SavedGlyph>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedGlyph {
+    key: GlyphKey,
+    texture_glyph: TextureGlyph,
+    placed: bool,
+    pixel_type: PixelType,
+    data: Vec<u8>,
+}
+
+impl<T: Texture> FontCache<T> {
+    /// Save the state of the cache, including the data of each cached glyph
+    ///
+    /// This lets an application skip rendering a fixed set of glyphs every time it starts, by
+    /// restoring the cache with [`FontCache::load`]. The glyph data is read back from the
+    /// texture, so this returns [`CacheError::Unsupported`] if the texture doesn't support
+    /// [`Texture::read_rect`], if the cache has more than one page, or if fonts have been added
+    /// with [`FontCache::add_font`].
+    pub fn save(&self) -> Result<Vec<u8>, CacheError> {
+        if self.cache.pages.len() > 1 || !self.cache.extra_fonts.is_empty() {
+            return Err(CacheError::Unsupported);
+        }
+        let page = &self.cache.pages[0];
+        let glyphs = self
+            .cache
+            .map
+            .iter()
+            .map(|(key, cached)| {
+                let pixel_type = self.cache.font.glyph_pixel_type(key.glyph);
+                let mut data = Vec::new();
+                page.texture
+                    .read_rect(pixel_type, &cached.texture_glyph.bounds, &mut data)?;
+                Ok(SavedGlyph {
+                    key: *key,
+                    texture_glyph: cached.texture_glyph,
+                    placed: cached.placed,
+                    pixel_type,
+                    data,
+                })
+            })
+            .collect::<Result<_, CacheError>>()?;
+        let snapshot = Snapshot {
+            keep_whitespace: self.keep_whitespace,
+            eviction: self.cache.eviction,
+            padding: self.cache.padding,
+            packing: self.cache.packing,
+            allow_rotation: self.cache.allow_rotation,
+            max_glyph_dimensions: self.cache.max_glyph_dimensions,
+            missing_glyph: self.cache.missing_glyph,
+            origin: self.cache.origin,
+            packer: page.packer.clone(),
+            glyphs,
+        };
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        let body = postcard::to_allocvec(&snapshot).expect("Failed to serialize the cache");
+        bytes.extend_from_slice(&body);
+
+        Ok(bytes)
+    }
+
+    /// Restore a cache saved with [`FontCache::save`]
+    ///
+    /// The font should be the same one the cache was saved with, because glyphs are only
+    /// meaningful to the font that produced them. The texture has to be the same size as the
+    /// saved one; the glyphs are written back into it.
+    ///
+    /// Returns [`CacheError::InvalidSnapshot`] if the data isn't a save from a compatible version
+    /// of this library, or doesn't match the size of the texture.
+    pub fn load(font: Box<dyn FontProvider>, texture: T, bytes: &[u8]) -> Result<Self, CacheError> {
+        let header_length = MAGIC.len() + 2;
+        if bytes.len() < header_length || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(CacheError::InvalidSnapshot);
+        }
+        let version = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+        if version != VERSION {
+            return Err(CacheError::InvalidSnapshot);
+        }
+        let snapshot: Snapshot = postcard::from_bytes(&bytes[header_length..])
+            .map_err(|_| CacheError::InvalidSnapshot)?;
+        if snapshot.packer.width != texture.width() || snapshot.packer.height != texture.height() {
+            return Err(CacheError::InvalidSnapshot);
+        }
+
+        let mut cache = FontCache::builder(font, texture)
+            .padding(snapshot.padding)
+            .packing(snapshot.packing)
+            .allow_rotation(snapshot.allow_rotation)
+            .max_glyph_dimensions(snapshot.max_glyph_dimensions)
+            .missing_glyph_policy(snapshot.missing_glyph)
+            .texture_origin(snapshot.origin)
+            .eviction(snapshot.eviction)
+            .keep_whitespace(snapshot.keep_whitespace)
+            .build();
+        cache.cache.pages[0].packer = snapshot.packer;
+        for saved in snapshot.glyphs {
+            let page = &mut cache.cache.pages[0];
+            let bounds = &saved.texture_glyph.bounds;
+            let fits = bounds.x >= 0
+                && bounds.y >= 0
+                && bounds.x as u32 + bounds.width <= page.texture.width()
+                && bounds.y as u32 + bounds.height <= page.texture.height();
+            let expected =
+                (bounds.width * bounds.height) as usize * saved.pixel_type.bytes_per_pixel();
+            let key_matches = saved.key.glyph == saved.texture_glyph.glyph
+                && saved.key.font == FontId::DEFAULT
+                && saved.key.subpixel < GlyphKey::SUBPIXEL_VARIANTS;
+            if !fits
+                || !key_matches
+                || saved.data.len() != expected
+                || saved.texture_glyph.page != 0
+            {
+                return Err(CacheError::InvalidSnapshot);
+            }
+            page.texture
+                .put_rect(saved.pixel_type, &saved.data, &saved.texture_glyph);
+            let font = cache.cache.font.as_ref();
+            let metrics = if cache.cache.is_tofu(saved.key) {
+                tofu(font, saved.pixel_type).0
+            } else {
+                cache.cache.metrics(saved.key)
+            };
+            cache.cache.map.insert(
+                saved.key,
+                CachedGlyph {
+                    texture_glyph: saved.texture_glyph,
+                    metrics,
+                    last_used: 0,
+                    placed: saved.placed,
+                    refs: None,
+                },
+            );
+        }
+
+        Ok(cache)
+    }
+}
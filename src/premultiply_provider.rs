@@ -0,0 +1,122 @@
+use super::*;
+
+/// Premultiplies the color of an RGBA provider's glyphs by their alpha
+///
+/// Color glyphs (like emoji) are usually stored with straight alpha, but GPU blending with
+/// `(1, 1 - source alpha)` expects the color to already be multiplied by the alpha, and draws
+/// dark or bright fringes around the edges of straight alpha glyphs. This wrapper converts the
+/// data before it reaches the texture, so the texture holds premultiplied alpha throughout.
+///
+/// Only glyphs with a [`glyph_pixel_type`](FontProvider::glyph_pixel_type) of
+/// [`PixelType::RGBA`] are converted; every other glyph, and everything besides rasterizing, is
+/// passed straight through.
+pub struct PremultiplyProvider<P: FontProvider> {
+    inner: P,
+}
+
+impl<P: FontProvider> PremultiplyProvider<P> {
+    pub fn new(inner: P) -> Self {
+        PremultiplyProvider { inner }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+impl<P: FontProvider> FontProvider for PremultiplyProvider<P> {
+    delegate_to_inner!(
+        pixel_type,
+        glyph_pixel_type,
+        is_color_glyph,
+        single_glyph,
+        has_glyph,
+        covered_chars,
+        notdef,
+        glyphs,
+        glyphs_directional,
+        glyphs_with_clusters,
+        line_height,
+        ascent,
+        descent,
+        vertical_line_width,
+        scale_factor,
+        units_per_em,
+        unscaled_advance,
+        metrics,
+        bounds_phased,
+        kerning,
+    );
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        let mut data = Vec::new();
+        self.rasterize_into(glyph, &mut data)?;
+
+        Ok(data)
+    }
+
+    fn rasterize_into(&self, glyph: Glyph, out: &mut Vec<u8>) -> Result<(), CacheError> {
+        let start = out.len();
+        self.inner.rasterize_into(glyph, out)?;
+        if self.inner.glyph_pixel_type(glyph) == PixelType::RGBA {
+            premultiply(&mut out[start..]);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        let future = self.inner.rasterize_async(glyph);
+        if self.inner.glyph_pixel_type(glyph) != PixelType::RGBA {
+            return future;
+        }
+
+        Box::pin(async move {
+            let mut data = future.await?;
+            premultiply(&mut data);
+
+            Ok(data)
+        })
+    }
+
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        x_phase: f32,
+        y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        let mut data = self.inner.rasterize_phased(glyph, x_phase, y_phase)?;
+        if self.inner.glyph_pixel_type(glyph) == PixelType::RGBA {
+            premultiply(&mut data);
+        }
+
+        Ok(data)
+    }
+}
+
+/// Multiply the color channels of RGBA pixels by their alpha, rounding to the nearest value
+fn premultiply(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        for channel in pixel[..3].iter_mut() {
+            *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplies_color_by_alpha() {
+        let mut data = [255, 0, 0, 128, 10, 200, 30, 255, 255, 255, 255, 0];
+        premultiply(&mut data);
+        assert_eq!(data, [128, 0, 0, 128, 10, 200, 30, 255, 0, 0, 0, 0]);
+    }
+}
@@ -1,16 +1,105 @@
 use super::*;
 
-use rusttype::{Font, GlyphId, Point, Scale};
+use rusttype::{Font, FontCollection, GlyphId, Point, Scale};
 
 #[derive(Clone)]
 pub struct SizedFont<'a> {
     font: Font<'a>,
+    /// The font's cmap table, if the font was parsed from bytes by this provider
+    cmap: Option<&'a [u8]>,
     size: f32,
+    scale_factor: f32,
+    subpixel: bool,
+    hinting: bool,
 }
 
 impl<'a> SizedFont<'a> {
     pub fn new(font: Font<'a>, size: f32) -> Self {
-        SizedFont { font, size }
+        SizedFont {
+            font,
+            cmap: None,
+            size,
+            scale_factor: 1.0,
+            subpixel: false,
+            hinting: false,
+        }
+    }
+
+    /// Parse a font file, returning [`CacheError::InvalidFont`] if it isn't a valid font
+    ///
+    /// rusttype panics on data that's too short for the tables it reads, so this first checks
+    /// that every table the file lists is inside of it. That catches truncated files and data
+    /// that isn't a font at all, but not every way the contents of a table can be corrupt.
+    pub fn from_bytes(data: &'a [u8], size: f32) -> Result<Self, CacheError> {
+        Self::from_bytes_indexed(data, 0, size)
+    }
+
+    /// Parse one of the fonts in a font collection (like a .ttc file)
+    ///
+    /// For a file with a single font, the only index is 0. Returns [`CacheError::InvalidFont`]
+    /// if the data isn't valid, or doesn't have a font at that index.
+    pub fn from_bytes_indexed(data: &'a [u8], index: u32, size: f32) -> Result<Self, CacheError> {
+        if !tables_in_bounds(data, index as usize) {
+            return Err(CacheError::InvalidFont);
+        }
+        let font = FontCollection::from_bytes(data)
+            .and_then(|collection| collection.font_at(index as usize))
+            .map_err(|_| CacheError::InvalidFont)?;
+
+        Ok(SizedFont {
+            cmap: find_table(data, index as usize, b"cmap"),
+            ..SizedFont::new(font, size)
+        })
+    }
+
+    /// Create a copy of the font that renders with subpixel anti-aliasing (or without it)
+    ///
+    /// In subpixel mode, glyphs are rasterized at three times the horizontal resolution, which is
+    /// then filtered to reduce color fringes and stored as [`PixelType::RGBSubpixel`]. This only
+    /// looks right on displays with a horizontal RGB layout, drawn without scaling, and with a
+    /// renderer that blends each color channel separately.
+    pub fn with_subpixel(&self, subpixel: bool) -> Self {
+        SizedFont {
+            subpixel,
+            ..self.clone()
+        }
+    }
+
+    pub fn subpixel(&self) -> bool {
+        self.subpixel
+    }
+
+    /// Create a copy of the font with hinting turned on (or off)
+    ///
+    /// rusttype can't hint glyphs, so they're always rendered unhinted and this only records the
+    /// setting, which lets code that configures fonts work the same with every provider. Use the
+    /// swash provider for hinted glyphs.
+    pub fn with_hinting(&self, hinting: bool) -> Self {
+        SizedFont {
+            hinting,
+            ..self.clone()
+        }
+    }
+
+    pub fn hinting(&self) -> bool {
+        self.hinting
+    }
+
+    /// Create a copy of the font that rasterizes at `scale_factor` times its size, for HiDPI
+    /// displays
+    ///
+    /// The size stays in logical pixels, so the line metrics, advances, bearings and kerning
+    /// don't change with the scale factor. Everything that describes the rasterized image is in
+    /// physical pixels instead: the [`bounds`](Metrics::bounds) and
+    /// [`bounds_f`](Metrics::bounds_f) of a glyph, the phases of
+    /// [`bounds_phased`](FontProvider::bounds_phased), and the data itself. A glyph whose pen
+    /// position is at `x` logical pixels should then be drawn at `x * scale_factor + bounds.x`
+    /// physical pixels.
+    pub fn with_scale_factor(&self, scale_factor: f32) -> Self {
+        SizedFont {
+            scale_factor,
+            ..self.clone()
+        }
     }
 
     pub fn with_size(&self, size: f32) -> Self {
@@ -27,49 +116,155 @@ impl<'a> SizedFont<'a> {
     pub fn size(&self) -> f32 {
         self.size
     }
+
+    /// The size glyphs are rasterized at, in physical pixels
+    fn pixel_size(&self) -> f32 {
+        self.size * self.scale_factor
+    }
+
+    /// The pixels covered by a glyph with its origin at the given position
+    fn bounds_at(&self, glyph: Glyph, position: Point<f32>) -> Option<Bounds> {
+        if self.subpixel {
+            return subpixel_glyph(&self.font, glyph, self.pixel_size(), position)
+                .pixel_bounding_box()
+                .map(|shape| subpixel_bounds(&shape));
+        }
+        scaled_glyph(&self.font, glyph, self.pixel_size())
+            .positioned(position)
+            .pixel_bounding_box()
+            .map(|shape| Bounds {
+                x: shape.min.x,
+                y: shape.min.y,
+                width: shape.width() as u32,
+                height: shape.height() as u32,
+            })
+    }
+
+    /// Rasterize a glyph with its origin at the given position, which should be within a pixel
+    /// of the origin
+    fn rasterize_at(
+        &self,
+        glyph: Glyph,
+        position: Point<f32>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), CacheError> {
+        if self.subpixel {
+            let data = rasterize_subpixel(&self.font, glyph, self.pixel_size(), position)?;
+            out.extend_from_slice(&data);
+            return Ok(());
+        }
+        let scaled_glyph = scaled_glyph(&self.font, glyph, self.pixel_size()).positioned(position);
+        let bounds = scaled_glyph
+            .pixel_bounding_box()
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+        let start = out.len();
+        out.resize(start + (bounds.width() * bounds.height()) as usize, 0);
+        let buffer = &mut out[start..];
+        let width = bounds.width() as u32;
+        scaled_glyph.draw(|x, y, val| buffer[(x + y * width) as usize] = (val * 255.0) as u8);
+
+        Ok(())
+    }
+
+    fn v_metrics(&self) -> rusttype::VMetrics {
+        self.font.v_metrics(Scale {
+            x: self.size,
+            y: self.size,
+        })
+    }
 }
 
 impl FontProvider for SizedFont<'_> {
     fn line_height(&self) -> f32 {
-        let metrics = self.font.v_metrics(Scale {
-            x: self.size,
-            y: self.size,
-        });
+        let metrics = self.v_metrics();
 
         metrics.ascent - metrics.descent + metrics.line_gap
     }
 
+    fn ascent(&self) -> f32 {
+        self.v_metrics().ascent
+    }
+
+    fn descent(&self) -> f32 {
+        self.v_metrics().descent
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn units_per_em(&self) -> u16 {
+        self.font.units_per_em()
+    }
+
+    fn unscaled_advance(&self, glyph: Glyph) -> Option<f32> {
+        // rusttype scales to the height from the ascent to the descent, so scaling to that
+        // height in design units leaves them unchanged
+        let metrics = self.font.v_metrics_unscaled();
+        let h_metrics =
+            scaled_glyph(&self.font, glyph, metrics.ascent - metrics.descent).h_metrics();
+
+        Some(h_metrics.advance_width)
+    }
+
     fn pixel_type(&self) -> PixelType {
-        PixelType::Alpha
+        if self.subpixel {
+            PixelType::RGBSubpixel
+        } else {
+            PixelType::Alpha
+        }
     }
 
     fn single_glyph(&self, c: char) -> Glyph {
         Glyph(self.font.glyph(c).id().0)
     }
 
+    /// rusttype can't list the characters in a font's cmap, so for fonts parsed with
+    /// [`SizedFont::from_bytes`] this reads the ranges of characters from the cmap itself, and
+    /// only looks those up. Fonts created with [`SizedFont::new`] (or with a cmap in a format
+    /// other than 4 or 12) look up every character instead, which takes tens of milliseconds
+    /// even in a release build.
+    fn covered_chars(&self, out: &mut Vec<char>) {
+        let ranges = match self.cmap.and_then(cmap_ranges) {
+            Some(ranges) => ranges,
+            None => alloc::vec![(0, char::MAX as u32)],
+        };
+        for (start, end) in ranges {
+            out.extend(
+                (start..=end)
+                    .filter_map(char::from_u32)
+                    .filter(|c| self.has_glyph(*c)),
+            );
+        }
+    }
+
     fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
-        glyphs.extend(
-            self.font
-                .glyphs_for(string.chars())
-                .map(|g| Glyph(g.id().0)),
-        );
+        char_glyphs(string, glyphs, None, |c| self.single_glyph(c));
+    }
+
+    fn glyphs_with_clusters(
+        &self,
+        string: &str,
+        glyphs: &mut Vec<Glyph>,
+        clusters: &mut Vec<Range<usize>>,
+    ) {
+        char_glyphs(string, glyphs, Some(clusters), |c| self.single_glyph(c));
     }
 
     fn metrics(&self, glyph: Glyph) -> Metrics {
-        let glyph = scaled_glyph(&self.font, glyph, self.size);
-        let h_metrics = glyph.h_metrics();
-        let bounds = glyph
-            .positioned(Point { x: 0.0, y: 0.0 })
-            .pixel_bounding_box()
-            .map(|shape| Bounds {
+        let h_metrics = scaled_glyph(&self.font, glyph, self.size).h_metrics();
+        let bounds_f = scaled_glyph(&self.font, glyph, self.pixel_size())
+            .exact_bounding_box()
+            .map(|shape| RectF {
                 x: shape.min.x,
                 y: shape.min.y,
-                width: shape.width() as u32,
-                height: shape.height() as u32,
+                width: shape.width(),
+                height: shape.height(),
             });
 
         Metrics {
-            bounds,
+            bounds: self.bounds_at(glyph, ORIGIN),
+            bounds_f,
             bearing_x: h_metrics.left_side_bearing,
             advance_x: h_metrics.advance_width,
             bearing_y: 0.0,
@@ -78,14 +273,41 @@ impl FontProvider for SizedFont<'_> {
     }
 
     fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
-        let scaled_glyph =
-            scaled_glyph(&self.font, glyph, self.size).positioned(Point { x: 0.0, y: 0.0 });
-        let bounds = scaled_glyph
-            .pixel_bounding_box()
-            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
-        let mut buffer = alloc::vec![0u8; (bounds.width() * bounds.height()) as usize];
-        let width = bounds.width() as u32;
-        scaled_glyph.draw(|x, y, val| buffer[(x + y * width) as usize] = (val * 255.0) as u8);
+        let mut buffer = Vec::new();
+        self.rasterize_into(glyph, &mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    fn rasterize_into(&self, glyph: Glyph, out: &mut Vec<u8>) -> Result<(), CacheError> {
+        self.rasterize_at(glyph, ORIGIN, out)
+    }
+
+    fn bounds_phased(&self, glyph: Glyph, x_phase: f32, y_phase: f32) -> Option<Bounds> {
+        self.bounds_at(
+            glyph,
+            Point {
+                x: x_phase,
+                y: y_phase,
+            },
+        )
+    }
+
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        x_phase: f32,
+        y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        let mut buffer = Vec::new();
+        self.rasterize_at(
+            glyph,
+            Point {
+                x: x_phase,
+                y: y_phase,
+            },
+            &mut buffer,
+        )?;
 
         Ok(buffer)
     }
@@ -102,8 +324,182 @@ impl FontProvider for SizedFont<'_> {
     }
 }
 
+fn read_u16(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// Where the table directory of the font at the given index starts, and how many tables it has
+fn table_directory(data: &[u8], index: usize) -> Option<(usize, usize)> {
+    let start = if data.starts_with(b"ttcf") {
+        let fonts = read_u32(data, 8)?;
+        if index >= fonts {
+            return None;
+        }
+        read_u32(data, 12 + index * 4)?
+    } else if index == 0 {
+        0
+    } else {
+        return None;
+    };
+
+    Some((start, read_u16(data, start + 4)?))
+}
+
+/// Whether the data has a font at the given index whose table directory only points inside of
+/// the data
+fn tables_in_bounds(data: &[u8], index: usize) -> bool {
+    let (start, tables) = match table_directory(data, index) {
+        Some(directory) => directory,
+        None => return false,
+    };
+
+    (0..tables).all(|table| {
+        let record = start + 12 + table * 16;
+        match (read_u32(data, record + 8), read_u32(data, record + 12)) {
+            (Some(offset), Some(length)) => offset
+                .checked_add(length)
+                .is_some_and(|end| end <= data.len()),
+            _ => false,
+        }
+    })
+}
+
+/// The data of one of the tables of the font at the given index
+fn find_table<'a>(data: &'a [u8], index: usize, tag: &[u8; 4]) -> Option<&'a [u8]> {
+    let (start, tables) = table_directory(data, index)?;
+    let record = (0..tables)
+        .map(|table| start + 12 + table * 16)
+        .find(|record| data.get(*record..*record + 4) == Some(&tag[..]))?;
+    let offset = read_u32(data, record + 8)?;
+    let length = read_u32(data, record + 12)?;
+
+    data.get(offset..offset.checked_add(length)?)
+}
+
+/// The ranges of characters a cmap table maps to glyphs, from its first subtable in format 12
+/// (which covers every plane) or otherwise its first in format 4 (which covers the BMP)
+///
+/// Characters in a range can still map to the notdef glyph, so each one has to be looked up.
+fn cmap_ranges(cmap: &[u8]) -> Option<Vec<(u32, u32)>> {
+    let subtables = (0..read_u16(cmap, 2)?)
+        .map(|subtable| read_u32(cmap, 4 + subtable * 8 + 4))
+        .collect::<Option<Vec<_>>>()?;
+    let format = |format: usize| {
+        subtables
+            .iter()
+            .copied()
+            .find(|offset| read_u16(cmap, *offset) == Some(format))
+    };
+    // Ranges past the last character are cut off, in case the cmap is corrupt
+    let range = |start: usize, end: usize| (start as u32, (end as u32).min(char::MAX as u32));
+
+    if let Some(offset) = format(12) {
+        let groups = read_u32(cmap, offset + 12)?;
+        (0..groups)
+            .map(|group| {
+                let group = offset + 16 + group * 12;
+                Some(range(read_u32(cmap, group)?, read_u32(cmap, group + 4)?))
+            })
+            .collect()
+    } else if let Some(offset) = format(4) {
+        let segments = read_u16(cmap, offset + 6)? / 2;
+        let end_codes = offset + 14;
+        let start_codes = end_codes + segments * 2 + 2;
+        (0..segments)
+            .map(|segment| {
+                let start = read_u16(cmap, start_codes + segment * 2)?;
+                Some(range(start, read_u16(cmap, end_codes + segment * 2)?))
+            })
+            .collect()
+    } else {
+        None
+    }
+}
+
 fn scaled_glyph<'a>(font: &'a Font, glyph: Glyph, size: f32) -> rusttype::ScaledGlyph<'a> {
     let id = GlyphId(glyph.0);
     let glyph = font.glyph(id);
     glyph.scaled(Scale { x: size, y: size })
 }
+
+const ORIGIN: Point<f32> = Point { x: 0.0, y: 0.0 };
+
+/// The glyph scaled to three times its width, so each pixel covers one subpixel
+fn subpixel_glyph<'a>(
+    font: &'a Font,
+    glyph: Glyph,
+    size: f32,
+    position: Point<f32>,
+) -> rusttype::PositionedGlyph<'a> {
+    font.glyph(GlyphId(glyph.0))
+        .scaled(Scale {
+            x: size * 3.0,
+            y: size,
+        })
+        .positioned(Point {
+            x: position.x * 3.0,
+            y: position.y,
+        })
+}
+
+/// The whole pixels covered by a glyph's subpixel bounding box, with a pixel of margin on each
+/// side for the filter to spread into
+fn subpixel_bounds(shape: &rusttype::Rect<i32>) -> Bounds {
+    let left = shape.min.x.div_euclid(3) - 1;
+    let right = (shape.max.x + 2).div_euclid(3) + 1;
+
+    Bounds {
+        x: left,
+        y: shape.min.y,
+        width: (right - left) as u32,
+        height: shape.height() as u32,
+    }
+}
+
+fn rasterize_subpixel(
+    font: &Font,
+    glyph: Glyph,
+    size: f32,
+    position: Point<f32>,
+) -> Result<Vec<u8>, CacheError> {
+    // The LCD filter FreeType uses by default, which spreads each subpixel over its neighbors
+    const FILTER: [u32; 5] = [8, 77, 86, 77, 8];
+
+    let positioned = subpixel_glyph(font, glyph, size, position);
+    let shape = positioned
+        .pixel_bounding_box()
+        .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+    let bounds = subpixel_bounds(&shape);
+    let row_length = bounds.width as usize * PixelType::RGBSubpixel.bytes_per_pixel();
+    let offset = (shape.min.x - bounds.x * 3) as u32;
+    let mut coverage = alloc::vec![0u8; row_length * bounds.height as usize];
+    positioned.draw(|x, y, val| {
+        coverage[(x + offset) as usize + y as usize * row_length] = (val * 255.0) as u8;
+    });
+
+    let mut buffer = alloc::vec![0u8; coverage.len()];
+    for (source, output) in coverage
+        .chunks_exact(row_length)
+        .zip(buffer.chunks_exact_mut(row_length))
+    {
+        for (x, value) in output.iter_mut().enumerate() {
+            let sum: u32 = FILTER
+                .iter()
+                .enumerate()
+                .filter_map(|(tap, weight)| {
+                    let index = (x + tap).checked_sub(2)?;
+                    Some(*source.get(index)? as u32 * weight)
+                })
+                .sum();
+            *value = (sum / 256).min(255) as u8;
+        }
+    }
+
+    Ok(buffer)
+}
@@ -0,0 +1,338 @@
+use super::*;
+
+use libm::{ceilf, floorf, sqrtf};
+
+/// Turns the glyphs of an Alpha provider into signed distance fields
+///
+/// Each glyph is rasterized by the inner provider, which should use a larger size than the
+/// desired output, and converted into a distance field with the 8SSEDT algorithm. The field is
+/// then downsampled by `scale` (so an inner provider at 64px with a scale of 0.25 produces 16px
+/// glyphs.) Pixels on the outline of the glyph have a value of about 128, increasing to 255 at
+/// `spread` output pixels inside of it, and decreasing to 0 at `spread` pixels outside of it.
+///
+/// The glyphs are padded by `spread` pixels on each side so the field isn't cut off, and all of
+/// the metrics are scaled to the output size.
+pub struct SdfFontProvider<P: FontProvider> {
+    inner: P,
+    scale: f32,
+    spread: f32,
+}
+
+impl<P: FontProvider> SdfFontProvider<P> {
+    /// Wrap a provider, producing distance fields that are `scale` times its size
+    ///
+    /// # Panics
+    ///
+    /// If the inner provider doesn't produce Alpha data, or the scale or spread aren't positive
+    pub fn new(inner: P, scale: f32, spread: f32) -> Self {
+        assert_eq!(inner.pixel_type(), PixelType::Alpha);
+        assert!(scale > 0.0);
+        assert!(spread > 0.0);
+
+        SdfFontProvider {
+            inner,
+            scale,
+            spread,
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn spread(&self) -> f32 {
+        self.spread
+    }
+}
+
+impl<P: FontProvider> FontProvider for SdfFontProvider<P> {
+    delegate_to_inner!(
+        single_glyph,
+        has_glyph,
+        covered_chars,
+        notdef,
+        glyphs,
+        glyphs_directional,
+        glyphs_with_clusters,
+        scale_factor,
+        units_per_em,
+        unscaled_advance,
+    );
+
+    fn pixel_type(&self) -> PixelType {
+        PixelType::Alpha
+    }
+
+    fn line_height(&self) -> f32 {
+        self.inner.line_height() * self.scale
+    }
+
+    fn ascent(&self) -> f32 {
+        self.inner.ascent() * self.scale
+    }
+
+    fn descent(&self) -> f32 {
+        self.inner.descent() * self.scale
+    }
+
+    fn vertical_line_width(&self) -> f32 {
+        self.inner.vertical_line_width() * self.scale
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        let metrics = self.inner.metrics(glyph);
+
+        let bounds = metrics
+            .bounds
+            .map(|bounds| field_bounds(&bounds, self.scale, self.spread));
+
+        Metrics {
+            bounds,
+            bounds_f: bounds.map(RectF::from),
+            bearing_x: metrics.bearing_x * self.scale,
+            advance_x: metrics.advance_x * self.scale,
+            bearing_y: metrics.bearing_y * self.scale,
+            advance_y: metrics.advance_y * self.scale,
+        }
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        if self.inner.glyph_pixel_type(glyph) != PixelType::Alpha {
+            return Err(CacheError::NonRenderableGlyph(glyph));
+        }
+        let bounds = self
+            .inner
+            .metrics(glyph)
+            .bounds
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+        let coverage = self.inner.rasterize(glyph)?;
+        check_length(&bounds, PixelType::Alpha, &coverage)?;
+
+        Ok(signed_distance_field(
+            &coverage,
+            &bounds,
+            self.scale,
+            self.spread,
+        ))
+    }
+
+    #[cfg(feature = "async")]
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        let bounds = self.inner.metrics(glyph).bounds;
+        let bounds = match bounds {
+            Some(bounds) if self.inner.glyph_pixel_type(glyph) == PixelType::Alpha => bounds,
+            _ => {
+                return Box::pin(core::future::ready(Err(CacheError::NonRenderableGlyph(
+                    glyph,
+                ))))
+            }
+        };
+        let future = self.inner.rasterize_async(glyph);
+        let (scale, spread) = (self.scale, self.spread);
+
+        Box::pin(async move {
+            let coverage = future.await?;
+            check_length(&bounds, PixelType::Alpha, &coverage)?;
+
+            Ok(signed_distance_field(&coverage, &bounds, scale, spread))
+        })
+    }
+
+    fn kerning(&self, a: Glyph, b: Glyph) -> f32 {
+        self.inner.kerning(a, b) * self.scale
+    }
+}
+
+/// How many pixels of the inner glyph to pad each side with
+fn source_padding(scale: f32, spread: f32) -> u32 {
+    ceilf(spread / scale) as u32
+}
+
+/// The bounds of the distance field for an inner glyph with the given bounds
+fn field_bounds(bounds: &Bounds, scale: f32, spread: f32) -> Bounds {
+    let padding = source_padding(scale, spread);
+    Bounds {
+        x: floorf((bounds.x - padding as i32) as f32 * scale) as i32,
+        y: floorf((bounds.y - padding as i32) as f32 * scale) as i32,
+        width: ceilf((bounds.width + padding * 2) as f32 * scale) as u32,
+        height: ceilf((bounds.height + padding * 2) as f32 * scale) as u32,
+    }
+}
+
+/// Turn the coverage of an inner glyph with the given bounds into a distance field with the
+/// bounds from [`field_bounds`]
+fn signed_distance_field(coverage: &[u8], bounds: &Bounds, scale: f32, spread: f32) -> Vec<u8> {
+    // Pad the glyph so the field has room to fall off outside of the outline
+    let padding = source_padding(scale, spread);
+    let width = bounds.width + padding * 2;
+    let height = bounds.height + padding * 2;
+    let mut inside = alloc::vec![false; (width * height) as usize];
+    for y in 0..bounds.height {
+        for x in 0..bounds.width {
+            let value = coverage[(x + y * bounds.width) as usize];
+            inside[(x + padding + (y + padding) * width) as usize] = value >= 128;
+        }
+    }
+    let outside_distance = distance_field(&inside, width, height, true);
+    let inside_distance = distance_field(&inside, width, height, false);
+
+    // Sample the field at the center of each output pixel
+    let field = field_bounds(bounds, scale, spread);
+    let mut output = alloc::vec![0u8; (field.width * field.height) as usize];
+    for y in 0..field.height {
+        for x in 0..field.width {
+            let source_x = (((x as f32 + 0.5) / scale) as u32).min(width - 1);
+            let source_y = (((y as f32 + 0.5) / scale) as u32).min(height - 1);
+            let index = (source_x + source_y * width) as usize;
+            let distance = (outside_distance[index] - inside_distance[index]) * scale;
+            let value = 0.5 - distance / (2.0 * spread);
+            output[(x + y * field.width) as usize] = (value.clamp(0.0, 1.0) * 255.0) as u8;
+        }
+    }
+
+    output
+}
+
+/// Find the distance of each pixel to the nearest pixel where `inside` is equal to `target`,
+/// with the 8-point signed sequential Euclidean distance transform (8SSEDT)
+fn distance_field(inside: &[bool], width: u32, height: u32, target: bool) -> Vec<f32> {
+    const FAR: (i32, i32) = (9999, 9999);
+    let (width, height) = (width as i32, height as i32);
+    let mut grid: Vec<(i32, i32)> = inside
+        .iter()
+        .map(|&value| if value == target { (0, 0) } else { FAR })
+        .collect();
+
+    let length = |(x, y): (i32, i32)| x * x + y * y;
+    let compare = |grid: &mut Vec<(i32, i32)>, x: i32, y: i32, dx: i32, dy: i32| {
+        let (nx, ny) = (x + dx, y + dy);
+        if nx < 0 || ny < 0 || nx >= width || ny >= height {
+            return;
+        }
+        let other = grid[(nx + ny * width) as usize];
+        let candidate = (other.0 + dx, other.1 + dy);
+        let current = &mut grid[(x + y * width) as usize];
+        if length(candidate) < length(*current) {
+            *current = candidate;
+        }
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            compare(&mut grid, x, y, -1, 0);
+            compare(&mut grid, x, y, 0, -1);
+            compare(&mut grid, x, y, -1, -1);
+            compare(&mut grid, x, y, 1, -1);
+        }
+        for x in (0..width).rev() {
+            compare(&mut grid, x, y, 1, 0);
+        }
+    }
+    for y in (0..height).rev() {
+        for x in (0..width).rev() {
+            compare(&mut grid, x, y, 1, 0);
+            compare(&mut grid, x, y, 0, 1);
+            compare(&mut grid, x, y, -1, 1);
+            compare(&mut grid, x, y, 1, 1);
+        }
+        for x in 0..width {
+            compare(&mut grid, x, y, -1, 0);
+        }
+    }
+
+    grid.into_iter()
+        .map(|offset| sqrtf(length(offset) as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_peaks_inside_and_falls_off_outside() {
+        let bounds = Bounds {
+            x: 0,
+            y: -10,
+            width: 10,
+            height: 10,
+        };
+        let coverage = alloc::vec![255; 100];
+        let field = signed_distance_field(&coverage, &bounds, 1.0, 4.0);
+        let field_bounds = field_bounds(&bounds, 1.0, 4.0);
+        assert_eq!((field_bounds.width, field_bounds.height), (18, 18));
+        assert_eq!(field.len(), 18 * 18);
+
+        let at = |x: usize, y: usize| field[x + y * 18];
+        // The center of the block is more than `spread` from its outline
+        assert!(at(9, 9) >= 250);
+        // The corners of the padding are more than `spread` outside of it
+        assert!(at(0, 0) <= 5);
+        // The outline is halfway between the two
+        assert!((100..=160).contains(&at(4, 9)));
+        assert!(at(3, 9) < at(4, 9) && at(4, 9) < at(5, 9));
+    }
+
+    /// A provider that rasterizes too little data for its glyph's bounds
+    struct Truncated;
+
+    impl FontProvider for Truncated {
+        fn pixel_type(&self) -> PixelType {
+            PixelType::Alpha
+        }
+
+        fn single_glyph(&self, _character: char) -> Glyph {
+            Glyph(1)
+        }
+
+        fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+            glyphs.extend(string.chars().map(|_| Glyph(1)));
+        }
+
+        fn line_height(&self) -> f32 {
+            4.0
+        }
+
+        fn metrics(&self, _glyph: Glyph) -> Metrics {
+            let bounds = Bounds {
+                x: 0,
+                y: -4,
+                width: 4,
+                height: 4,
+            };
+
+            Metrics {
+                bounds: Some(bounds),
+                bounds_f: Some(RectF::from(bounds)),
+                bearing_x: 0.0,
+                advance_x: 4.0,
+                bearing_y: 0.0,
+                advance_y: 0.0,
+            }
+        }
+
+        fn rasterize(&self, _glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+            Ok(alloc::vec![255; 3])
+        }
+    }
+
+    #[test]
+    fn wrong_coverage_length_is_an_error() {
+        let sdf = SdfFontProvider::new(Truncated, 1.0, 2.0);
+        assert!(matches!(
+            sdf.rasterize(Glyph(1)),
+            Err(CacheError::MalformedGlyphData {
+                expected: 16,
+                actual: 3
+            })
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+use super::*;
+
+use alloc::collections::VecDeque;
+
+/// Remembers the glyphs of the most recently converted strings, so rendering the same string
+/// again doesn't call [`FontProvider::glyphs`]
+///
+/// The entries are kept from most to least recently used, and looked up with a linear search,
+/// which is fast for the small capacities this is meant for. A capacity of 0 turns it off. Each
+/// entry remembers which font it was converted with, since the same string has different glyphs
+/// in different fonts.
+pub(crate) struct ShapingCache {
+    capacity: usize,
+    entries: VecDeque<(FontId, String, Vec<Glyph>)>,
+}
+
+impl ShapingCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        ShapingCache {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.entries.truncate(capacity);
+    }
+
+    /// Forget every string converted with the given font
+    pub(crate) fn forget(&mut self, id: FontId) {
+        self.entries.retain(|(font, _, _)| *font != id);
+    }
+
+    /// Push the glyphs for a (prepared) string into the buffer, from the cache if possible
+    pub(crate) fn glyphs(
+        &mut self,
+        id: FontId,
+        font: &dyn FontProvider,
+        string: &str,
+        glyphs: &mut Vec<Glyph>,
+    ) {
+        if self.capacity == 0 {
+            font.glyphs(string, glyphs);
+            return;
+        }
+        if let Some(index) = self
+            .entries
+            .iter()
+            .position(|(font, key, _)| *font == id && key == string)
+        {
+            let entry = self
+                .entries
+                .remove(index)
+                .expect("The entry was just found");
+            glyphs.extend_from_slice(&entry.2);
+            self.entries.push_front(entry);
+            return;
+        }
+
+        let start = glyphs.len();
+        font.glyphs(string, glyphs);
+        self.entries.truncate(self.capacity - 1);
+        self.entries
+            .push_front((id, string.to_owned(), glyphs[start..].to_vec()));
+    }
+}
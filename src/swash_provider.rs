@@ -0,0 +1,249 @@
+use super::*;
+
+use std::sync::Mutex;
+use swash::scale::image::{Content, Image};
+use swash::scale::{Render, ScaleContext, Source, StrikeWith};
+use swash::zeno::Format;
+use swash::{tag_from_bytes, FontRef, GlyphId, TableProvider};
+
+/// The tables every font needs for its characters to be mapped to glyphs and measured
+const REQUIRED_TABLES: [&[u8; 4]; 5] = [b"cmap", b"head", b"hhea", b"hmtx", b"maxp"];
+
+/// A swash font at a fixed size, which can render color glyphs (like emoji)
+///
+/// Color outlines (COLR) and color bitmaps (CBDT, sbix) are preferred over regular outlines when
+/// the font has them. Color glyphs are produced as RGBA, and monochrome glyphs as Alpha (see
+/// [`FontProvider::glyph_pixel_type`]), so the texture should support both.
+///
+/// Unlike the rusttype and ab_glyph providers, the size is in pixels per em rather than the
+/// pixel height of the font's ascent and descent.
+pub struct SizedFont<'a> {
+    font: FontRef<'a>,
+    size: f32,
+    scale_factor: f32,
+    hinting: bool,
+    context: Mutex<ScaleContext>,
+}
+
+impl<'a> SizedFont<'a> {
+    pub fn new(font: FontRef<'a>, size: f32) -> Self {
+        SizedFont {
+            font,
+            size,
+            scale_factor: 1.0,
+            hinting: false,
+            context: Mutex::new(ScaleContext::new()),
+        }
+    }
+
+    /// Parse the first font in a font file, returning [`CacheError::InvalidFont`] if it isn't a
+    /// valid font
+    ///
+    /// swash only reads the header of the file up front, so this also checks that the tables
+    /// every font needs are listed and inside of the data. That catches truncated files and
+    /// headers with nothing after them, but not every way the contents of a table can be corrupt.
+    pub fn from_bytes(data: &'a [u8], size: f32) -> Result<Self, CacheError> {
+        Self::from_bytes_indexed(data, 0, size)
+    }
+
+    /// Parse one of the fonts in a font collection (like a .ttc file)
+    ///
+    /// For a file with a single font, the only index is 0. Returns [`CacheError::InvalidFont`]
+    /// if the data isn't valid, or doesn't have a font at that index.
+    pub fn from_bytes_indexed(data: &'a [u8], index: u32, size: f32) -> Result<Self, CacheError> {
+        let font = FontRef::from_index(data, index as usize).ok_or(CacheError::InvalidFont)?;
+        let missing = REQUIRED_TABLES
+            .iter()
+            .any(|tag| font.table_by_tag(tag_from_bytes(tag)).is_none());
+        if missing {
+            return Err(CacheError::InvalidFont);
+        }
+
+        Ok(SizedFont::new(font, size))
+    }
+
+    pub fn with_size(&self, size: f32) -> Self {
+        SizedFont {
+            size,
+            ..self.clone()
+        }
+    }
+
+    pub fn font(&self) -> FontRef<'a> {
+        self.font
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// Create a copy of the font that renders images at `scale_factor` times its size, for HiDPI
+    /// displays
+    ///
+    /// Only the rendered images are scaled: their bounds and data are in physical pixels, while
+    /// the line metrics, advances and bearings are in logical pixels at the font's size.
+    pub fn with_scale_factor(&self, scale_factor: f32) -> Self {
+        SizedFont {
+            scale_factor,
+            ..self.clone()
+        }
+    }
+
+    /// Create a copy of the font that hints glyphs (or doesn't), fitting their outlines to the
+    /// pixel grid
+    ///
+    /// Hinting makes small text crisper, but moves the outlines by up to a pixel, so glyphs
+    /// animated in size or scaled after rendering look better without it. It's off by default.
+    /// Only the rendered images are hinted: advances and bearings are the font's unhinted
+    /// metrics either way.
+    pub fn with_hinting(&self, hinting: bool) -> Self {
+        SizedFont {
+            hinting,
+            ..self.clone()
+        }
+    }
+
+    pub fn hinting(&self) -> bool {
+        self.hinting
+    }
+
+    fn render(&self, glyph: Glyph) -> Option<Image> {
+        let mut context = self.context.lock().unwrap_or_else(|err| err.into_inner());
+        let mut scaler = context
+            .builder(self.font)
+            .size(self.size * self.scale_factor)
+            .hint(self.hinting)
+            .build();
+        let image = Render::new(&[
+            Source::ColorOutline(0),
+            Source::ColorBitmap(StrikeWith::BestFit),
+            Source::Outline,
+        ])
+        .format(Format::Alpha)
+        .render(&mut scaler, glyph.0 as GlyphId)?;
+        if image.placement.width == 0 || image.placement.height == 0 {
+            None
+        } else {
+            Some(image)
+        }
+    }
+}
+
+impl Clone for SizedFont<'_> {
+    fn clone(&self) -> Self {
+        SizedFont {
+            font: self.font,
+            size: self.size,
+            scale_factor: self.scale_factor,
+            hinting: self.hinting,
+            context: Mutex::new(ScaleContext::new()),
+        }
+    }
+}
+
+impl FontProvider for SizedFont<'_> {
+    fn line_height(&self) -> f32 {
+        let metrics = self.font.metrics(&[]).scale(self.size);
+
+        metrics.ascent + metrics.descent + metrics.leading
+    }
+
+    fn ascent(&self) -> f32 {
+        self.font.metrics(&[]).scale(self.size).ascent
+    }
+
+    fn descent(&self) -> f32 {
+        // Swash reports the descent as a positive distance
+        -self.font.metrics(&[]).scale(self.size).descent
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn units_per_em(&self) -> u16 {
+        self.font.metrics(&[]).units_per_em
+    }
+
+    fn unscaled_advance(&self, glyph: Glyph) -> Option<f32> {
+        Some(
+            self.font
+                .glyph_metrics(&[])
+                .advance_width(glyph.0 as GlyphId),
+        )
+    }
+
+    fn pixel_type(&self) -> PixelType {
+        PixelType::RGBA
+    }
+
+    fn glyph_pixel_type(&self, glyph: Glyph) -> PixelType {
+        match self.render(glyph).map(|image| image.content) {
+            Some(Content::Color) | Some(Content::SubpixelMask) => PixelType::RGBA,
+            Some(Content::Mask) | None => PixelType::Alpha,
+        }
+    }
+
+    fn is_color_glyph(&self, glyph: Glyph) -> bool {
+        self.render(glyph)
+            .is_some_and(|image| image.content == Content::Color)
+    }
+
+    fn single_glyph(&self, c: char) -> Glyph {
+        Glyph(self.font.charmap().map(c) as u32)
+    }
+
+    fn covered_chars(&self, out: &mut Vec<char>) {
+        self.font.charmap().enumerate(|c, glyph| {
+            if let Some(c) = char::from_u32(c).filter(|_| glyph != 0) {
+                out.push(c);
+            }
+        });
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        let charmap = self.font.charmap();
+        char_glyphs(string, glyphs, None, |c| Glyph(charmap.map(c) as u32));
+    }
+
+    fn glyphs_with_clusters(
+        &self,
+        string: &str,
+        glyphs: &mut Vec<Glyph>,
+        clusters: &mut Vec<Range<usize>>,
+    ) {
+        let charmap = self.font.charmap();
+        char_glyphs(string, glyphs, Some(clusters), |c| {
+            Glyph(charmap.map(c) as u32)
+        });
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        let glyph_metrics = self.font.glyph_metrics(&[]).scale(self.size);
+        let id = glyph.0 as GlyphId;
+        let bounds = self.render(glyph).map(|image| Bounds {
+            x: image.placement.left,
+            y: -image.placement.top,
+            width: image.placement.width,
+            height: image.placement.height,
+        });
+
+        Metrics {
+            bounds,
+            // Swash only places rendered images on whole pixels
+            bounds_f: bounds.map(RectF::from),
+            bearing_x: glyph_metrics.lsb(id),
+            advance_x: glyph_metrics.advance_width(id),
+            bearing_y: 0.0,
+            advance_y: 0.0,
+        }
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        let image = self
+            .render(glyph)
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+
+        Ok(image.data)
+    }
+}
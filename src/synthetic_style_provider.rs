@@ -0,0 +1,278 @@
+use super::*;
+
+use libm::{ceilf, floorf, roundf, tanf};
+
+/// Fakes bold and italic styles for fonts that don't have them, by emboldening and shearing the
+/// glyphs of an Alpha provider
+///
+/// Emboldening widens each glyph by `embolden` pixels, spreading its coverage to the right, and
+/// adds the same amount to its advance so emboldened text doesn't run together. Shearing slants
+/// each glyph by an angle from the vertical, moving the part above the baseline right and the
+/// part below it left (for an angle above 0), like an italic face. The bounds of every glyph grow
+/// to fit both.
+///
+/// Shearing doesn't change advances, so slanted glyphs may overlap their neighbors slightly,
+/// like real italics. The line metrics, kerning and design metrics are the inner provider's.
+pub struct SyntheticStyleProvider<P: FontProvider> {
+    inner: P,
+    style: Style,
+}
+
+/// The angle and emboldening of a [`SyntheticStyleProvider`], which is all it needs to style
+/// coverage
+#[derive(Copy, Clone)]
+struct Style {
+    angle: f32,
+    embolden: u32,
+}
+
+impl<P: FontProvider> SyntheticStyleProvider<P> {
+    /// Wrap a provider, slanting its glyphs by `angle` degrees and widening them by `embolden`
+    /// pixels
+    ///
+    /// Either can be 0 to leave that style out. Around 12 degrees looks like a typical italic,
+    /// and an embolden of about a fifteenth of the font's size like a typical bold.
+    ///
+    /// # Panics
+    ///
+    /// If the inner provider doesn't produce Alpha data
+    pub fn new(inner: P, angle: f32, embolden: u32) -> Self {
+        assert_eq!(inner.pixel_type(), PixelType::Alpha);
+
+        SyntheticStyleProvider {
+            inner,
+            style: Style { angle, embolden },
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    pub fn angle(&self) -> f32 {
+        self.style.angle
+    }
+
+    pub fn embolden(&self) -> u32 {
+        self.style.embolden
+    }
+}
+
+impl Style {
+    /// How far right the glyph is moved at a position relative to the baseline (with negative
+    /// positions above it)
+    fn shear_at(&self, y: f32) -> f32 {
+        -y * tanf(self.angle.to_radians())
+    }
+
+    /// How far right each of the first and last rows of a glyph with the given bounds is moved
+    fn row_shears(&self, bounds: &Bounds) -> (f32, f32) {
+        let top = self.shear_at(bounds.y as f32 + 0.5);
+        let bottom = self.shear_at(bounds.y as f32 + bounds.height as f32 - 0.5);
+
+        (top.min(bottom), top.max(bottom))
+    }
+
+    /// Grow bounds to fit the styled glyph
+    fn bounds(&self, bounds: &Bounds) -> Bounds {
+        let (min, max) = self.row_shears(bounds);
+        let left = floorf(min) as i32;
+        let right = ceilf(max) as i32;
+
+        Bounds {
+            x: bounds.x + left,
+            y: bounds.y,
+            width: (bounds.width + self.embolden) + (right - left) as u32,
+            height: bounds.height,
+        }
+    }
+
+    /// Turn the coverage of a glyph with the given bounds into the styled glyph's coverage
+    fn apply(&self, coverage: &[u8], bounds: &Bounds) -> Vec<u8> {
+        let (width, height) = (bounds.width as i32, bounds.height as i32);
+        let embolden = self.embolden as i32;
+        let bold_width = width + embolden;
+        // Each pixel of the emboldened glyph has the most coverage of the pixels up to
+        // `embolden` to its left
+        let bold_at = |x: i32, y: i32| {
+            if x < 0 || x >= bold_width {
+                return 0;
+            }
+            let row = &coverage[(y * width) as usize..((y + 1) * width) as usize];
+            let start = (x - embolden).max(0);
+            let end = x.min(width - 1);
+            (start..=end).map(|x| row[x as usize]).max().unwrap_or(0)
+        };
+
+        let styled = self.bounds(bounds);
+        let mut output = Vec::with_capacity((styled.width * styled.height) as usize);
+        for y in 0..height {
+            let shear = self.shear_at(bounds.y as f32 + y as f32 + 0.5);
+            for x in 0..styled.width as i32 {
+                // Position in the emboldened glyph, which falls between two of its pixels
+                let source = (styled.x - bounds.x + x) as f32 - shear;
+                let left = floorf(source);
+                let fraction = source - left;
+                let left = left as i32;
+                let value = bold_at(left, y) as f32 * (1.0 - fraction)
+                    + bold_at(left + 1, y) as f32 * fraction;
+                output.push(roundf(value) as u8);
+            }
+        }
+
+        output
+    }
+}
+
+impl<P: FontProvider> FontProvider for SyntheticStyleProvider<P> {
+    delegate_to_inner!(
+        single_glyph,
+        has_glyph,
+        covered_chars,
+        notdef,
+        glyphs,
+        glyphs_directional,
+        glyphs_with_clusters,
+        line_height,
+        ascent,
+        descent,
+        vertical_line_width,
+        scale_factor,
+        units_per_em,
+        unscaled_advance,
+        kerning,
+    );
+
+    fn pixel_type(&self) -> PixelType {
+        PixelType::Alpha
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        let metrics = self.inner.metrics(glyph);
+        let embolden = self.style.embolden as f32;
+
+        Metrics {
+            bounds: metrics.bounds.map(|bounds| self.style.bounds(&bounds)),
+            bounds_f: metrics.bounds_f.map(|bounds| {
+                let top = self.style.shear_at(bounds.y);
+                let bottom = self.style.shear_at(bounds.y + bounds.height);
+                RectF {
+                    x: bounds.x + top.min(bottom),
+                    y: bounds.y,
+                    width: bounds.width + embolden + (top - bottom).abs(),
+                    height: bounds.height,
+                }
+            }),
+            advance_x: metrics.advance_x + embolden,
+            ..metrics
+        }
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        self.rasterize_phased(glyph, 0.0, 0.0)
+    }
+
+    fn bounds_phased(&self, glyph: Glyph, x_phase: f32, y_phase: f32) -> Option<Bounds> {
+        self.inner
+            .bounds_phased(glyph, x_phase, y_phase)
+            .map(|bounds| self.style.bounds(&bounds))
+    }
+
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        x_phase: f32,
+        y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        if self.inner.glyph_pixel_type(glyph) != PixelType::Alpha {
+            return Err(CacheError::NonRenderableGlyph(glyph));
+        }
+        let bounds = self
+            .inner
+            .bounds_phased(glyph, x_phase, y_phase)
+            .ok_or(CacheError::NonRenderableGlyph(glyph))?;
+        let coverage = self.inner.rasterize_phased(glyph, x_phase, y_phase)?;
+        check_length(&bounds, PixelType::Alpha, &coverage)?;
+
+        Ok(self.style.apply(&coverage, &bounds))
+    }
+
+    #[cfg(feature = "async")]
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        let bounds = self.inner.bounds_phased(glyph, 0.0, 0.0);
+        let bounds = match bounds {
+            Some(bounds) if self.inner.glyph_pixel_type(glyph) == PixelType::Alpha => bounds,
+            _ => {
+                return Box::pin(core::future::ready(Err(CacheError::NonRenderableGlyph(
+                    glyph,
+                ))))
+            }
+        };
+        let future = self.inner.rasterize_async(glyph);
+        let style = self.style;
+
+        Box::pin(async move {
+            let coverage = future.await?;
+            check_length(&bounds, PixelType::Alpha, &coverage)?;
+
+            Ok(style.apply(&coverage, &bounds))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A solid column one pixel wide, from 4 pixels above the baseline down to it
+    const COLUMN: Bounds = Bounds {
+        x: 0,
+        y: -4,
+        width: 1,
+        height: 4,
+    };
+
+    #[test]
+    fn shearing_moves_the_top_rows_right() {
+        let style = Style {
+            angle: 45.0,
+            embolden: 0,
+        };
+        let bounds = style.bounds(&COLUMN);
+        let styled = style.apply(&[255; 4], &COLUMN);
+        assert_eq!(styled.len(), (bounds.width * bounds.height) as usize);
+
+        // The weighted position of each row's coverage, relative to the styled bounds
+        let centers: Vec<f32> = styled
+            .chunks(bounds.width as usize)
+            .map(|row| {
+                let total: f32 = row.iter().map(|&value| value as f32).sum();
+                let moment: f32 = row
+                    .iter()
+                    .enumerate()
+                    .map(|(x, &value)| x as f32 * value as f32)
+                    .sum();
+                moment / total
+            })
+            .collect();
+        // At 45 degrees, each row is a pixel further right than the one below it
+        for pair in centers.windows(2) {
+            assert!((pair[0] - pair[1] - 1.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn emboldening_widens_to_the_right() {
+        let style = Style {
+            angle: 0.0,
+            embolden: 2,
+        };
+        let bounds = style.bounds(&COLUMN);
+        assert_eq!((bounds.x, bounds.width), (0, 3));
+        assert_eq!(style.apply(&[255; 4], &COLUMN), [255; 12]);
+    }
+}
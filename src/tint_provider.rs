@@ -0,0 +1,153 @@
+use super::*;
+
+/// Bakes a color into the glyphs of an Alpha provider, for backends that can't tint text when
+/// drawing it
+///
+/// Each Alpha glyph is stored as [`PixelType::RGBA`], with the color of the tint and an alpha of
+/// the glyph's coverage multiplied by the tint's alpha. A fully covered pixel is exactly the
+/// tint color. The alpha is straight, so wrap this in a
+/// [`PremultiplyProvider`](crate::premultiply_provider::PremultiplyProvider) for premultiplied
+/// blending.
+///
+/// Glyphs that are already RGBA (like color emoji) keep their own colors and are passed through
+/// unchanged. Glyphs of any other type can't be tinted, and return
+/// [`CacheError::NonRenderableGlyph`]. Bounds, metrics and everything besides rasterizing are the
+/// inner provider's.
+pub struct TintProvider<P: FontProvider> {
+    inner: P,
+    color: [u8; 4],
+}
+
+impl<P: FontProvider> TintProvider<P> {
+    /// Wrap a provider, tinting its glyphs with an RGBA color
+    pub fn new(inner: P, color: [u8; 4]) -> Self {
+        TintProvider { inner, color }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    pub fn color(&self) -> [u8; 4] {
+        self.color
+    }
+}
+
+impl<P: FontProvider> FontProvider for TintProvider<P> {
+    delegate_to_inner!(
+        is_color_glyph,
+        single_glyph,
+        has_glyph,
+        covered_chars,
+        notdef,
+        glyphs,
+        glyphs_directional,
+        glyphs_with_clusters,
+        line_height,
+        ascent,
+        descent,
+        vertical_line_width,
+        scale_factor,
+        units_per_em,
+        unscaled_advance,
+        metrics,
+        bounds_phased,
+        kerning,
+    );
+
+    fn pixel_type(&self) -> PixelType {
+        PixelType::RGBA
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        let mut data = Vec::new();
+        self.rasterize_into(glyph, &mut data)?;
+
+        Ok(data)
+    }
+
+    fn rasterize_into(&self, glyph: Glyph, out: &mut Vec<u8>) -> Result<(), CacheError> {
+        let data = self.inner.rasterize(glyph)?;
+        let pixel_type = self.inner.glyph_pixel_type(glyph);
+        tint(glyph, pixel_type, self.color, &data, out)
+    }
+
+    #[cfg(feature = "async")]
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        let future = self.inner.rasterize_async(glyph);
+        let pixel_type = self.inner.glyph_pixel_type(glyph);
+        let color = self.color;
+
+        Box::pin(async move {
+            let data = future.await?;
+            let mut tinted = Vec::new();
+            tint(glyph, pixel_type, color, &data, &mut tinted)?;
+
+            Ok(tinted)
+        })
+    }
+
+    fn rasterize_phased(
+        &self,
+        glyph: Glyph,
+        x_phase: f32,
+        y_phase: f32,
+    ) -> Result<Vec<u8>, CacheError> {
+        let data = self.inner.rasterize_phased(glyph, x_phase, y_phase)?;
+        let pixel_type = self.inner.glyph_pixel_type(glyph);
+        let mut tinted = Vec::new();
+        tint(glyph, pixel_type, self.color, &data, &mut tinted)?;
+
+        Ok(tinted)
+    }
+}
+
+/// Turn the inner provider's data for a glyph of the given type into RGBA data tinted with
+/// `color`, pushed onto `out`
+fn tint(
+    glyph: Glyph,
+    pixel_type: PixelType,
+    color: [u8; 4],
+    data: &[u8],
+    out: &mut Vec<u8>,
+) -> Result<(), CacheError> {
+    match pixel_type {
+        PixelType::Alpha => {
+            let [red, green, blue, alpha] = color;
+            out.reserve(data.len() * 4);
+            for &coverage in data {
+                let alpha = ((coverage as u32 * alpha as u32 + 127) / 255) as u8;
+                out.extend_from_slice(&[red, green, blue, alpha]);
+            }
+        }
+        PixelType::RGBA => out.extend_from_slice(data),
+        _ => return Err(CacheError::NonRenderableGlyph(glyph)),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_coverage_is_the_tint_color() {
+        let color = [200, 100, 50, 180];
+        let mut out = Vec::new();
+        tint(Glyph(0), PixelType::Alpha, color, &[255, 0], &mut out).unwrap();
+        assert_eq!(out, [200, 100, 50, 180, 200, 100, 50, 0]);
+    }
+
+    #[test]
+    fn rgba_is_passed_through() {
+        let data = [1, 2, 3, 4];
+        let mut out = Vec::new();
+        tint(Glyph(0), PixelType::RGBA, [255; 4], &data, &mut out).unwrap();
+        assert_eq!(out, data);
+    }
+}
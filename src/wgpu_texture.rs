@@ -0,0 +1,198 @@
+//! A [`Texture`] that stores its glyphs in a wgpu texture
+//!
+//! Alpha and Luma glyphs are stored in an `R8Unorm` texture, with the value in the red channel.
+//! RGBA and RGB glyphs are stored in an `Rgba8UnormSrgb` texture, and subpixel glyphs in an
+//! `Rgba8Unorm` texture (because coverage is linear). Either way, the texture is created with
+//! `TEXTURE_BINDING`, `COPY_DST`, and `COPY_SRC` usages, so shaders can sample it with a bind
+//! group layout like:
+//!
+//! ```ignore
+//! device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+//!     label: Some("glyph cache"),
+//!     entries: &[
+//!         wgpu::BindGroupLayoutEntry {
+//!             binding: 0,
+//!             visibility: wgpu::ShaderStages::FRAGMENT,
+//!             ty: wgpu::BindingType::Texture {
+//!                 sample_type: wgpu::TextureSampleType::Float { filterable: true },
+//!                 view_dimension: wgpu::TextureViewDimension::D2,
+//!                 multisampled: false,
+//!             },
+//!             count: None,
+//!         },
+//!         wgpu::BindGroupLayoutEntry {
+//!             binding: 1,
+//!             visibility: wgpu::ShaderStages::FRAGMENT,
+//!             ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+//!             count: None,
+//!         },
+//!     ],
+//! })
+//! ```
+//!
+//! Every call to [`Texture::put_rect`] becomes a `Queue::write_texture`, which wgpu stages until
+//! the next submission.
+
+use super::*;
+
+use wgpu::{
+    Device, Extent3d, Origin3d, Queue, TexelCopyBufferLayout, TexelCopyTextureInfo, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+
+pub struct WgpuTexture {
+    device: Device,
+    queue: Queue,
+    texture: wgpu::Texture,
+    pixel_type: PixelType,
+}
+
+impl WgpuTexture {
+    /// Create a texture of the given size on the GPU, to store glyphs of the given type
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        width: u32,
+        height: u32,
+        pixel_type: PixelType,
+    ) -> Self {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("elefont glyph cache"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: Self::format(pixel_type),
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        WgpuTexture {
+            device: device.clone(),
+            queue: queue.clone(),
+            texture,
+            pixel_type,
+        }
+    }
+
+    /// The texture format used to store glyphs of the given type
+    pub fn format(pixel_type: PixelType) -> TextureFormat {
+        match pixel_type {
+            PixelType::Alpha | PixelType::Luma => TextureFormat::R8Unorm,
+            PixelType::RGBA | PixelType::RGB => TextureFormat::Rgba8UnormSrgb,
+            PixelType::RGBSubpixel => TextureFormat::Rgba8Unorm,
+        }
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn pixel_type(&self) -> PixelType {
+        self.pixel_type
+    }
+}
+
+impl Texture for WgpuTexture {
+    fn width(&self) -> u32 {
+        self.texture.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.texture.height()
+    }
+
+    fn put_rect(&mut self, pixel: PixelType, data: &[u8], gpu: &TextureGlyph) {
+        let bounds = &gpu.bounds;
+        if bounds.width == 0 || bounds.height == 0 {
+            return;
+        }
+        assert!(bounds.x >= 0);
+        assert!(bounds.y >= 0);
+
+        // Glyphs that don't match the texture format are converted, the same way the image
+        // texture treats them
+        let data = convert_pixels(pixel, self.pixel_type, data);
+        let bytes_per_pixel = match self.pixel_type {
+            PixelType::Alpha | PixelType::Luma => 1,
+            _ => 4,
+        };
+
+        self.queue.write_texture(
+            TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: Origin3d {
+                    x: bounds.x as u32,
+                    y: bounds.y as u32,
+                    z: 0,
+                },
+                aspect: TextureAspect::All,
+            },
+            &data,
+            TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bounds.width * bytes_per_pixel),
+                rows_per_image: Some(bounds.height),
+            },
+            Extent3d {
+                width: bounds.width,
+                height: bounds.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    fn clear(&mut self, _pixel: PixelType) {
+        // Write zeroes in the format the texture stores, so they aren't converted
+        let storage = match self.pixel_type {
+            PixelType::Alpha | PixelType::Luma => self.pixel_type,
+            _ => PixelType::RGBA,
+        };
+        let bounds = Bounds {
+            x: 0,
+            y: 0,
+            width: self.width(),
+            height: self.height(),
+        };
+        let data =
+            alloc::vec![0; (bounds.width * bounds.height) as usize * storage.bytes_per_pixel()];
+        self.put_rect(
+            storage,
+            &data,
+            &TextureGlyph {
+                glyph: Glyph(0),
+                page: 0,
+                bounds,
+
+                rotated: false,
+            },
+        );
+    }
+
+    fn copy_from(&mut self, src: &Self) -> Result<(), CacheError> {
+        // Textures of different formats can't be copied between directly
+        if src.pixel_type != self.pixel_type {
+            return Err(CacheError::Unsupported);
+        }
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_texture_to_texture(
+            src.texture.as_image_copy(),
+            self.texture.as_image_copy(),
+            Extent3d {
+                width: src.width().min(self.width()),
+                height: src.height().min(self.height()),
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+//! The ab_glyph provider, with a real font
+#![cfg(feature = "ab_glyph")]
+
+use ab_glyph::FontRef;
+use elefont::abglyph_provider::SizedFont;
+use elefont::{FontProvider, PixelType};
+
+fn font() -> SizedFont<FontRef<'static>> {
+    let data = include_bytes!("../examples/DejaVuSans.ttf") as &[u8];
+    SizedFont::from_bytes(data, 32.0).expect("Error constructing Font")
+}
+
+#[test]
+fn rasterizes_alpha_coverage() {
+    let font = font();
+    let a = font.single_glyph('a');
+    assert_eq!(font.pixel_type(), PixelType::Alpha);
+
+    let bounds = font.metrics(a).bounds.unwrap();
+    let data = font.rasterize(a).unwrap();
+    assert_eq!(data.len(), (bounds.width * bounds.height) as usize);
+    assert!(data.iter().any(|&alpha| alpha > 0));
+    assert!(data.contains(&0));
+}
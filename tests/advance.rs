@@ -0,0 +1,62 @@
+//! Querying a glyph's advance with `advance`, including glyphs that don't move the pen
+
+use elefont::bitmap_provider::{BitmapFontProvider, BitmapGlyph};
+use elefont::{Bounds, FontCache, NullTexture, PixelType};
+
+/// A font with a 4x4 `e`, an acute accent that draws above the previous glyph without
+/// advancing, and a zero-width space with nothing to draw
+fn font() -> BitmapFontProvider {
+    let glyph = |character, x, width, height, offset, advance| BitmapGlyph {
+        character,
+        source: Bounds {
+            x,
+            y: 0,
+            width,
+            height,
+        },
+        offset,
+        advance,
+    };
+
+    BitmapFontProvider::new(
+        PixelType::Alpha,
+        6,
+        4,
+        vec![255; 24],
+        4.0,
+        vec![
+            glyph('e', 0, 4, 4, (0, -4), 4.0),
+            glyph('\u{301}', 4, 2, 2, (-3, -6), 0.0),
+            glyph('\u{200b}', 0, 0, 0, (0, 0), 0.0),
+        ],
+    )
+}
+
+#[test]
+fn zero_width_glyphs() {
+    let mut cache = FontCache::new(Box::new(font()), NullTexture::new(16, 16));
+    let [e, mark, space] = ['e', '\u{301}', '\u{200b}'].map(|c| cache.font().single_glyph(c));
+
+    assert_eq!(cache.advance(e), (4.0, 0.0));
+    // The mark still has something to draw
+    assert_eq!(cache.advance(mark), (0.0, 0.0));
+    let bounds = cache.render_glyph(mark).unwrap().1.bounds;
+    assert_eq!((bounds.width, bounds.height), (2, 2));
+    // The space doesn't, which `advance` doesn't mind
+    assert!(cache.font().metrics(space).bounds.is_none());
+    assert_eq!(cache.advance(space), (0.0, 0.0));
+}
+
+#[cfg(feature = "rusttype")]
+#[test]
+fn combining_marks_in_a_real_font() {
+    use elefont::rusttype_provider::SizedFont;
+
+    let data = include_bytes!("../examples/DejaVuSans.ttf");
+    let font = SizedFont::from_bytes(&data[..], 24.0).unwrap();
+    let cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+    let mark = cache.font().single_glyph('\u{301}');
+
+    assert!(cache.font().metrics(mark).bounds.is_some());
+    assert_eq!(cache.advance(mark), (0.0, 0.0));
+}
@@ -0,0 +1,92 @@
+//! How often the cache allocates, counted by a wrapper around the system allocator
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, NullTexture};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the allocations made on each thread, so tests running at the same time don't disturb
+/// each other
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// How many allocations the current thread makes while running `f`
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn chars() -> Vec<(char, u32, u32)> {
+    ('a'..='z').map(|c| (c, 4, 6)).collect()
+}
+
+#[test]
+fn repeated_renders_dont_allocate_more() {
+    let mut cache = FontCache::new(Box::new(block_font(&chars())), NullTexture::new(256, 256));
+    let h = cache.font().single_glyph('h');
+    cache.cache_string("hello world").unwrap();
+
+    let hit = allocations(|| {
+        cache.render_glyph(h).unwrap();
+    });
+    assert_eq!(hit, 0);
+    let counts: Vec<_> = (0..5)
+        .map(|_| {
+            allocations(|| {
+                for result in cache.render_string("hello world") {
+                    result.unwrap();
+                }
+            })
+        })
+        .collect();
+    assert!(
+        counts.iter().all(|count| *count == counts[0]),
+        "{:?}",
+        counts
+    );
+}
+
+#[test]
+fn misses_reuse_the_scratch_buffer() {
+    let mut cache = FontCache::new(Box::new(block_font(&chars())), NullTexture::new(256, 256));
+    let glyphs: Vec<_> = ('a'..='z').map(|c| cache.font().single_glyph(c)).collect();
+    for glyph in &glyphs {
+        cache.render_glyph(*glyph).unwrap();
+    }
+    // Clearing keeps the memory of the map and the buffers
+    cache.clear();
+    cache.take_miss_count();
+
+    let misses = allocations(|| {
+        for glyph in &glyphs {
+            cache.render_glyph(*glyph).unwrap();
+        }
+    });
+    assert_eq!(cache.take_miss_count(), glyphs.len());
+    assert_eq!(misses, 0);
+}
@@ -0,0 +1,65 @@
+//! The bitmap font provider, with small sprite sheets built in memory
+
+use elefont::bitmap_provider::{BitmapFontProvider, BitmapGlyph};
+use elefont::{Bounds, FontCache, FontProvider, Glyph, NullTexture, PixelType};
+
+fn glyph(character: char, x: i32, width: u32, height: u32) -> BitmapGlyph {
+    BitmapGlyph {
+        character,
+        source: Bounds {
+            x,
+            y: 0,
+            width,
+            height,
+        },
+        offset: (1, -(height as i32)),
+        advance: width as f32 + 2.0,
+    }
+}
+
+#[test]
+fn alpha_sheet() {
+    // A 3x2 block next to a 1x1 dot, each pixel a different value
+    #[rustfmt::skip]
+    let pixels = vec![
+        1, 2, 3, 7,
+        4, 5, 6, 0,
+    ];
+    let glyphs = [glyph('a', 0, 3, 2), glyph('.', 3, 1, 1)];
+    let font = BitmapFontProvider::new(PixelType::Alpha, 4, 2, pixels, 3.0, glyphs);
+    let a = font.single_glyph('a');
+    let dot = font.single_glyph('.');
+
+    assert_eq!(font.rasterize(a).unwrap(), [1, 2, 3, 4, 5, 6]);
+    assert_eq!(font.rasterize(dot).unwrap(), [7]);
+    let metrics = font.metrics(a);
+    let bounds = metrics.bounds.unwrap();
+    assert_eq!(
+        (bounds.x, bounds.y, bounds.width, bounds.height),
+        (1, -2, 3, 2)
+    );
+    assert_eq!(metrics.advance_x, 5.0);
+    assert_eq!(font.line_height(), 3.0);
+    assert_eq!(font.single_glyph('b'), Glyph::NOTDEF);
+    assert!(font.metrics(Glyph::NOTDEF).bounds.is_none());
+}
+
+#[test]
+fn rgba_sheet() {
+    let red = [255, 0, 0, 255];
+    let blue = [0, 0, 255, 128];
+    let pixels = [red, red, blue, blue].concat();
+    let glyphs = [glyph('r', 0, 2, 1), glyph('b', 2, 2, 1)];
+    let font = BitmapFontProvider::new(PixelType::RGBA, 4, 1, pixels, 1.0, glyphs);
+    let b = font.single_glyph('b');
+
+    assert_eq!(font.pixel_type(), PixelType::RGBA);
+    assert_eq!(font.rasterize(b).unwrap(), [blue, blue].concat());
+
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(16, 16));
+    let (_, texture_glyph) = cache.render_glyph(b).unwrap();
+    assert_eq!(
+        (texture_glyph.bounds.width, texture_glyph.bounds.height),
+        (2, 1)
+    );
+}
@@ -0,0 +1,32 @@
+//! Caching a range of characters with `FontCache::cache_range`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, NullTexture};
+
+#[test]
+fn caches_printable_ascii() {
+    let blocks: Vec<_> = (' '..='~').map(|c| (c, 2, 2)).collect();
+    let mut cache = FontCache::new(Box::new(block_font(&blocks)), NullTexture::new(64, 64));
+
+    cache.cache_range(' ', '~').unwrap();
+    // Everything but the space
+    assert_eq!(cache.len(), 94);
+    assert!(cache.contains(cache.font().single_glyph('~')));
+    assert!(!cache.contains(cache.font().single_glyph(' ')));
+
+    cache.clear();
+    cache.set_keep_whitespace(true);
+    cache.cache_range(' ', '~').unwrap();
+    assert_eq!(cache.len(), 95);
+}
+
+#[test]
+fn skips_surrogates() {
+    let font = block_font(&[('\u{D7FF}', 2, 2), ('\u{E000}', 2, 2)]);
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+
+    cache.cache_range('\u{D7FF}', '\u{E000}').unwrap();
+    assert_eq!(cache.len(), 2);
+}
@@ -0,0 +1,24 @@
+//! The byte ranges yielded by `FontCache::render_string_with_clusters`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, NullTexture};
+
+#[test]
+fn ranges_cover_multi_byte_characters() {
+    let font = block_font(&[('a', 4, 4), ('é', 4, 4), ('€', 4, 4), ('𝄞', 4, 4)]);
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+    let string = "a€é𝄞a";
+
+    let rendered: Vec<_> = cache
+        .render_string_with_clusters(string)
+        .map(Result::unwrap)
+        .collect();
+    let ranges: Vec<_> = rendered.iter().map(|(range, _, _)| range.clone()).collect();
+    assert_eq!(ranges, [0..1, 1..4, 4..6, 6..10, 10..11]);
+    for (range, _, texture_glyph) in &rendered {
+        let character = string[range.clone()].chars().next().unwrap();
+        assert_eq!(texture_glyph.glyph, cache.font().single_glyph(character));
+    }
+}
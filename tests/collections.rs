@@ -0,0 +1,106 @@
+//! Picking one font out of a font collection (a .ttc file) with `from_bytes_indexed`
+#![cfg(any(feature = "rusttype", feature = "ab_glyph", feature = "swash"))]
+
+use elefont::{CacheError, FontProvider};
+
+const DEJAVU_SANS: &[u8] = include_bytes!("../examples/DejaVuSans.ttf");
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// A collection of two copies of DejaVu Sans, where the second one has 1000 units per em
+/// instead of 2048 so the two can be told apart
+///
+/// Both fonts share the tables of the original file, except for the second font's `head`,
+/// which is a modified copy at the end of the collection.
+fn collection() -> Vec<u8> {
+    let tables = u16::from_be_bytes([DEJAVU_SANS[4], DEJAVU_SANS[5]]) as usize;
+    let directory_length = 12 + tables * 16;
+    let header_length = 12 + 2 * 4;
+    let shared = header_length + directory_length * 2;
+    let head = shared + DEJAVU_SANS.len().next_multiple_of(4);
+
+    let mut data = Vec::new();
+    data.extend_from_slice(b"ttcf");
+    data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    data.extend_from_slice(&2u32.to_be_bytes());
+    for font in 0..2 {
+        let directory = header_length + directory_length * font;
+        data.extend_from_slice(&(directory as u32).to_be_bytes());
+    }
+    let mut head_table = None;
+    for font in 0..2 {
+        data.extend_from_slice(&DEJAVU_SANS[..12]);
+        for table in 0..tables {
+            let record = &DEJAVU_SANS[12 + table * 16..][..16];
+            let (offset, length) = (read_u32(record, 8) as usize, read_u32(record, 12) as usize);
+            data.extend_from_slice(&record[..8]);
+            if &record[..4] == b"head" {
+                head_table = Some(&DEJAVU_SANS[offset..offset + length]);
+            }
+            // The offsets in a collection are from the start of the file
+            let offset = if font == 1 && &record[..4] == b"head" {
+                head
+            } else {
+                shared + offset
+            };
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&(length as u32).to_be_bytes());
+        }
+    }
+    data.extend_from_slice(DEJAVU_SANS);
+    data.resize(head, 0);
+    let mut head_table = head_table.expect("DejaVu Sans has a head table").to_vec();
+    head_table[18..20].copy_from_slice(&1000u16.to_be_bytes());
+    data.extend_from_slice(&head_table);
+
+    data
+}
+
+/// The units per em of a parsed font, or `None` if there was no font to parse
+fn units_per_em(font: Result<impl FontProvider, CacheError>) -> Option<u16> {
+    match font {
+        Ok(font) => Some(font.units_per_em()),
+        Err(CacheError::InvalidFont) => None,
+        Err(err) => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[test]
+#[cfg(feature = "rusttype")]
+fn rusttype() {
+    use elefont::rusttype_provider::SizedFont;
+
+    let data = collection();
+    let faces =
+        [0, 1, 2].map(|index| units_per_em(SizedFont::from_bytes_indexed(&data, index, 16.0)));
+    assert_eq!(faces, [Some(2048), Some(1000), None]);
+}
+
+#[test]
+#[cfg(feature = "ab_glyph")]
+fn ab_glyph() {
+    use elefont::abglyph_provider::SizedFont;
+
+    let data = collection();
+    let faces =
+        [0, 1, 2].map(|index| units_per_em(SizedFont::from_bytes_indexed(&data, index, 16.0)));
+    assert_eq!(faces, [Some(2048), Some(1000), None]);
+}
+
+#[test]
+#[cfg(feature = "swash")]
+fn swash() {
+    use elefont::swash_provider::SizedFont;
+
+    let data = collection();
+    let faces =
+        [0, 1, 2].map(|index| units_per_em(SizedFont::from_bytes_indexed(&data, index, 16.0)));
+    assert_eq!(faces, [Some(2048), Some(1000), None]);
+}
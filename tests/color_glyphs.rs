@@ -0,0 +1,106 @@
+//! Telling color glyphs (like emoji) from regular ones with the swash provider
+#![cfg(feature = "swash")]
+
+use elefont::swash_provider::SizedFont;
+use elefont::{FontProvider, Glyph, PixelType};
+
+const DEJAVU_SANS: &[u8] = include_bytes!("../examples/DejaVuSans.ttf");
+
+fn read_u32(data: &[u8], offset: usize) -> usize {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ]) as usize
+}
+
+/// DejaVu Sans with COLR and CPAL tables that color one glyph red, so the test doesn't need an
+/// emoji font
+///
+/// The glyph is drawn as a single layer: its own outline, in the only color of the palette.
+fn with_color_glyph(glyph: Glyph) -> Vec<u8> {
+    let id = (glyph.0 as u16).to_be_bytes();
+    let colr = [
+        &[0, 0, 0, 1][..],           // version 0, one base glyph
+        &[0, 0, 0, 14],              // base glyph records start after the header
+        &[0, 0, 0, 20, 0, 1],        // then one layer record after them
+        &[id[0], id[1], 0, 0, 0, 1], // the glyph's layers start at 0, and it has one
+        &[id[0], id[1], 0, 0],       // the layer is the glyph itself, in color 0
+    ]
+    .concat();
+    let cpal = [
+        &[0, 0, 0, 1, 0, 1, 0, 1][..], // version 0, one entry in one palette, one color
+        &[0, 0, 0, 14, 0, 0],          // the colors start after the palette's first index
+        &[0, 0, 255, 255],             // red, in BGRA
+    ]
+    .concat();
+
+    let count = u16::from_be_bytes([DEJAVU_SANS[4], DEJAVU_SANS[5]]) as usize;
+    let mut tables: Vec<([u8; 4], &[u8])> = (0..count)
+        .map(|table| {
+            let record = 12 + table * 16;
+            let mut tag = [0; 4];
+            tag.copy_from_slice(&DEJAVU_SANS[record..record + 4]);
+            let offset = read_u32(DEJAVU_SANS, record + 8);
+            let length = read_u32(DEJAVU_SANS, record + 12);
+            (tag, &DEJAVU_SANS[offset..offset + length])
+        })
+        .collect();
+    tables.push((*b"COLR", &colr));
+    tables.push((*b"CPAL", &cpal));
+    // Tables are looked up with a binary search, so they have to be sorted by tag
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let mut font = DEJAVU_SANS[..4].to_vec();
+    let count = tables.len() as u16;
+    let search_range = 16 * (1 << (15 - count.leading_zeros()));
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    for value in [
+        count,
+        search_range,
+        entry_selector,
+        count * 16 - search_range,
+    ] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, data) in &tables {
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&[0; 4]);
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        font.extend_from_slice(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+
+    font
+}
+
+#[test]
+fn color_glyphs_are_rgba() {
+    let plain = SizedFont::from_bytes(DEJAVU_SANS, 24.0).unwrap();
+    let a = plain.single_glyph('a');
+    let b = plain.single_glyph('b');
+    assert!(!plain.is_color_glyph(a));
+    let data = with_color_glyph(a);
+    let font = SizedFont::from_bytes(&data, 24.0).unwrap();
+
+    assert!(font.is_color_glyph(a));
+    assert_eq!(font.glyph_pixel_type(a), PixelType::RGBA);
+    assert!(!font.is_color_glyph(b));
+    assert_eq!(font.glyph_pixel_type(b), PixelType::Alpha);
+
+    let bounds = font.metrics(a).bounds.unwrap();
+    let pixels = font.rasterize(a).unwrap();
+    assert_eq!(pixels.len(), (bounds.width * bounds.height) as usize * 4);
+    // Every covered pixel is red
+    assert!(pixels
+        .chunks_exact(4)
+        .filter(|pixel| pixel[3] > 0)
+        .all(|pixel| pixel[0] > 0 && pixel[1] == 0 && pixel[2] == 0));
+    assert!(pixels.chunks_exact(4).any(|pixel| pixel[3] > 250));
+}
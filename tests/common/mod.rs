@@ -0,0 +1,44 @@
+//! Fonts for the integration tests, which don't need a real font file
+
+use elefont::bitmap_provider::{BitmapFontProvider, BitmapGlyph};
+use elefont::{Bounds, PixelType};
+
+/// A font where each character is a solid block of the given width and height, sitting on the
+/// baseline and advancing by its width
+pub fn block_font(blocks: &[(char, u32, u32)]) -> BitmapFontProvider {
+    let width: u32 = blocks.iter().map(|(_, width, _)| width).sum();
+    let height = blocks
+        .iter()
+        .map(|(_, _, height)| *height)
+        .max()
+        .unwrap_or(0);
+    let mut x = 0;
+    let glyphs: Vec<_> = blocks
+        .iter()
+        .map(|&(character, width, height)| {
+            let glyph = BitmapGlyph {
+                character,
+                source: Bounds {
+                    x,
+                    y: 0,
+                    width,
+                    height,
+                },
+                offset: (0, -(height as i32)),
+                advance: width as f32,
+            };
+            x += width as i32;
+            glyph
+        })
+        .collect();
+    let pixels = vec![255; (width * height) as usize];
+
+    BitmapFontProvider::new(
+        PixelType::Alpha,
+        width,
+        height,
+        pixels,
+        height as f32,
+        glyphs,
+    )
+}
@@ -0,0 +1,82 @@
+//! Reclaiming the holes left by removed glyphs with `FontCache::compact`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, FontCacheBuilder, Glyph, MissingGlyphPolicy, NullTexture, Texture};
+
+fn cache(policy: MissingGlyphPolicy) -> FontCache<NullTexture> {
+    let blocks = [
+        ('a', 8, 8),
+        ('b', 8, 8),
+        ('c', 8, 8),
+        ('d', 8, 8),
+        ('e', 8, 8),
+        ('f', 8, 8),
+    ];
+    FontCacheBuilder::new(Box::new(block_font(&blocks)), NullTexture::new(16, 32))
+        .padding(0)
+        .missing_glyph_policy(policy)
+        .build()
+}
+
+/// How far down the texture the cached glyphs reach
+fn used_height<T: Texture>(cache: &FontCache<T>) -> i32 {
+    cache
+        .iter()
+        .map(|(_, glyph)| glyph.bounds.y + glyph.bounds.height as i32)
+        .max()
+        .unwrap_or(0)
+}
+
+fn glyph<T: Texture>(cache: &FontCache<T>, character: char) -> Glyph {
+    cache.font().single_glyph(character)
+}
+
+#[test]
+fn compacting_reduces_the_used_height() {
+    let mut cache = cache(MissingGlyphPolicy::Render);
+    cache.cache_string("abcdef").unwrap();
+    assert_eq!(used_height(&cache), 24);
+    for c in ['b', 'c', 'e'] {
+        cache.remove_glyph(glyph(&cache, c)).unwrap();
+    }
+    assert_eq!(used_height(&cache), 24);
+    cache.take_miss_count();
+
+    cache.compact().unwrap();
+    assert_eq!(used_height(&cache), 16);
+    assert_eq!(cache.take_miss_count(), 3);
+    assert_eq!(cache.len(), 3);
+    for c in ['a', 'd', 'f'] {
+        assert!(cache.contains(glyph(&cache, c)));
+    }
+}
+
+#[test]
+fn compacting_keeps_tofus() {
+    let mut cache = cache(MissingGlyphPolicy::Tofu);
+    cache.cache_string("ab☃c").unwrap();
+    let notdef = cache.font().notdef();
+    let tofu = cache.get(notdef).unwrap();
+    cache.remove_glyph(glyph(&cache, 'a')).unwrap();
+
+    cache.compact().unwrap();
+    assert_eq!(cache.len(), 3);
+    let moved = cache.get(notdef).unwrap();
+    assert_eq!(
+        (moved.bounds.width, moved.bounds.height),
+        (tofu.bounds.width, tofu.bounds.height)
+    );
+}
+
+#[test]
+fn compacting_after_growing_does_nothing() {
+    let mut cache = cache(MissingGlyphPolicy::Render);
+    cache.cache_string("abcd").unwrap();
+    cache.grow_texture(NullTexture::new(32, 32)).unwrap();
+    cache.take_miss_count();
+
+    cache.compact().unwrap();
+    assert_eq!(cache.take_miss_count(), 0);
+}
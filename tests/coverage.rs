@@ -0,0 +1,39 @@
+//! Listing the characters a font covers with `FontProvider::covered_chars`
+
+mod common;
+
+use common::block_font;
+use elefont::FontProvider;
+
+fn covered(font: &dyn FontProvider) -> Vec<char> {
+    let mut chars = Vec::new();
+    font.covered_chars(&mut chars);
+    chars.sort_unstable();
+    chars
+}
+
+#[test]
+fn bitmap_fonts_cover_their_glyphs() {
+    let font = block_font(&[('a', 2, 2), ('é', 2, 2), ('𝄞', 2, 2)]);
+
+    assert_eq!(covered(&font), ['a', 'é', '𝄞']);
+}
+
+#[test]
+#[cfg(feature = "rusttype")]
+fn rusttype_reads_the_cmap() {
+    use elefont::rusttype_provider::SizedFont;
+    use rusttype::Font;
+
+    let data = include_bytes!("../examples/DejaVuSans.ttf") as &[u8];
+    let parsed = SizedFont::from_bytes(data, 16.0).unwrap();
+    let chars = covered(&parsed);
+    // DejaVu Sans covers most of Latin, Greek and Cyrillic, and a lot of symbols
+    assert!(chars.len() > 3000, "{}", chars.len());
+    assert!(chars.contains(&'a') && chars.contains(&'Ж') && chars.contains(&'∑'));
+    assert!(!chars.contains(&'\u{4e00}'));
+
+    // Without the cmap every character is looked up, which finds the same ones
+    let font = Font::from_bytes(data).unwrap();
+    assert_eq!(covered(&SizedFont::new(font, 16.0)), chars);
+}
@@ -0,0 +1,59 @@
+//! Reading a font's design units with `units_per_em` and `unscaled_advance`, which don't depend
+//! on the size it's rasterized at
+
+mod common;
+
+use common::block_font;
+use elefont::FontProvider;
+
+/// The size of DejaVu Sans' em square, and the advance of its `A`, in design units
+#[cfg(any(feature = "rusttype", feature = "ab_glyph", feature = "swash"))]
+const DEJAVU_SANS: (u16, f32) = (2048, 1401.0);
+
+/// The units per em of a font, and the unscaled advance of its `A`
+#[cfg(any(feature = "rusttype", feature = "ab_glyph", feature = "swash"))]
+fn design_metrics(font: &dyn FontProvider) -> (u16, f32) {
+    let a = font.single_glyph('A');
+    (font.units_per_em(), font.unscaled_advance(a).unwrap())
+}
+
+#[test]
+fn bitmap_fonts_use_the_default() {
+    assert_eq!(block_font(&[('A', 4, 5)]).units_per_em(), 1000);
+}
+
+#[test]
+#[cfg(feature = "rusttype")]
+fn rusttype() {
+    use elefont::rusttype_provider::SizedFont;
+
+    let data = include_bytes!("../examples/DejaVuSans.ttf");
+    for size in [12.0, 40.0] {
+        let font = SizedFont::from_bytes(&data[..], size).unwrap();
+        assert_eq!(design_metrics(&font), DEJAVU_SANS);
+    }
+}
+
+#[test]
+#[cfg(feature = "ab_glyph")]
+fn ab_glyph() {
+    use elefont::abglyph_provider::SizedFont;
+
+    let data = include_bytes!("../examples/DejaVuSans.ttf");
+    for size in [12.0, 40.0] {
+        let font = SizedFont::from_bytes(&data[..], size).unwrap();
+        assert_eq!(design_metrics(&font), DEJAVU_SANS);
+    }
+}
+
+#[test]
+#[cfg(feature = "swash")]
+fn swash() {
+    use elefont::swash_provider::SizedFont;
+
+    let data = include_bytes!("../examples/DejaVuSans.ttf");
+    for size in [12.0, 40.0] {
+        let font = SizedFont::from_bytes(&data[..], size).unwrap();
+        assert_eq!(design_metrics(&font), DEJAVU_SANS);
+    }
+}
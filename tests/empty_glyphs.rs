@@ -0,0 +1,51 @@
+//! Glyphs with nothing to draw, whose bounds are `None`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, FontCacheBuilder, NullTexture};
+
+fn cache() -> FontCache<NullTexture> {
+    // A zero height block has no bounds, like a space in a real font
+    let font = block_font(&[('a', 4, 6), (' ', 3, 0)]);
+    FontCache::new(Box::new(font), NullTexture::new(16, 16))
+}
+
+#[test]
+fn render_reports_metrics_without_storing_anything() {
+    let mut cache = cache();
+    let space = cache.font().single_glyph(' ');
+
+    let (metrics, texture_glyph) = cache.render_glyph(space).unwrap();
+    assert!(metrics.bounds.is_none());
+    assert_eq!(metrics.advance_x, 3.0);
+    assert_eq!(texture_glyph.glyph, space);
+    assert_eq!(
+        (texture_glyph.bounds.width, texture_glyph.bounds.height),
+        (0, 0)
+    );
+    assert_eq!(cache.stats().glyphs, 0);
+}
+
+#[test]
+fn layout_moves_the_pen_past_it() {
+    let mut cache = cache();
+
+    let glyphs = cache.layout_string("a a").unwrap();
+    let pens: Vec<_> = glyphs.iter().map(|glyph| glyph.x).collect();
+    assert_eq!(pens, [0.0, 4.0, 7.0]);
+    assert!(glyphs[1].bounds().is_none());
+    assert_eq!(glyphs[2].bounds().unwrap().x, 7);
+}
+
+#[test]
+fn measuring_skips_its_ink() {
+    let font = block_font(&[('a', 4, 6), (' ', 3, 0)]);
+    let cache = FontCacheBuilder::new(Box::new(font), NullTexture::new(16, 16))
+        .keep_whitespace(true)
+        .build();
+
+    assert!(cache.measure_string_bounds(" ").is_none());
+    let bounds = cache.measure_string_bounds(" a").unwrap();
+    assert_eq!((bounds.x, bounds.width), (3.0, 4.0));
+}
@@ -0,0 +1,84 @@
+//! Evicting glyphs with `EvictionPolicy::Lru`
+
+mod common;
+
+use common::block_font;
+use elefont::{CacheError, EvictionPolicy, FontCache, FontCacheBuilder, NullTexture};
+
+fn lru_cache(blocks: &[(char, u32, u32)], width: u32, height: u32) -> FontCache<NullTexture> {
+    FontCacheBuilder::new(
+        Box::new(block_font(blocks)),
+        NullTexture::new(width, height),
+    )
+    .padding(0)
+    .eviction(EvictionPolicy::Lru)
+    .build()
+}
+
+#[test]
+fn evicts_a_glyph_big_enough_for_the_new_one() {
+    let mut cache = lru_cache(&[('a', 4, 8), ('b', 4, 8), ('B', 8, 8), ('C', 8, 8)], 16, 8);
+    cache.cache_string("a").unwrap();
+    cache.cache_string("b").unwrap();
+    cache.cache_string("B").unwrap();
+
+    // The two oldest glyphs only make room together, while the space of `B` fits `C` by itself
+    cache.cache_string("C").unwrap();
+    let contains = |c| cache.contains(cache.font().single_glyph(c));
+    assert!(contains('a'));
+    assert!(contains('b'));
+    assert!(!contains('B'));
+    assert!(contains('C'));
+    assert_eq!(cache.len(), 3);
+}
+
+#[test]
+fn evicts_the_oldest_glyphs_when_none_is_big_enough() {
+    let mut cache = lru_cache(
+        &[
+            ('a', 4, 8),
+            ('b', 4, 8),
+            ('c', 4, 8),
+            ('d', 4, 8),
+            ('B', 8, 8),
+        ],
+        16,
+        8,
+    );
+    cache.cache_string("abcd").unwrap();
+
+    cache.cache_string("B").unwrap();
+    let contains = |c| cache.contains(cache.font().single_glyph(c));
+    assert!(!contains('a'));
+    assert!(!contains('b'));
+    assert!(contains('c'));
+    assert!(contains('d'));
+    assert!(contains('B'));
+}
+
+#[test]
+fn glyphs_of_the_current_call_are_not_evicted() {
+    let mut cache = lru_cache(&[('a', 8, 8), ('b', 8, 8), ('c', 8, 8)], 16, 8);
+
+    let rendered: Vec<_> = cache.render_string("abc").collect();
+    let first = rendered[0].as_ref().unwrap().1;
+    let second = rendered[1].as_ref().unwrap().1;
+    assert!(matches!(rendered[2], Err(CacheError::OutOfSpace { .. })));
+    assert_ne!(first.bounds.x, second.bounds.x);
+    assert_eq!(cache.len(), 2);
+
+    // The next call is free to evict them
+    let (_, third) = cache.render_glyph(cache.font().single_glyph('c')).unwrap();
+    assert_eq!(third.bounds.x, first.bounds.x);
+    assert!(!cache.contains(cache.font().single_glyph('a')));
+}
+
+#[test]
+fn glyphs_of_an_earlier_call_are_evicted() {
+    let mut cache = lru_cache(&[('a', 8, 8), ('b', 8, 8), ('c', 8, 8)], 16, 8);
+    cache.cache_string("ab").unwrap();
+
+    cache.cache_string("bc").unwrap();
+    assert!(!cache.contains(cache.font().single_glyph('a')));
+    assert_eq!(cache.len(), 2);
+}
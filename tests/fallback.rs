@@ -0,0 +1,61 @@
+//! Combining fonts with `FallbackProvider`
+
+mod common;
+
+use common::block_font;
+use elefont::fallback_provider::FallbackProvider;
+use elefont::{FontCache, FontProvider, NullTexture};
+
+fn chain() -> FallbackProvider {
+    FallbackProvider::new(vec![
+        Box::new(block_font(&[('a', 4, 8)])),
+        Box::new(block_font(&[('b', 6, 4)])),
+    ])
+}
+
+#[test]
+fn glyphs_round_trip_through_the_encoding() {
+    let chain = chain();
+    let a = block_font(&[('a', 4, 8)]).single_glyph('a');
+    let b = block_font(&[('b', 6, 4)]).single_glyph('b');
+
+    let chain_a = chain.single_glyph('a');
+    let chain_b = chain.single_glyph('b');
+    assert_eq!(FallbackProvider::decode(chain_a), (0, a));
+    assert_eq!(FallbackProvider::decode(chain_b), (1, b));
+    // The first font's glyphs keep their ids
+    assert_eq!(chain_a, a);
+    assert_eq!(FallbackProvider::encode(1, b), chain_b);
+
+    let mut glyphs = Vec::new();
+    chain.glyphs("ab", &mut glyphs);
+    assert_eq!(glyphs, [chain_a, chain_b]);
+    // Each glyph is measured and rasterized by its own font
+    assert_eq!(chain.metrics(chain_b).advance_x, 6.0);
+    assert_eq!(chain.rasterize(chain_b).unwrap().len(), 6 * 4);
+    assert_eq!(chain.single_glyph('c'), chain.notdef());
+}
+
+#[test]
+fn renders_glyphs_from_both_fonts() {
+    let mut cache = FontCache::new(Box::new(chain()), NullTexture::new(64, 64));
+    let sizes: Vec<_> = cache
+        .render_string("ab")
+        .map(|result| {
+            let (_, glyph) = result.unwrap();
+            (glyph.bounds.width, glyph.bounds.height)
+        })
+        .collect();
+    assert_eq!(sizes, [(4, 8), (6, 4)]);
+}
+
+#[test]
+fn ids_that_dont_fit_become_notdef() {
+    // The inner chain's glyphs for `b` use the top bits, which the outer chain needs
+    let nested = FallbackProvider::new(vec![Box::new(chain())]);
+    assert_eq!(nested.single_glyph('b'), nested.notdef());
+
+    let mut glyphs = Vec::new();
+    nested.glyphs("ab", &mut glyphs);
+    assert_eq!(glyphs, [nested.single_glyph('a'), nested.notdef()]);
+}
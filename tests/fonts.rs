@@ -0,0 +1,78 @@
+//! Glyphs of several fonts sharing one cache
+
+use elefont::bitmap_provider::{BitmapFontProvider, BitmapGlyph};
+use elefont::{Bounds, FontCache, FontId, GlyphKey, NullTexture, PixelType};
+
+/// A font with only an `a`, drawn as a solid block of the given size
+fn block_font(width: u32, height: u32) -> BitmapFontProvider {
+    let glyph = BitmapGlyph {
+        character: 'a',
+        source: Bounds {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        },
+        offset: (0, -(height as i32)),
+        advance: width as f32,
+    };
+    let pixels = vec![255; (width * height) as usize];
+
+    BitmapFontProvider::new(
+        PixelType::Alpha,
+        width,
+        height,
+        pixels,
+        height as f32,
+        Some(glyph),
+    )
+}
+
+fn rect(bounds: &Bounds) -> (i32, i32, u32, u32) {
+    (bounds.x, bounds.y, bounds.width, bounds.height)
+}
+
+#[test]
+fn same_glyph_ids_in_different_fonts_dont_collide() {
+    let mut cache = FontCache::new(Box::new(block_font(4, 4)), NullTexture::new(64, 64));
+    let big_font = cache.add_font(Box::new(block_font(8, 12)));
+    let small = cache.font().single_glyph('a');
+    let big = cache.font_by_id(big_font).unwrap().single_glyph('a');
+    assert_eq!(small, big);
+
+    let (small_metrics, small_glyph) = cache.render_glyph(small).unwrap();
+    let (big_metrics, big_glyph) = cache.render_glyph_with(big_font, big).unwrap();
+    assert_eq!(cache.len(), 2);
+    assert_eq!(small_metrics.advance_x, 4.0);
+    assert_eq!(big_metrics.advance_x, 8.0);
+    assert_eq!(
+        (small_glyph.bounds.width, small_glyph.bounds.height),
+        (4, 4)
+    );
+    assert_eq!((big_glyph.bounds.width, big_glyph.bounds.height), (8, 12));
+
+    // Rendering again hits each font's own entry
+    let (_, again) = cache.render_glyph(small).unwrap();
+    assert_eq!(rect(&again.bounds), rect(&small_glyph.bounds));
+    let (_, again) = cache.render_glyph_with(big_font, big).unwrap();
+    assert_eq!(rect(&again.bounds), rect(&big_glyph.bounds));
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn iter_and_get_key_tell_fonts_apart() {
+    let mut cache = FontCache::new(Box::new(block_font(4, 4)), NullTexture::new(64, 64));
+    let big_font = cache.add_font(Box::new(block_font(8, 12)));
+    let glyph = cache.font().single_glyph('a');
+    cache.render_glyph(glyph).unwrap();
+    let (_, big_glyph) = cache.render_glyph_with(big_font, glyph).unwrap();
+
+    let mut keys: Vec<_> = cache.iter().map(|(key, _)| key.font).collect();
+    keys.sort_by_key(|font| font.0);
+    assert_eq!(keys, [FontId::DEFAULT, big_font]);
+
+    let key = GlyphKey::new(glyph).with_font(big_font);
+    let found = cache.get_key(key).unwrap();
+    assert_eq!(rect(&found.bounds), rect(&big_glyph.bounds));
+    assert_eq!((found.bounds.width, found.bounds.height), (8, 12));
+}
@@ -0,0 +1,60 @@
+//! The fractional bounds of glyphs, compared to the whole pixels they're packed with
+#![cfg(feature = "rusttype")]
+
+use elefont::FontProvider;
+
+const DEJAVU_SANS: &[u8] = include_bytes!("../examples/DejaVuSans.ttf");
+
+fn assert_within_a_pixel(font: &dyn FontProvider) {
+    let mut glyphs = Vec::new();
+    font.glyphs("Hello, gjy! @W", &mut glyphs);
+    for glyph in glyphs {
+        let metrics = font.metrics(glyph);
+        let (bounds, exact) = match (metrics.bounds, metrics.bounds_f) {
+            (Some(bounds), Some(exact)) => (bounds, exact),
+            (None, None) => continue,
+            _ => panic!("{:?} has only one kind of bounds", glyph),
+        };
+        let edges = [
+            (bounds.x as f32, exact.x),
+            (bounds.y as f32, exact.y),
+            (
+                (bounds.x + bounds.width as i32) as f32,
+                exact.x + exact.width,
+            ),
+            (
+                (bounds.y + bounds.height as i32) as f32,
+                exact.y + exact.height,
+            ),
+        ];
+        for (whole, fractional) in edges {
+            assert!(
+                (whole - fractional).abs() <= 1.0,
+                "{:?}: {:?} and {:?} are more than a pixel apart",
+                glyph,
+                bounds,
+                exact
+            );
+        }
+    }
+}
+
+#[test]
+fn rusttype() {
+    let font = elefont::rusttype_provider::SizedFont::from_bytes(DEJAVU_SANS, 27.5).unwrap();
+    assert_within_a_pixel(&font);
+}
+
+#[test]
+#[cfg(feature = "ab_glyph")]
+fn ab_glyph() {
+    let font = elefont::abglyph_provider::SizedFont::from_bytes(DEJAVU_SANS, 27.5).unwrap();
+    assert_within_a_pixel(&font);
+}
+
+#[test]
+#[cfg(feature = "swash")]
+fn swash() {
+    let font = elefont::swash_provider::SizedFont::from_bytes(DEJAVU_SANS, 27.5).unwrap();
+    assert_within_a_pixel(&font);
+}
@@ -0,0 +1,57 @@
+//! Parsing fonts that aren't valid with each provider's `from_bytes`
+#![cfg(any(feature = "rusttype", feature = "ab_glyph", feature = "swash"))]
+
+use elefont::CacheError;
+
+const DEJAVU_SANS: &[u8] = include_bytes!("../examples/DejaVuSans.ttf");
+
+/// Data that doesn't hold a font: nothing at all, text, a font header without any tables, and a
+/// font cut off partway through
+fn not_fonts() -> Vec<&'static [u8]> {
+    vec![
+        b"",
+        b"certainly not a font, just some text",
+        &[0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        &DEJAVU_SANS[..1000],
+    ]
+}
+
+fn assert_invalid<T>(result: Result<T, CacheError>) {
+    assert!(matches!(result, Err(CacheError::InvalidFont)));
+}
+
+#[test]
+#[cfg(feature = "rusttype")]
+fn rusttype() {
+    use elefont::rusttype_provider::SizedFont;
+
+    for data in not_fonts() {
+        assert_invalid(SizedFont::from_bytes(data, 16.0));
+    }
+    assert!(SizedFont::from_bytes(DEJAVU_SANS, 16.0).is_ok());
+    assert_invalid(SizedFont::from_bytes_indexed(DEJAVU_SANS, 1, 16.0));
+}
+
+#[test]
+#[cfg(feature = "ab_glyph")]
+fn ab_glyph() {
+    use elefont::abglyph_provider::SizedFont;
+
+    for data in not_fonts() {
+        assert_invalid(SizedFont::from_bytes(data, 16.0));
+    }
+    assert!(SizedFont::from_bytes(DEJAVU_SANS, 16.0).is_ok());
+    assert_invalid(SizedFont::from_bytes_indexed(DEJAVU_SANS, 1, 16.0));
+}
+
+#[test]
+#[cfg(feature = "swash")]
+fn swash() {
+    use elefont::swash_provider::SizedFont;
+
+    for data in not_fonts() {
+        assert_invalid(SizedFont::from_bytes(data, 16.0));
+    }
+    assert!(SizedFont::from_bytes(DEJAVU_SANS, 16.0).is_ok());
+    assert_invalid(SizedFont::from_bytes_indexed(DEJAVU_SANS, 1, 16.0));
+}
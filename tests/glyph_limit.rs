@@ -0,0 +1,39 @@
+//! Refusing glyphs over `FontCache::set_max_glyph_dimensions`
+
+mod common;
+
+use common::block_font;
+use elefont::{Bounds, CacheError, FontCache, NullTexture};
+
+#[test]
+fn glyphs_over_the_limit_are_refused() {
+    let font = block_font(&[('a', 4, 4), ('b', 6, 10)]);
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+    cache.set_max_glyph_dimensions(Some((8, 8)));
+    let a = cache.font().single_glyph('a');
+    let b = cache.font().single_glyph('b');
+
+    match cache.render_glyph(b) {
+        Err(CacheError::GlyphTooLarge { glyph, size, max }) => {
+            assert_eq!(glyph, b);
+            assert_eq!(size, (6, 10));
+            assert_eq!(max, (8, 8));
+        }
+        other => panic!("expected the glyph to be too large, got {:?}", other),
+    }
+    assert!(!cache.contains(b));
+    let needed = Bounds {
+        x: 0,
+        y: 0,
+        width: 6,
+        height: 10,
+    };
+    assert!(cache.next_position(needed).is_none());
+
+    // Glyphs under the limit are still cached
+    cache.render_glyph(a).unwrap();
+    assert!(cache.contains(a));
+
+    cache.set_max_glyph_dimensions(None);
+    cache.render_glyph(b).unwrap();
+}
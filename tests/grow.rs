@@ -0,0 +1,73 @@
+//! Enlarging the texture with `FontCache::grow_texture`
+
+mod common;
+
+use common::block_font;
+use elefont::{
+    CacheError, FontCache, FontCacheBuilder, NullTexture, PixelType, Texture, TextureGlyph,
+    TextureOrigin,
+};
+
+#[test]
+fn keeps_the_cached_glyphs() {
+    let mut cache = FontCache::new(Box::new(block_font(&[('a', 4, 4)])), NullTexture::new(8, 8));
+    cache.cache_string("a").unwrap();
+
+    let old = cache.grow_texture(NullTexture::new(16, 8)).unwrap();
+    assert_eq!(old.width(), 8);
+    assert_eq!(cache.texture().width(), 16);
+    assert!(cache.contains(cache.font().single_glyph('a')));
+}
+
+#[test]
+fn rejects_smaller_textures() {
+    let mut cache = FontCache::new(Box::new(block_font(&[('a', 4, 4)])), NullTexture::new(8, 8));
+
+    assert!(matches!(
+        cache.grow_texture(NullTexture::new(16, 4)),
+        Err(CacheError::Unsupported)
+    ));
+    assert_eq!(cache.texture().height(), 8);
+}
+
+#[test]
+fn rejects_height_changes_from_the_bottom_left() {
+    let mut cache =
+        FontCacheBuilder::new(Box::new(block_font(&[('a', 4, 4)])), NullTexture::new(8, 8))
+            .texture_origin(TextureOrigin::BottomLeft)
+            .build();
+
+    assert!(matches!(
+        cache.grow_texture(NullTexture::new(8, 16)),
+        Err(CacheError::Unsupported)
+    ));
+    assert!(cache.grow_texture(NullTexture::new(16, 8)).is_ok());
+}
+
+/// A texture that can't be copied from, like one that lives on the GPU
+struct WriteOnly(u32, u32);
+
+impl Texture for WriteOnly {
+    fn width(&self) -> u32 {
+        self.0
+    }
+
+    fn height(&self) -> u32 {
+        self.1
+    }
+
+    fn put_rect(&mut self, _pixel: PixelType, _data: &[u8], _gpu: &TextureGlyph) {}
+}
+
+#[test]
+fn fails_for_textures_that_cant_be_copied() {
+    let mut cache = FontCache::new(Box::new(block_font(&[('a', 4, 4)])), WriteOnly(8, 8));
+    cache.cache_string("a").unwrap();
+
+    assert!(matches!(
+        cache.grow_texture(WriteOnly(16, 16)),
+        Err(CacheError::Unsupported)
+    ));
+    assert_eq!(cache.texture().width(), 8);
+    assert!(cache.contains(cache.font().single_glyph('a')));
+}
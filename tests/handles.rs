@@ -0,0 +1,54 @@
+//! Reclaiming glyphs once their `GlyphHandle`s are dropped
+
+mod common;
+
+use common::block_font;
+use elefont::{CacheError, FontCache, FontCacheBuilder, NullTexture};
+
+/// Room for two of the three glyphs
+fn cache() -> FontCache<NullTexture> {
+    FontCacheBuilder::new(
+        Box::new(block_font(&[('a', 4, 4), ('b', 4, 4), ('c', 4, 4)])),
+        NullTexture::new(8, 4),
+    )
+    .padding(0)
+    .build()
+}
+
+#[test]
+fn dropping_every_handle_frees_the_glyph() {
+    let mut cache = cache();
+    let a = cache.font().single_glyph('a');
+    let handle = cache.render_glyph_handle(a).unwrap();
+    let copy = handle.clone();
+    cache.cache_string("b").unwrap();
+
+    // A live copy of the handle keeps `a`
+    drop(handle);
+    let c = cache.font().single_glyph('c');
+    assert!(matches!(
+        cache.render_glyph(c),
+        Err(CacheError::OutOfSpace { .. })
+    ));
+    assert!(cache.contains(a));
+
+    drop(copy);
+    let (_, rendered) = cache.render_glyph(c).unwrap();
+    assert!(!cache.contains(a));
+    assert_eq!((rendered.bounds.x, rendered.bounds.y), (0, 0));
+}
+
+#[test]
+fn glyphs_used_earlier_in_the_call_are_not_reclaimed() {
+    let mut cache = cache();
+    let a = cache.font().single_glyph('a');
+    drop(cache.render_glyph_handle(a).unwrap());
+    cache.cache_string("b").unwrap();
+
+    let rendered: Vec<_> = cache.render_string("ac").collect();
+    let (_, first) = rendered[0].as_ref().unwrap();
+    assert_eq!((first.bounds.x, first.bounds.y), (0, 0));
+    // `a` was handed out by this call, so `c` can't take its place
+    assert!(matches!(rendered[1], Err(CacheError::OutOfSpace { .. })));
+    assert!(cache.contains(a));
+}
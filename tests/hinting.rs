@@ -0,0 +1,72 @@
+//! Turning hinting on and off with each provider's `with_hinting`
+#![cfg(any(feature = "rusttype", feature = "ab_glyph", feature = "swash"))]
+
+use elefont::{FontCache, FontProvider, NullTexture};
+
+const DEJAVU_SANS: &[u8] = include_bytes!("../examples/DejaVuSans.ttf");
+const TEXT: &str = "Hinted text";
+
+/// Cache each glyph of the text, returning the advance and rasterized data of each one
+fn render(font: impl FontProvider + 'static) -> Vec<(f32, Vec<u8>)> {
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(256, 256));
+    cache.cache_string(TEXT).unwrap();
+    let glyphs: Vec<_> = TEXT
+        .chars()
+        .map(|character| cache.font().single_glyph(character))
+        .collect();
+
+    glyphs
+        .into_iter()
+        .map(|glyph| {
+            let mut data = Vec::new();
+            let metrics = cache.rasterize_glyph(glyph, &mut data).unwrap();
+            (metrics.advance_x, data)
+        })
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "rusttype")]
+fn rusttype_records_the_setting() {
+    use elefont::rusttype_provider::SizedFont;
+
+    let font = SizedFont::from_bytes(DEJAVU_SANS, 11.0).unwrap();
+    assert!(!font.hinting());
+    let hinted = font.with_hinting(true);
+    assert!(hinted.hinting());
+    assert!(!hinted.with_hinting(false).hinting());
+    assert_eq!(render(hinted), render(font));
+}
+
+#[test]
+#[cfg(feature = "ab_glyph")]
+fn ab_glyph_records_the_setting() {
+    use elefont::abglyph_provider::SizedFont;
+
+    let font = SizedFont::from_bytes(DEJAVU_SANS, 11.0).unwrap();
+    assert!(!font.hinting());
+    let hinted = font.with_hinting(true);
+    assert!(hinted.hinting());
+    assert!(!hinted.with_hinting(false).hinting());
+    assert_eq!(render(hinted), render(font));
+}
+
+#[test]
+#[cfg(feature = "swash")]
+fn swash_hints_the_images() {
+    use elefont::swash_provider::SizedFont;
+
+    let font = SizedFont::from_bytes(DEJAVU_SANS, 11.0).unwrap();
+    assert!(!font.hinting());
+    let hinted = font.with_hinting(true);
+    assert!(hinted.hinting());
+    assert!(!hinted.with_hinting(false).hinting());
+
+    let (hinted, unhinted) = (render(hinted), render(font));
+    // Advances are the unhinted metrics either way
+    let advances = |glyphs: &[(f32, Vec<u8>)]| -> Vec<f32> {
+        glyphs.iter().map(|(advance, _)| *advance).collect()
+    };
+    assert_eq!(advances(&hinted), advances(&unhinted));
+    assert_ne!(hinted, unhinted);
+}
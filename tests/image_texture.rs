@@ -0,0 +1,46 @@
+//! Caching glyphs into an `image` buffer
+#![cfg(feature = "image")]
+
+mod common;
+
+use common::block_font;
+use elefont::FontCache;
+use image::{DynamicImage, GenericImageView, ImageBuffer, Rgba};
+
+type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+#[test]
+fn clear_and_zero_erases_every_pixel() {
+    let font = block_font(&[('a', 4, 6), ('b', 5, 3)]);
+    let mut cache = FontCache::new(Box::new(font), Image::new(16, 16));
+    cache.cache_string("ab").unwrap();
+    cache.take_dirty_region();
+    assert!(cache.texture().pixels().any(|pixel| pixel.0 != [0; 4]));
+
+    cache.clear_and_zero();
+    assert!(cache.texture().pixels().all(|pixel| pixel.0 == [0; 4]));
+    assert!(cache.is_empty());
+    let dirty = cache.take_dirty_region().unwrap();
+    assert_eq!(
+        (dirty.x, dirty.y, dirty.width, dirty.height),
+        (0, 0, 16, 16)
+    );
+}
+
+#[test]
+fn to_image_has_the_rendered_glyphs() {
+    let font = block_font(&[('a', 4, 6), ('b', 5, 3)]);
+    let mut cache = FontCache::new(Box::new(font), Image::new(16, 16));
+    let empty = cache.to_image().unwrap();
+    assert!(empty.pixels().all(|(_, _, pixel)| pixel.0[3] == 0));
+
+    cache.cache_string("ab").unwrap();
+    let image = cache.to_image().unwrap();
+    assert!(matches!(image, DynamicImage::ImageLumaA8(_)));
+    assert_eq!(image.dimensions(), (16, 16));
+    let covered = image
+        .pixels()
+        .filter(|(_, _, pixel)| pixel.0[3] == 255)
+        .count();
+    assert_eq!(covered, 4 * 6 + 5 * 3);
+}
@@ -0,0 +1,40 @@
+//! Kerning in the rusttype provider and in laid out text
+#![cfg(all(feature = "image", feature = "rusttype"))]
+
+use elefont::rusttype_provider::SizedFont;
+use elefont::{FontCache, FontProvider};
+use image::{ImageBuffer, Rgba};
+use rusttype::Font;
+
+fn font() -> SizedFont<'static> {
+    let data = include_bytes!("../examples/DejaVuSans.ttf") as &[u8];
+    let font = Font::from_bytes(data).expect("Error constructing Font");
+
+    SizedFont::new(font, 32.0)
+}
+
+#[test]
+fn av_kerns_closer() {
+    let font = font();
+    let a = font.single_glyph('A');
+    let v = font.single_glyph('V');
+
+    assert!(font.kerning(a, v) < 0.0);
+    assert_eq!(font.kerning(v, v), 0.0);
+}
+
+#[test]
+fn layout_string_applies_kerning() {
+    let texture: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(256, 256);
+    let mut cache = FontCache::new(Box::new(font()), texture);
+    let a = cache.font().single_glyph('A');
+    let v = cache.font().single_glyph('V');
+    let kerning = cache.font().kerning(a, v);
+    let advance = cache.measure(a).advance_x;
+
+    let glyphs = cache.layout_string("AV").unwrap();
+    assert_eq!(glyphs.len(), 2);
+    assert_eq!(glyphs[0].x, 0.0);
+    assert!((glyphs[1].x - (advance + kerning)).abs() < 1e-4);
+    assert!(glyphs[1].x < advance);
+}
@@ -0,0 +1,77 @@
+//! Kerning from a provider that only kerns one pair of glyphs
+
+mod common;
+
+use common::block_font;
+use elefont::bitmap_provider::BitmapFontProvider;
+use elefont::{CacheError, FontCache, FontProvider, Glyph, Metrics, NullTexture, PixelType};
+
+/// Block glyphs where `a` followed by `b` is pulled 2 pixels closer
+struct Kerned(BitmapFontProvider);
+
+impl FontProvider for Kerned {
+    fn pixel_type(&self) -> PixelType {
+        self.0.pixel_type()
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        self.0.single_glyph(character)
+    }
+
+    fn notdef(&self) -> Glyph {
+        self.0.notdef()
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        self.0.glyphs(string, glyphs);
+    }
+
+    fn line_height(&self) -> f32 {
+        self.0.line_height()
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        self.0.metrics(glyph)
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        self.0.rasterize(glyph)
+    }
+
+    fn kerning(&self, left: Glyph, right: Glyph) -> f32 {
+        if (left, right) == (self.single_glyph('a'), self.single_glyph('b')) {
+            -2.0
+        } else {
+            0.0
+        }
+    }
+}
+
+fn cache() -> FontCache<NullTexture> {
+    let font = Kerned(block_font(&[('a', 4, 6), ('b', 5, 6)]));
+    FontCache::new(Box::new(font), NullTexture::new(32, 32))
+}
+
+#[test]
+fn layout_applies_only_the_kerned_pair() {
+    let mut cache = cache();
+
+    let glyphs = cache.layout_string("abba").unwrap();
+    let pens: Vec<_> = glyphs.iter().map(|glyph| glyph.x).collect();
+    assert_eq!(pens, [0.0, 2.0, 7.0, 12.0]);
+    let lefts: Vec<_> = glyphs
+        .iter()
+        .map(|glyph| glyph.bounds().unwrap().x)
+        .collect();
+    assert_eq!(lefts, [0, 2, 7, 12]);
+}
+
+#[test]
+fn measuring_applies_the_kerned_pair() {
+    let cache = cache();
+
+    let bounds = cache.measure_string_bounds("ab").unwrap();
+    assert_eq!((bounds.x, bounds.width), (0.0, 7.0));
+    let bounds = cache.measure_string_bounds("ba").unwrap();
+    assert_eq!((bounds.x, bounds.width), (0.0, 9.0));
+}
@@ -0,0 +1,49 @@
+//! The line height, ascent and descent of a real font
+#![cfg(feature = "rusttype")]
+
+use elefont::FontProvider;
+
+const DEJAVU_SANS: &[u8] = include_bytes!("../examples/DejaVuSans.ttf");
+
+/// DejaVu Sans has no line gap, so its lines are exactly as tall as its ascent and descent
+fn assert_consistent(font: &dyn FontProvider) {
+    let (ascent, descent, line_height) = (font.ascent(), font.descent(), font.line_height());
+    assert!(ascent > 0.0 && descent < 0.0, "{} {}", ascent, descent);
+    assert!(
+        (ascent + descent.abs() - line_height).abs() < 1e-3,
+        "{} + |{}| != {}",
+        ascent,
+        descent,
+        line_height
+    );
+    // DejaVu Sans reaches about a quarter of its ascent below the baseline
+    assert!(descent.abs() < ascent / 2.0);
+}
+
+#[test]
+fn rusttype() {
+    let font = elefont::rusttype_provider::SizedFont::from_bytes(DEJAVU_SANS, 32.0).unwrap();
+    assert_consistent(&font);
+}
+
+#[test]
+#[cfg(feature = "ab_glyph")]
+fn ab_glyph() {
+    let font = elefont::abglyph_provider::SizedFont::from_bytes(DEJAVU_SANS, 32.0).unwrap();
+    assert_consistent(&font);
+}
+
+#[test]
+#[cfg(all(feature = "rusttype", feature = "image"))]
+fn the_cache_passes_them_through() {
+    use image::{ImageBuffer, Rgba};
+
+    let font = elefont::rusttype_provider::SizedFont::from_bytes(DEJAVU_SANS, 32.0).unwrap();
+    let (ascent, descent, line_height) = (font.ascent(), font.descent(), font.line_height());
+    let texture: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(64, 64);
+    let cache = elefont::FontCache::new(Box::new(font), texture);
+
+    assert_eq!(cache.ascent(), ascent);
+    assert_eq!(cache.descent(), descent);
+    assert_eq!(cache.line_height(), line_height);
+}
@@ -0,0 +1,65 @@
+//! Providers that rasterize the wrong amount of data for a glyph's bounds
+
+mod common;
+
+use common::block_font;
+use elefont::bitmap_provider::BitmapFontProvider;
+use elefont::{CacheError, FontCache, FontProvider, Glyph, Metrics, NullTexture, PixelType};
+
+/// A provider that drops the last byte of every glyph it rasterizes
+struct Truncated(BitmapFontProvider);
+
+impl FontProvider for Truncated {
+    fn pixel_type(&self) -> PixelType {
+        self.0.pixel_type()
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        self.0.single_glyph(character)
+    }
+
+    fn notdef(&self) -> Glyph {
+        self.0.notdef()
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        self.0.glyphs(string, glyphs);
+    }
+
+    fn line_height(&self) -> f32 {
+        self.0.line_height()
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        self.0.metrics(glyph)
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        let mut data = self.0.rasterize(glyph)?;
+        data.pop();
+        Ok(data)
+    }
+}
+
+#[test]
+fn short_data_is_an_error() {
+    let font = Truncated(block_font(&[('a', 4, 5)]));
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(16, 16));
+    let a = cache.font().single_glyph('a');
+
+    assert!(matches!(
+        cache.render_glyph(a),
+        Err(CacheError::MalformedGlyphData {
+            expected: 20,
+            actual: 19
+        })
+    ));
+    assert!(!cache.contains(a));
+
+    let mut out = Vec::new();
+    assert!(matches!(
+        cache.rasterize_glyph(a, &mut out),
+        Err(CacheError::MalformedGlyphData { .. })
+    ));
+    assert!(out.is_empty());
+}
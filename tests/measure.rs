@@ -0,0 +1,45 @@
+//! Measuring the ink of a string with `FontCache::measure_string_bounds`
+
+mod common;
+
+use common::block_font;
+use elefont::bitmap_provider::{BitmapFontProvider, BitmapGlyph};
+use elefont::{Bounds, FontCache, NullTexture, PixelType, RectF};
+
+fn extents(bounds: RectF) -> (f32, f32, f32, f32) {
+    (bounds.x, bounds.y, bounds.width, bounds.height)
+}
+
+#[test]
+fn a_single_word() {
+    let font = block_font(&[('a', 4, 6), ('b', 5, 8)]);
+    let cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+
+    let bounds = cache.measure_string_bounds("abba").unwrap();
+    assert_eq!(extents(bounds), (0.0, -8.0, 18.0, 8.0));
+}
+
+#[test]
+fn descenders_reach_below_the_baseline() {
+    let glyph = |character, x, height, offset| BitmapGlyph {
+        character,
+        source: Bounds {
+            x,
+            y: 0,
+            width: 4,
+            height,
+        },
+        offset: (0, offset),
+        advance: 5.0,
+    };
+    // `x` sits on the baseline, and `p` hangs 3 pixels below it
+    let glyphs = [glyph('x', 0, 5, -5), glyph('p', 4, 8, -5)];
+    let font = BitmapFontProvider::new(PixelType::Alpha, 8, 8, vec![255; 64], 10.0, glyphs);
+    let cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+
+    let bounds = cache.measure_string_bounds("xx").unwrap();
+    assert_eq!(extents(bounds), (0.0, -5.0, 9.0, 5.0));
+    let bounds = cache.measure_string_bounds("xp").unwrap();
+    assert_eq!(extents(bounds), (0.0, -5.0, 9.0, 8.0));
+    assert_eq!(bounds.y + bounds.height, 3.0);
+}
@@ -0,0 +1,90 @@
+//! How often the cache asks its provider for metrics
+
+mod common;
+
+use common::block_font;
+use elefont::bitmap_provider::BitmapFontProvider;
+use elefont::metrics_cache_provider::MetricsCache;
+use elefont::{CacheError, FontCache, FontProvider, Glyph, Metrics, NullTexture, PixelType};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A provider that counts its calls to `metrics`
+struct Counting {
+    inner: BitmapFontProvider,
+    metrics_calls: Arc<AtomicUsize>,
+}
+
+impl FontProvider for Counting {
+    fn pixel_type(&self) -> PixelType {
+        self.inner.pixel_type()
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        self.inner.single_glyph(character)
+    }
+
+    fn notdef(&self) -> Glyph {
+        self.inner.notdef()
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        self.inner.glyphs(string, glyphs);
+    }
+
+    fn line_height(&self) -> f32 {
+        self.inner.line_height()
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        self.metrics_calls.fetch_add(1, Ordering::Relaxed);
+        self.inner.metrics(glyph)
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        self.inner.rasterize(glyph)
+    }
+}
+
+#[test]
+fn one_metrics_call_per_miss() {
+    let metrics_calls = Arc::new(AtomicUsize::new(0));
+    let font = Counting {
+        inner: block_font(&[('a', 4, 8), ('b', 6, 8)]),
+        metrics_calls: metrics_calls.clone(),
+    };
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+    let take_calls = || metrics_calls.swap(0, Ordering::Relaxed);
+
+    let a = cache.font().single_glyph('a');
+    let (metrics, _) = cache.render_glyph(a).unwrap();
+    assert_eq!(metrics.advance_x, 4.0);
+    assert_eq!(take_calls(), 1);
+
+    cache.cache_string("ab").unwrap();
+    assert_eq!(take_calls(), 1);
+
+    // Hits reuse the metrics stored with the glyph
+    cache.cache_string("abba").unwrap();
+    assert_eq!(take_calls(), 0);
+}
+
+#[test]
+fn metrics_cache_asks_once_per_glyph() {
+    let metrics_calls = Arc::new(AtomicUsize::new(0));
+    let font = MetricsCache::new(Counting {
+        inner: block_font(&[('a', 4, 8), ('b', 6, 8)]),
+        metrics_calls: metrics_calls.clone(),
+    });
+    let cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+    let take_calls = || metrics_calls.swap(0, Ordering::Relaxed);
+
+    let advance: f32 = cache.measure_string("abba").map(|m| m.advance_x).sum();
+    assert_eq!(advance, 20.0);
+    assert_eq!(take_calls(), 2);
+
+    // Measuring again is answered from the wrapper
+    cache.measure_string("abba").for_each(drop);
+    cache.measure_string_bounds("ab").unwrap();
+    assert_eq!(take_calls(), 0);
+}
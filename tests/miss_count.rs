@@ -0,0 +1,30 @@
+//! Counting the glyphs that had to be rasterized and uploaded, with `take_miss_count`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, NullTexture, Texture};
+
+fn render<T: Texture>(cache: &mut FontCache<T>, string: &str) {
+    for result in cache.render_string(string) {
+        result.unwrap();
+    }
+}
+
+#[test]
+fn only_new_glyphs_are_misses() {
+    let font = block_font(&[('a', 4, 5), ('b', 3, 6), ('c', 5, 4), ('d', 2, 2)]);
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(32, 32));
+    assert_eq!(cache.take_miss_count(), 0);
+
+    // The second `a` is already cached by the time it's rendered
+    render(&mut cache, "abca");
+    assert_eq!(cache.take_miss_count(), 3);
+    assert_eq!(cache.take_miss_count(), 0);
+
+    render(&mut cache, "abca");
+    assert_eq!(cache.take_miss_count(), 0);
+
+    render(&mut cache, "dab");
+    assert_eq!(cache.take_miss_count(), 1);
+}
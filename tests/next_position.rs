@@ -0,0 +1,79 @@
+//! Predicting where glyphs go with `FontCache::next_position`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, FontCacheBuilder, NullTexture, Packing, TextureOrigin};
+
+/// Glyphs of mixed sizes, enough of them to fill several rows of a 32x32 texture, and one that
+/// only fits sideways
+const BLOCKS: &[(char, u32, u32)] = &[
+    ('a', 5, 7),
+    ('b', 9, 3),
+    ('c', 4, 4),
+    ('d', 12, 6),
+    ('e', 3, 9),
+    ('f', 7, 5),
+    ('g', 10, 2),
+    ('h', 6, 6),
+    ('i', 2, 11),
+    ('j', 8, 4),
+    ('k', 30, 3),
+    ('l', 3, 30),
+];
+
+fn cache(packing: Packing, origin: TextureOrigin) -> FontCache<NullTexture> {
+    FontCacheBuilder::new(Box::new(block_font(BLOCKS)), NullTexture::new(32, 32))
+        .packing(packing)
+        .allow_rotation(true)
+        .texture_origin(origin)
+        .build()
+}
+
+/// Render a character, checking that it lands where `next_position` said it would, and return
+/// whether it was placed
+fn assert_predicted(cache: &mut FontCache<NullTexture>, character: char) -> bool {
+    let glyph = cache.font().single_glyph(character);
+    let needed = cache.measure(glyph).bounds.unwrap();
+    let predicted = cache.next_position(needed);
+    match cache.render_glyph(glyph) {
+        Ok((_, texture_glyph)) => {
+            let predicted = predicted.expect("the glyph was placed without a prediction");
+            let bounds = texture_glyph.bounds;
+            assert_eq!(
+                (predicted.x, predicted.y, predicted.width, predicted.height),
+                (bounds.x, bounds.y, bounds.width, bounds.height),
+                "{}",
+                character
+            );
+            true
+        }
+        Err(_) => {
+            assert!(predicted.is_none(), "{}", character);
+            false
+        }
+    }
+}
+
+#[test]
+fn predictions_match_renders() {
+    for packing in [
+        Packing::Shelf,
+        Packing::ShelfBucketed { bucket: 4 },
+        Packing::Skyline,
+        Packing::MaxRects,
+    ] {
+        for origin in [TextureOrigin::TopLeft, TextureOrigin::BottomLeft] {
+            let mut cache = cache(packing, origin);
+            let placed = BLOCKS
+                .iter()
+                .filter(|(character, _, _)| assert_predicted(&mut cache, *character))
+                .count();
+            assert!(placed >= 10, "{:?} only placed {}", packing, placed);
+            // Removing a glyph leaves a hole that the same glyph fits back into
+            let d = cache.font().single_glyph('d');
+            cache.remove_glyph(d);
+            assert!(assert_predicted(&mut cache, 'd'));
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Normalizing strings before they're converted to glyphs, with `FontCache::set_normalization`
+#![cfg(feature = "unicode-normalization")]
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, NormalizationForm, NullTexture};
+
+const COMPOSED: &str = "\u{e9}";
+const DECOMPOSED: &str = "e\u{301}";
+
+fn cache(normalization: Option<NormalizationForm>) -> FontCache<NullTexture> {
+    let font = block_font(&[('e', 4, 4), ('\u{301}', 2, 2), ('\u{e9}', 4, 6)]);
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+    cache.set_normalization(normalization);
+    cache
+}
+
+#[test]
+fn nfc_combines_accents() {
+    let cache = cache(Some(NormalizationForm::Nfc));
+
+    assert_eq!(cache.measure_string(DECOMPOSED).count(), 1);
+    assert_eq!(cache.measure_string(COMPOSED).count(), 1);
+    assert_eq!(cache.normalize_for_render(DECOMPOSED), COMPOSED);
+}
+
+#[test]
+fn nfd_splits_accents() {
+    let cache = cache(Some(NormalizationForm::Nfd));
+
+    assert_eq!(cache.measure_string(COMPOSED).count(), 2);
+    assert_eq!(cache.measure_string(DECOMPOSED).count(), 2);
+}
+
+#[test]
+fn no_normalization_keeps_the_string() {
+    let cache = cache(None);
+
+    assert_eq!(cache.measure_string(COMPOSED).count(), 1);
+    assert_eq!(cache.measure_string(DECOMPOSED).count(), 2);
+}
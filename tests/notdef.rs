@@ -0,0 +1,46 @@
+//! Characters the font doesn't have, under each `MissingGlyphPolicy`
+
+mod common;
+
+use common::block_font;
+use elefont::{CacheError, FontCache, FontCacheBuilder, MissingGlyphPolicy, NullTexture};
+
+fn cache(policy: MissingGlyphPolicy) -> FontCache<NullTexture> {
+    FontCacheBuilder::new(
+        Box::new(block_font(&[('a', 4, 6)])),
+        NullTexture::new(32, 32),
+    )
+    .missing_glyph_policy(policy)
+    .build()
+}
+
+#[test]
+fn missing_characters_are_the_notdef_glyph() {
+    for policy in [
+        MissingGlyphPolicy::Render,
+        MissingGlyphPolicy::Skip,
+        MissingGlyphPolicy::Tofu,
+    ] {
+        let mut cache = cache(policy);
+        let results: Vec<_> = cache.render_string("aé").map(Result::unwrap).collect();
+        let glyphs: Vec<_> = results.iter().map(|(_, glyph)| glyph.glyph).collect();
+        assert!(!cache.is_notdef(glyphs[0]), "{:?}", policy);
+        assert!(cache.is_notdef(glyphs[1]), "{:?}", policy);
+        assert!(glyphs[1].is_notdef(), "{:?}", policy);
+
+        let drawn = results[1].0.bounds.is_some();
+        assert_eq!(drawn, policy == MissingGlyphPolicy::Tofu, "{:?}", policy);
+    }
+}
+
+#[test]
+fn the_error_names_the_notdef_glyph() {
+    let mut cache = cache(MissingGlyphPolicy::Error);
+    let results: Vec<_> = cache.render_string("aé").collect();
+
+    assert!(results[0].is_ok());
+    match results[1] {
+        Err(CacheError::MissingGlyph(glyph)) => assert!(cache.is_notdef(glyph)),
+        ref other => panic!("expected a missing glyph error, got {:?}", other),
+    }
+}
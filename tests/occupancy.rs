@@ -0,0 +1,27 @@
+//! Measuring how much of the texture the packer has used, with `occupancy` and `wasted_area`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCacheBuilder, NullTexture, Packing};
+
+#[test]
+fn known_placements() {
+    let font = block_font(&[('a', 10, 10), ('b', 10, 4), ('c', 20, 6)]);
+    let mut cache = FontCacheBuilder::new(Box::new(font), NullTexture::new(32, 32))
+        .padding(0)
+        .packing(Packing::Shelf)
+        .build();
+    assert_eq!(cache.occupancy(), 0.0);
+    assert_eq!(cache.wasted_area(), 0);
+
+    // `a` and `b` share a row 10 pixels tall, leaving 6 pixels above `b` empty
+    cache.cache_string("ab").unwrap();
+    assert_eq!(cache.occupancy(), 140.0 / 1024.0);
+    assert_eq!(cache.wasted_area(), 60);
+
+    // `c` doesn't fit after them, so the rest of the first row is passed over
+    cache.cache_string("c").unwrap();
+    assert_eq!(cache.occupancy(), 260.0 / 1024.0);
+    assert_eq!(cache.wasted_area(), 10 * 32 - 140);
+}
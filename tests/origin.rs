@@ -0,0 +1,37 @@
+//! Reporting glyph positions from either corner of the texture with `TextureOrigin`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, FontCacheBuilder, NullTexture, TextureOrigin};
+
+fn cache(origin: TextureOrigin) -> FontCache<NullTexture> {
+    FontCacheBuilder::new(
+        Box::new(block_font(&[('a', 4, 6), ('b', 8, 8), ('c', 8, 8)])),
+        NullTexture::new(16, 32),
+    )
+    .padding(0)
+    .texture_origin(origin)
+    .build()
+}
+
+#[test]
+fn bottom_left_measures_up_from_the_bottom_edge() {
+    let mut top_left = cache(TextureOrigin::TopLeft);
+    let mut bottom_left = cache(TextureOrigin::BottomLeft);
+
+    for character in ['a', 'b', 'c'] {
+        let glyph = top_left.font().single_glyph(character);
+        let (_, top) = top_left.render_glyph(glyph).unwrap();
+        let (_, bottom) = bottom_left.render_glyph(glyph).unwrap();
+        assert_eq!(bottom.bounds.x, top.bounds.x);
+        assert_eq!(
+            bottom.bounds.y,
+            32 - top.bounds.y - top.bounds.height as i32
+        );
+        assert_eq!(bottom.bounds.height, top.bounds.height);
+    }
+    // The third glyph doesn't fit beside the first two, so it starts a second row
+    let c = top_left.font().single_glyph('c');
+    assert!(top_left.render_glyph(c).unwrap().1.bounds.y > 0);
+}
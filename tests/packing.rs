@@ -0,0 +1,43 @@
+//! Where each `Packing` strategy places glyphs in the texture
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, FontCacheBuilder, NullTexture, Packing};
+
+fn cache(blocks: &[(char, u32, u32)], packing: Packing) -> FontCache<NullTexture> {
+    FontCacheBuilder::new(Box::new(block_font(blocks)), NullTexture::new(16, 16))
+        .padding(0)
+        .packing(packing)
+        .build()
+}
+
+/// The top-left corner of each glyph of a string, in order
+fn positions(cache: &mut FontCache<NullTexture>, string: &str) -> Vec<(i32, i32)> {
+    cache
+        .render_string(string)
+        .map(|result| {
+            let (_, glyph) = result.unwrap();
+            (glyph.bounds.x, glyph.bounds.y)
+        })
+        .collect()
+}
+
+#[test]
+fn skyline_places_each_glyph_as_low_as_possible() {
+    let blocks = [
+        ('a', 6, 10),
+        ('b', 10, 4),
+        ('c', 10, 4),
+        ('d', 4, 6),
+        ('e', 6, 6),
+    ];
+    let mut cache = cache(&blocks, Packing::Skyline);
+
+    // `d` sits on the lower of the two spans it could reach, and `e` doesn't fit on top of it,
+    // so it takes the rest of that span
+    assert_eq!(
+        positions(&mut cache, "abcde"),
+        [(0, 0), (6, 0), (6, 4), (6, 8), (10, 8)]
+    );
+}
@@ -0,0 +1,26 @@
+//! The gap the packer leaves between glyphs, set with `FontCacheBuilder::padding`
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCacheBuilder, NullTexture, Packing};
+
+#[test]
+fn glyphs_are_four_pixels_apart() {
+    let font = block_font(&[('a', 6, 5), ('b', 6, 5), ('c', 6, 5)]);
+    let mut cache = FontCacheBuilder::new(Box::new(font), NullTexture::new(24, 24))
+        .padding(4)
+        .packing(Packing::Shelf)
+        .build();
+
+    let bounds: Vec<_> = cache
+        .render_string("abc")
+        .map(|result| result.unwrap().1.bounds)
+        .collect();
+    let (a, b, c) = (bounds[0], bounds[1], bounds[2]);
+    // `a` and `b` share a row, and `c` doesn't fit after them
+    assert_eq!(a.y, b.y);
+    assert_eq!(b.x - (a.x + a.width as i32), 4);
+    assert_eq!(c.x, a.x);
+    assert_eq!(c.y - (a.y + a.height as i32), 4);
+}
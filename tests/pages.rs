@@ -0,0 +1,30 @@
+//! Spilling glyphs onto more texture pages once the first one is full
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, NullTexture};
+
+#[test]
+fn spills_onto_a_second_page() {
+    let font = block_font(&[('a', 16, 10), ('b', 16, 10), ('c', 4, 4)]);
+    let mut cache = FontCache::with_pages(Box::new(font), || NullTexture::new(16, 16));
+    cache.set_padding(0);
+    assert_eq!(cache.page_count(), 1);
+
+    let glyphs: Vec<_> = cache
+        .render_string("abc")
+        .map(|result| result.unwrap().1)
+        .collect();
+    assert_eq!(cache.page_count(), 2);
+    let pages: Vec<_> = glyphs.iter().map(|glyph| glyph.page).collect();
+    // `c` still fits under `a` on the first page
+    assert_eq!(pages, [0, 1, 0]);
+    assert_eq!((glyphs[1].bounds.x, glyphs[1].bounds.y), (0, 0));
+    assert!(cache.page(1).is_some());
+    assert!(cache.page(2).is_none());
+
+    // Hits come back with the page they were stored on
+    let b = cache.font().single_glyph('b');
+    assert_eq!(cache.render_glyph(b).unwrap().1.page, 1);
+}
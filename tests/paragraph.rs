@@ -0,0 +1,59 @@
+//! Wrapping text with `FontCache::layout_paragraph`
+#![cfg(feature = "layout")]
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, GlyphInstance, NullTexture};
+
+fn cache() -> FontCache<NullTexture> {
+    // Every line is 6 pixels tall, the height of the tallest block
+    let font = block_font(&[('a', 4, 6), (' ', 2, 0)]);
+    FontCache::new(Box::new(font), NullTexture::new(64, 64))
+}
+
+fn corners(instances: &[GlyphInstance]) -> Vec<(f32, f32)> {
+    let top = instances[0].rect.y;
+    instances
+        .iter()
+        .map(|instance| (instance.rect.x, instance.rect.y - top))
+        .collect()
+}
+
+#[test]
+fn wraps_words_past_the_width() {
+    let mut cache = cache();
+
+    // "aa aa" is 18 pixels wide, so the third word doesn't fit
+    let instances = cache.layout_paragraph("aa aa aa", 20.0).unwrap();
+    assert_eq!(
+        corners(&instances),
+        [
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (10.0, 0.0),
+            (14.0, 0.0),
+            (0.0, 6.0),
+            (4.0, 6.0)
+        ]
+    );
+}
+
+#[test]
+fn long_words_overflow_their_own_line() {
+    let mut cache = cache();
+
+    let instances = cache.layout_paragraph("a aaa", 6.0).unwrap();
+    assert_eq!(
+        corners(&instances),
+        [(0.0, 0.0), (0.0, 6.0), (4.0, 6.0), (8.0, 6.0)]
+    );
+}
+
+#[test]
+fn breaks_at_newlines() {
+    let mut cache = cache();
+
+    let instances = cache.layout_paragraph("a a\n\r\na", 100.0).unwrap();
+    assert_eq!(corners(&instances), [(0.0, 0.0), (6.0, 0.0), (0.0, 12.0)]);
+}
@@ -0,0 +1,98 @@
+//! Caching strings with `FontCache::cache_string_parallel`
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use common::block_font;
+use elefont::bitmap_provider::BitmapFontProvider;
+use elefont::{
+    CacheError, EvictionPolicy, FontCache, FontCacheBuilder, FontProvider, Glyph, Metrics,
+    NullTexture, PixelType,
+};
+
+/// Room for two of the three glyphs
+fn lru_cache() -> FontCache<NullTexture> {
+    FontCacheBuilder::new(
+        Box::new(block_font(&[('a', 4, 4), ('b', 4, 4), ('c', 4, 4)])),
+        NullTexture::new(8, 4),
+    )
+    .padding(0)
+    .eviction(EvictionPolicy::Lru)
+    .build()
+}
+
+#[test]
+fn evicts_like_cache_string() {
+    let mut serial = lru_cache();
+    let mut parallel = lru_cache();
+    for cache in [&mut serial, &mut parallel] {
+        cache.cache_string("a").unwrap();
+        cache.cache_string("b").unwrap();
+    }
+
+    // `a` is the oldest glyph, but it's part of the string, so `b` makes room for `c`
+    serial.cache_string("ac").unwrap();
+    parallel.cache_string_parallel("ac").unwrap();
+    for c in ['a', 'b', 'c'] {
+        let glyph = serial.font().single_glyph(c);
+        let bounds = |cache: &FontCache<NullTexture>| {
+            let bounds = cache.get(glyph)?.bounds;
+            Some((bounds.x, bounds.y, bounds.width, bounds.height))
+        };
+        assert_eq!(bounds(&parallel), bounds(&serial));
+    }
+    assert!(!serial.contains(serial.font().single_glyph('b')));
+}
+
+/// A font that can't rasterize `b`
+struct FailsOnB(BitmapFontProvider);
+
+impl FontProvider for FailsOnB {
+    fn pixel_type(&self) -> PixelType {
+        self.0.pixel_type()
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        self.0.single_glyph(character)
+    }
+
+    fn notdef(&self) -> Glyph {
+        self.0.notdef()
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        self.0.glyphs(string, glyphs);
+    }
+
+    fn line_height(&self) -> f32 {
+        self.0.line_height()
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        self.0.metrics(glyph)
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        if glyph == self.0.single_glyph('b') {
+            return Err(CacheError::NonRenderableGlyph(glyph));
+        }
+        self.0.rasterize(glyph)
+    }
+}
+
+#[test]
+fn returns_the_first_error() {
+    let font = FailsOnB(block_font(&[('a', 4, 4), ('b', 4, 4), ('c', 4, 4)]));
+    let mut cache = FontCache::new(Box::new(font), NullTexture::new(64, 64));
+    let glyph = |cache: &FontCache<NullTexture>, c| cache.font().single_glyph(c);
+
+    let b = glyph(&cache, 'b');
+    assert!(matches!(
+        cache.cache_string_parallel("abc"),
+        Err(CacheError::NonRenderableGlyph(failed)) if failed == b
+    ));
+    // The glyphs before the failure are cached, and the ones after it aren't
+    assert!(cache.contains(glyph(&cache, 'a')));
+    assert!(!cache.contains(b));
+    assert!(!cache.contains(glyph(&cache, 'c')));
+}
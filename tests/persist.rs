@@ -0,0 +1,49 @@
+//! Saving a cache and loading it back with `FontCache::save` and `FontCache::load`
+#![cfg(all(feature = "serde", feature = "image"))]
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, FontCacheBuilder, TextureOrigin};
+use image::{ImageBuffer, Rgba};
+
+type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+fn placements(cache: &mut FontCache<Image>, string: &str) -> Vec<(u32, i32, i32, u32, u32, bool)> {
+    cache
+        .render_string(string)
+        .map(|result| {
+            let (_, glyph) = result.unwrap();
+            let bounds = glyph.bounds;
+            (
+                glyph.page,
+                bounds.x,
+                bounds.y,
+                bounds.width,
+                bounds.height,
+                glyph.rotated,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn round_trip_keeps_the_placements() {
+    let blocks = [('a', 4, 12), ('b', 6, 3)];
+    let mut cache = FontCacheBuilder::new(Box::new(block_font(&blocks)), Image::new(16, 8))
+        .padding(1)
+        .allow_rotation(true)
+        .texture_origin(TextureOrigin::BottomLeft)
+        .build();
+    let before = placements(&mut cache, "ab");
+    // `a` only fits sideways
+    assert!(before[0].5);
+    assert_eq!((before[0].3, before[0].4), (12, 4));
+    let bytes = cache.save().unwrap();
+
+    let mut loaded =
+        FontCache::load(Box::new(block_font(&blocks)), Image::new(16, 8), &bytes).unwrap();
+    assert_eq!(**loaded.texture(), **cache.texture());
+    assert_eq!(placements(&mut loaded, "ab"), before);
+    assert_eq!(loaded.take_miss_count(), 0);
+}
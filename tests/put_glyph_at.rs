@@ -0,0 +1,64 @@
+//! Placing glyphs with `FontCache::put_glyph_at`
+
+mod common;
+
+use common::block_font;
+use elefont::{Bounds, CacheError, FontCache, NullTexture};
+
+fn area(x: i32, y: i32, width: u32, height: u32) -> Bounds {
+    Bounds {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn puts_the_glyph_in_the_area() {
+    let mut cache = FontCache::new(
+        Box::new(block_font(&[('a', 4, 6)])),
+        NullTexture::new(16, 16),
+    );
+    let a = cache.font().single_glyph('a');
+
+    let placed = cache.put_glyph_at(a, area(10, 8, 4, 6)).unwrap();
+    assert_eq!((placed.bounds.x, placed.bounds.y), (10, 8));
+    let (_, rendered) = cache.render_glyph(a).unwrap();
+    assert_eq!((rendered.bounds.x, rendered.bounds.y), (10, 8));
+}
+
+#[test]
+fn rejects_areas_of_the_wrong_size() {
+    let mut cache = FontCache::new(
+        Box::new(block_font(&[('a', 4, 6)])),
+        NullTexture::new(16, 16),
+    );
+    let a = cache.font().single_glyph('a');
+
+    let result = cache.put_glyph_at(a, area(0, 0, 5, 6));
+    assert!(matches!(
+        result,
+        Err(CacheError::WrongAreaSize {
+            size: (4, 6),
+            area: (5, 6),
+            ..
+        })
+    ));
+    assert!(!cache.contains(a));
+}
+
+#[test]
+fn rejects_areas_outside_of_the_texture() {
+    let mut cache = FontCache::new(
+        Box::new(block_font(&[('a', 4, 6)])),
+        NullTexture::new(16, 16),
+    );
+    let a = cache.font().single_glyph('a');
+
+    for (x, y) in [(13, 0), (0, 11), (-1, 0), (i32::MAX, 0), (0, i32::MAX)] {
+        let result = cache.put_glyph_at(a, area(x, y, 4, 6));
+        assert!(matches!(result, Err(CacheError::TextureTooSmall { .. })));
+    }
+    assert!(!cache.contains(a));
+}
@@ -0,0 +1,121 @@
+//! Rasterizing with `FontProvider::rasterize_async`, through the provider wrappers and into the
+//! cache
+#![cfg(feature = "async")]
+
+mod common;
+
+use common::block_font;
+use elefont::bitmap_provider::BitmapFontProvider;
+use elefont::fallback_provider::FallbackProvider;
+use elefont::metrics_cache_provider::MetricsCache;
+use elefont::tint_provider::TintProvider;
+use elefont::{
+    CacheError, EvictionPolicy, FontCache, FontCacheBuilder, FontProvider, Glyph, Metrics,
+    NullTexture, PixelType, RasterizeFuture,
+};
+use std::future::Future;
+use std::task::{Context, Poll, Waker};
+
+/// A provider that can only rasterize asynchronously
+struct AsyncOnly(BitmapFontProvider);
+
+impl FontProvider for AsyncOnly {
+    fn pixel_type(&self) -> PixelType {
+        self.0.pixel_type()
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        self.0.single_glyph(character)
+    }
+
+    fn notdef(&self) -> Glyph {
+        self.0.notdef()
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        self.0.glyphs(string, glyphs);
+    }
+
+    fn line_height(&self) -> f32 {
+        self.0.line_height()
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        self.0.metrics(glyph)
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        Err(CacheError::NonRenderableGlyph(glyph))
+    }
+
+    fn rasterize_async(&self, glyph: Glyph) -> RasterizeFuture {
+        Box::pin(std::future::ready(self.0.rasterize(glyph)))
+    }
+}
+
+fn font() -> AsyncOnly {
+    AsyncOnly(block_font(&[('a', 2, 2)]))
+}
+
+/// Poll a future that's expected to be ready
+fn now<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let mut context = Context::from_waker(Waker::noop());
+    match future.as_mut().poll(&mut context) {
+        Poll::Ready(result) => result,
+        Poll::Pending => panic!("The future isn't ready"),
+    }
+}
+
+#[test]
+fn pass_through_wrappers_call_the_inner_provider() {
+    let cache = MetricsCache::new(font());
+    let a = cache.single_glyph('a');
+    assert_eq!(now(cache.rasterize_async(a)).unwrap(), [255; 4]);
+
+    let fallback = FallbackProvider::new(vec![Box::new(font())]);
+    let a = fallback.single_glyph('a');
+    assert_eq!(now(fallback.rasterize_async(a)).unwrap(), [255; 4]);
+}
+
+#[test]
+fn transforming_wrappers_transform_the_inner_future() {
+    let tint = TintProvider::new(font(), [10, 20, 30, 255]);
+    let a = tint.single_glyph('a');
+    let data = now(tint.rasterize_async(a)).unwrap();
+    assert_eq!(data, [10, 20, 30, 255].repeat(4));
+}
+
+#[test]
+fn inserting_evicts_like_cache_string() {
+    // Room for two of the three glyphs
+    let lru_cache = || {
+        FontCacheBuilder::new(
+            Box::new(block_font(&[('a', 4, 4), ('b', 4, 4), ('c', 4, 4)])),
+            NullTexture::new(8, 4),
+        )
+        .padding(0)
+        .eviction(EvictionPolicy::Lru)
+        .build()
+    };
+    let mut serial: FontCache<NullTexture> = lru_cache();
+    let mut inserted = lru_cache();
+    for cache in [&mut serial, &mut inserted] {
+        cache.cache_string("a").unwrap();
+        cache.cache_string("b").unwrap();
+    }
+
+    // `a` is the oldest glyph, but it's part of the string, so `b` makes room for `c`
+    serial.cache_string("ac").unwrap();
+    let rasterized = now(inserted.rasterize_string_async("ac"));
+    inserted.insert_rasterized(rasterized).unwrap();
+    for c in ['a', 'b', 'c'] {
+        let glyph = serial.font().single_glyph(c);
+        let bounds = |cache: &FontCache<NullTexture>| {
+            let bounds = cache.get(glyph)?.bounds;
+            Some((bounds.x, bounds.y, bounds.width, bounds.height))
+        };
+        assert_eq!(bounds(&inserted), bounds(&serial));
+    }
+    assert!(!serial.contains(serial.font().single_glyph('b')));
+}
@@ -0,0 +1,55 @@
+//! Rasterizing a glyph into a caller's buffer with `rasterize_glyph`, without packing it
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, NullTexture};
+
+#[test]
+fn output_covers_the_bounds() {
+    let font = block_font(&[('a', 4, 5), ('b', 7, 3), (' ', 0, 0)]);
+    // Too small to cache anything, which `rasterize_glyph` doesn't need
+    let cache = FontCache::new(Box::new(font), NullTexture::new(1, 1));
+
+    let mut out = vec![1, 2, 3];
+    for character in ['a', 'b'] {
+        let glyph = cache.font().single_glyph(character);
+        let bounds = cache
+            .rasterize_glyph(glyph, &mut out)
+            .unwrap()
+            .bounds
+            .unwrap();
+        assert_eq!(out.len(), (bounds.width * bounds.height) as usize);
+        assert!(out.iter().all(|&value| value == 255));
+    }
+    assert!(cache.is_empty());
+
+    let space = cache.font().single_glyph(' ');
+    assert!(cache
+        .rasterize_glyph(space, &mut out)
+        .unwrap()
+        .bounds
+        .is_none());
+    assert!(out.is_empty());
+}
+
+#[cfg(feature = "rusttype")]
+#[test]
+fn real_glyphs_cover_the_bounds() {
+    use elefont::rusttype_provider::SizedFont;
+
+    let data = include_bytes!("../examples/DejaVuSans.ttf");
+    let font = SizedFont::from_bytes(&data[..], 24.0).unwrap();
+    let cache = FontCache::new(Box::new(font), NullTexture::new(1, 1));
+
+    let mut out = Vec::new();
+    for character in "Ag@".chars() {
+        let glyph = cache.font().single_glyph(character);
+        let bounds = cache
+            .rasterize_glyph(glyph, &mut out)
+            .unwrap()
+            .bounds
+            .unwrap();
+        assert_eq!(out.len(), (bounds.width * bounds.height) as usize);
+    }
+}
@@ -0,0 +1,49 @@
+//! Turning glyphs sideways to fit them, with `FontCacheBuilder::allow_rotation`
+
+mod common;
+
+use common::block_font;
+use elefont::{CacheError, FontCache, FontCacheBuilder, NullTexture};
+
+fn cache(allow_rotation: bool) -> FontCache<NullTexture> {
+    // `a` is taller than the texture, but fits lying down
+    FontCacheBuilder::new(
+        Box::new(block_font(&[('a', 4, 12)])),
+        NullTexture::new(16, 8),
+    )
+    .padding(0)
+    .allow_rotation(allow_rotation)
+    .build()
+}
+
+#[test]
+fn tall_glyphs_fit_sideways() {
+    let mut cache = cache(true);
+    let a = cache.font().single_glyph('a');
+
+    let (metrics, texture_glyph) = cache.render_glyph(a).unwrap();
+    assert!(texture_glyph.rotated);
+    let bounds = texture_glyph.bounds;
+    assert_eq!(
+        (bounds.x, bounds.y, bounds.width, bounds.height),
+        (0, 0, 12, 4)
+    );
+    // The metrics still describe the upright glyph
+    let upright = metrics.bounds.unwrap();
+    assert_eq!((upright.width, upright.height), (4, 12));
+}
+
+#[test]
+fn tall_glyphs_dont_fit_upright() {
+    let mut cache = cache(false);
+    let a = cache.font().single_glyph('a');
+
+    assert!(matches!(
+        cache.render_glyph(a),
+        Err(CacheError::TextureTooSmall {
+            needed: (4, 12),
+            available: (16, 8),
+            ..
+        })
+    ));
+}
@@ -0,0 +1,54 @@
+//! Listing the packer's rows with `rows`, for drawing them in a debug overlay
+
+mod common;
+
+use common::block_font;
+use elefont::{FontCache, FontCacheBuilder, NullTexture, Packing, RowInfo};
+
+fn cache(packing: Packing) -> FontCache<NullTexture> {
+    let font = block_font(&[('a', 10, 10), ('b', 10, 4), ('c', 20, 6), ('d', 5, 3)]);
+    let mut cache = FontCacheBuilder::new(Box::new(font), NullTexture::new(32, 32))
+        .padding(1)
+        .packing(packing)
+        .build();
+    cache.cache_string("abcd").unwrap();
+
+    cache
+}
+
+/// The page, y, height and used width of each row
+fn rows(cache: &FontCache<NullTexture>) -> Vec<(u32, i32, u32, u32)> {
+    cache
+        .rows()
+        .map(|row: RowInfo| (row.page, row.y, row.height, row.used_width))
+        .collect()
+}
+
+#[test]
+fn shelf_rows() {
+    let cache = cache(Packing::Shelf);
+    // `c` doesn't fit after `a` and `b`, so it starts a row below the tallest of them
+    assert_eq!(rows(&cache), [(0, 0, 10, 21), (0, 11, 6, 26)]);
+    assert_eq!(cache.stats().rows, 2);
+}
+
+#[test]
+fn bucketed_rows() {
+    let cache = cache(Packing::ShelfBucketed { bucket: 4 });
+    // Padded heights are rounded up to a multiple of 4, so `b` and `c` share a row that's
+    // taller than either of them, and `d` gets a row of its own
+    assert_eq!(
+        rows(&cache),
+        [(0, 0, 11, 10), (0, 12, 7, 31), (0, 20, 3, 5)]
+    );
+    assert_eq!(cache.stats().rows, 3);
+}
+
+#[test]
+fn other_strategies_have_no_rows() {
+    for packing in [Packing::Skyline, Packing::MaxRects] {
+        let cache = cache(packing);
+        assert_eq!(cache.rows().count(), 0);
+        assert_eq!(cache.stats().rows, 0);
+    }
+}
@@ -0,0 +1,62 @@
+//! Rasterizing in physical pixels while laying text out in logical pixels
+#![cfg(feature = "rusttype")]
+
+use elefont::rusttype_provider::SizedFont;
+use elefont::{FontCache, FontProvider, NullTexture};
+
+fn font(scale_factor: f32) -> SizedFont<'static> {
+    let data = include_bytes!("../examples/DejaVuSans.ttf") as &[u8];
+
+    SizedFont::from_bytes(data, 24.0)
+        .unwrap()
+        .with_scale_factor(scale_factor)
+}
+
+#[test]
+fn doubles_the_bitmap_and_keeps_the_advance() {
+    let (logical, physical) = (font(1.0), font(2.0));
+    assert_eq!(physical.scale_factor(), 2.0);
+    let g = logical.single_glyph('g');
+
+    let small = logical.metrics(g);
+    let large = physical.metrics(g);
+    assert_eq!(large.advance_x, small.advance_x);
+    assert_eq!(large.bearing_x, small.bearing_x);
+    let (small_bounds, large_bounds) = (small.bounds.unwrap(), large.bounds.unwrap());
+    // Rounding out to whole pixels can add a pixel on each side
+    let close = |large: u32, small: u32| (large as i32 - small as i32 * 2).abs() <= 2;
+    assert!(close(large_bounds.width, small_bounds.width));
+    assert!(close(large_bounds.height, small_bounds.height));
+    let data = physical.rasterize(g).unwrap();
+    assert_eq!(
+        data.len(),
+        (large_bounds.width * large_bounds.height) as usize
+    );
+}
+
+#[test]
+fn layout_scales_the_pen_to_physical_pixels() {
+    let line = |scale_factor| {
+        let mut cache = FontCache::new(Box::new(font(scale_factor)), NullTexture::new(512, 512));
+        cache.layout_line("Hello", (10.0, 0.0)).unwrap()
+    };
+    let (logical, physical) = (line(1.0), line(2.0));
+    assert_eq!(logical.len(), physical.len());
+    for (small, large) in logical.iter().zip(physical.iter()) {
+        assert!((large.rect.x - small.rect.x * 2.0).abs() <= 3.0);
+        assert!((large.rect.y - small.rect.y * 2.0).abs() <= 3.0);
+    }
+}
+
+#[test]
+fn measuring_stays_in_logical_pixels() {
+    let measure = |scale_factor| {
+        let cache = FontCache::new(Box::new(font(scale_factor)), NullTexture::new(512, 512));
+        cache.measure_string_bounds("Hello, world").unwrap()
+    };
+    let (logical, physical) = (measure(1.0), measure(2.0));
+    assert!((physical.x - logical.x).abs() <= 1.0);
+    assert!((physical.y - logical.y).abs() <= 1.0);
+    assert!((physical.width - logical.width).abs() <= 1.0);
+    assert!((physical.height - logical.height).abs() <= 1.0);
+}
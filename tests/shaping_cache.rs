@@ -0,0 +1,98 @@
+//! Remembering the glyphs of recently rendered strings, with `FontCache::set_shaping_cache`
+
+mod common;
+
+use common::block_font;
+use elefont::bitmap_provider::BitmapFontProvider;
+use elefont::{CacheError, FontCache, FontProvider, Glyph, Metrics, NullTexture, PixelType};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A provider that counts how many strings it converts into glyphs
+struct Counting {
+    inner: BitmapFontProvider,
+    glyphs_calls: Arc<AtomicUsize>,
+}
+
+impl FontProvider for Counting {
+    fn pixel_type(&self) -> PixelType {
+        self.inner.pixel_type()
+    }
+
+    fn single_glyph(&self, character: char) -> Glyph {
+        self.inner.single_glyph(character)
+    }
+
+    fn notdef(&self) -> Glyph {
+        self.inner.notdef()
+    }
+
+    fn glyphs(&self, string: &str, glyphs: &mut Vec<Glyph>) {
+        self.glyphs_calls.fetch_add(1, Ordering::Relaxed);
+        self.inner.glyphs(string, glyphs);
+    }
+
+    fn line_height(&self) -> f32 {
+        self.inner.line_height()
+    }
+
+    fn metrics(&self, glyph: Glyph) -> Metrics {
+        self.inner.metrics(glyph)
+    }
+
+    fn rasterize(&self, glyph: Glyph) -> Result<Vec<u8>, CacheError> {
+        self.inner.rasterize(glyph)
+    }
+}
+
+fn cache() -> (FontCache<NullTexture>, Arc<AtomicUsize>) {
+    let glyphs_calls = Arc::new(AtomicUsize::new(0));
+    let font = Counting {
+        inner: block_font(&[('a', 4, 4), ('b', 4, 4), ('c', 4, 4)]),
+        glyphs_calls: glyphs_calls.clone(),
+    };
+
+    (
+        FontCache::new(Box::new(font), NullTexture::new(64, 64)),
+        glyphs_calls,
+    )
+}
+
+fn render(cache: &mut FontCache<NullTexture>, string: &str) -> Vec<Glyph> {
+    cache
+        .render_string(string)
+        .map(|result| result.unwrap().1.glyph)
+        .collect()
+}
+
+#[test]
+fn identical_renders_skip_the_provider() {
+    let (mut cache, glyphs_calls) = cache();
+    let take_calls = || glyphs_calls.swap(0, Ordering::Relaxed);
+    cache.set_shaping_cache(2);
+
+    let first = render(&mut cache, "ab");
+    assert_eq!(take_calls(), 1);
+    assert_eq!(render(&mut cache, "ab"), first);
+    assert_eq!(take_calls(), 0);
+
+    render(&mut cache, "ba");
+    render(&mut cache, "ab");
+    assert_eq!(take_calls(), 1);
+    // `ba` is the least recently used of the two, so it's forgotten first
+    render(&mut cache, "c");
+    render(&mut cache, "ab");
+    assert_eq!(take_calls(), 1);
+    render(&mut cache, "ba");
+    assert_eq!(take_calls(), 1);
+}
+
+#[test]
+fn off_by_default() {
+    let (mut cache, glyphs_calls) = cache();
+    assert_eq!(cache.shaping_cache(), 0);
+
+    render(&mut cache, "ab");
+    render(&mut cache, "ab");
+    assert_eq!(glyphs_calls.load(Ordering::Relaxed), 2);
+}
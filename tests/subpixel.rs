@@ -0,0 +1,32 @@
+//! Rasterizing glyphs shifted by fractions of a pixel
+#![cfg(feature = "rusttype")]
+
+use elefont::FontProvider;
+
+const DEJAVU_SANS: &[u8] = include_bytes!("../examples/DejaVuSans.ttf");
+
+fn assert_phases_differ(font: &dyn FontProvider) {
+    let l = font.single_glyph('l');
+    let still = font.rasterize_phased(l, 0.0, 0.0).unwrap();
+    let shifted = font.rasterize_phased(l, 0.5, 0.0).unwrap();
+    assert_ne!(still, shifted);
+
+    // Each phase's data fits its own bounds
+    for (phase, data) in [(0.0, &still), (0.5, &shifted)] {
+        let bounds = font.bounds_phased(l, phase, 0.0).unwrap();
+        assert_eq!(data.len(), (bounds.width * bounds.height) as usize);
+    }
+}
+
+#[test]
+fn rusttype() {
+    let font = elefont::rusttype_provider::SizedFont::from_bytes(DEJAVU_SANS, 20.0).unwrap();
+    assert_phases_differ(&font);
+}
+
+#[test]
+#[cfg(feature = "ab_glyph")]
+fn ab_glyph() {
+    let font = elefont::abglyph_provider::SizedFont::from_bytes(DEJAVU_SANS, 20.0).unwrap();
+    assert_phases_differ(&font);
+}
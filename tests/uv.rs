@@ -0,0 +1,65 @@
+//! Texture coordinates of cached glyphs
+
+mod common;
+
+use common::block_font;
+use elefont::{Bounds, FontCacheBuilder, Glyph, NullTexture, TextureGlyph};
+
+fn glyph(x: i32, y: i32, width: u32, height: u32) -> TextureGlyph {
+    TextureGlyph {
+        glyph: Glyph(1),
+        page: 0,
+        bounds: Bounds {
+            x,
+            y,
+            width,
+            height,
+        },
+        rotated: false,
+    }
+}
+
+#[test]
+fn uv_divides_by_the_texture_size() {
+    assert_eq!(
+        glyph(16, 32, 8, 4).uv(64, 128),
+        [0.25, 0.25, 0.375, 0.28125]
+    );
+    assert_eq!(glyph(0, 0, 64, 128).uv(64, 128), [0.0, 0.0, 1.0, 1.0]);
+}
+
+#[test]
+fn uv_for_uses_the_cache_texture() {
+    let font = block_font(&[('a', 8, 4), ('b', 4, 8)]);
+    let mut cache = FontCacheBuilder::new(Box::new(font), NullTexture::new(32, 16))
+        .padding(0)
+        .build();
+    let b = cache.font().single_glyph('b');
+    cache.cache_string("a").unwrap();
+
+    // `b` is placed right after `a`
+    let (_, texture_glyph) = cache.render_glyph(b).unwrap();
+    assert_eq!(cache.uv_for(&texture_glyph), [0.25, 0.0, 0.375, 0.5]);
+}
+
+#[test]
+fn inset_uvs_are_inside_the_raw_ones() {
+    let texture_glyph = glyph(16, 32, 8, 4);
+    let raw = texture_glyph.uv(64, 128);
+    let inset = texture_glyph.uv_inset(64, 128, 0.5);
+
+    assert!(inset[0] > raw[0] && inset[1] > raw[1]);
+    assert!(inset[2] < raw[2] && inset[3] < raw[3]);
+    // Each edge moves half a pixel inwards
+    assert_eq!(
+        inset,
+        [16.5 / 64.0, 32.5 / 128.0, 23.5 / 64.0, 35.5 / 128.0]
+    );
+}
+
+#[test]
+fn insets_stop_at_the_middle_of_the_glyph() {
+    let inset = glyph(10, 10, 1, 2).uv_inset(20, 20, 4.0);
+
+    assert_eq!(inset, [0.525, 0.55, 0.525, 0.55]);
+}